@@ -1,13 +1,13 @@
 use clerk_fapi_rs::{
-    clerk::Clerk, configuration::ClerkFapiConfiguration, models::client_sign_in::Status,
+    clerk::Clerk,
+    configuration::ClerkFapiConfiguration,
+    sign_in::{FirstFactor, PrepareFirstFactor, SecondFactor},
 };
 use dotenv::dotenv;
-use std::time::Duration;
 use std::{
     env,
     io::{self, Write},
 };
-use tokio::time::sleep;
 
 fn read_input(prompt: &str) -> String {
     print!("{prompt}");
@@ -51,52 +51,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Email Code flow
             let email = read_input("Please enter your email address: ");
 
-            // Create sign-in attempt
-            let sign_in_response = clerk
-                .get_fapi_client()
-                .create_sign_in(
-                    None,               // origin
-                    Some("email_code"), // strategy
-                    Some(&email),       // identifier
-                    None,               // password
-                    None,               // ticket
-                    None,               // redirect_url
-                    None,               // action_complete_redirect_url
-                    None,               // transfer
-                    None,               // code
-                    None,               // token
-                    None,               // oidc_login_hint
-                    None,               // oidc_prompt
-                )
+            // Create sign-in attempt and send the code
+            let mut sign_in = clerk.sign_in(&email).await?;
+            sign_in
+                .prepare_first_factor(PrepareFirstFactor::EmailCode)
                 .await?;
 
-            let sign_in_id = sign_in_response.id;
-
             println!("We've sent a verification code to your email.");
             println!("Please check your inbox and enter the code below.");
 
             let code = read_input("Enter verification code: ");
 
             // Attempt first factor verification
-            let verification_response = clerk
-                .get_fapi_client()
-                .attempt_sign_in_factor_one(
-                    &sign_in_id,  // sign_in_id
-                    "email_code", // strategy
-                    None,         // origin
-                    Some(&code),  // code
-                    None,         // password
-                    None,         // signature
-                    None,         // token
-                    None,         // ticket
-                    None,         //public_key_credential
-                )
+            sign_in
+                .attempt_first_factor(FirstFactor::EmailCode(code))
                 .await?;
 
-            if verification_response.status == Status::Complete {
+            if sign_in.needs_second_factor() {
+                let code = read_input("Enter authenticator app code: ");
+                sign_in
+                    .attempt_second_factor(SecondFactor::Totp(code))
+                    .await?;
+            }
+
+            if sign_in.is_complete() {
                 println!("Sign in successful!");
             } else {
-                println!("Sign in failed. Status: {:?}", verification_response.status);
+                println!("Sign in failed. Status: {:?}", sign_in.status());
                 return Ok(());
             }
         }
@@ -104,28 +85,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Ticket flow
             let ticket = read_input("Please enter your ticket: ");
 
-            let sign_in_response = clerk
-                .get_fapi_client()
-                .create_sign_in(
-                    None,           // origin
-                    Some("ticket"), // strategy
-                    None,           // identifier
-                    None,           // password
-                    Some(&ticket),  // ticket
-                    None,           // redirect_url
-                    None,           // action_complete_redirect_url
-                    None,           // transfer
-                    None,           // code
-                    None,           // token
-                    None,           // oidc_login_hint
-                    None,           // oidc_prompt
-                )
-                .await?;
+            let sign_in = clerk.sign_in_with_ticket(&ticket).await?;
 
-            if sign_in_response.status == Status::Complete {
+            if sign_in.is_complete() {
                 println!("Sign in successful!");
             } else {
-                println!("Sign in failed. Status: {:?}", sign_in_response.status);
+                println!("Sign in failed. Status: {:?}", sign_in.status());
                 return Ok(());
             }
         }
@@ -135,9 +100,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Give some time for the client to update
-    sleep(Duration::from_millis(500)).await;
-
     // Get and display user information
     if let Some(user) = clerk.user().unwrap() {
         println!("\nUser Information:");
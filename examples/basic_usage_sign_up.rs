@@ -1,13 +1,13 @@
 use clerk_fapi_rs::{
-    clerk::Clerk, configuration::ClerkFapiConfiguration, models::client_sign_up::Status,
+    clerk::Clerk,
+    configuration::ClerkFapiConfiguration,
+    sign_up::{PrepareVerification, SignUpField, SignUpParams, Verification},
 };
 use dotenv::dotenv;
-use std::time::Duration;
 use std::{
     env,
     io::{self, Write},
 };
-use tokio::time::sleep;
 
 fn read_input(prompt: &str) -> String {
     print!("{prompt}");
@@ -43,66 +43,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let email = read_input("Please enter your email address: ");
 
-    // Create sign-in attempt
-    let sign_up_response = clerk
-        .get_fapi_client()
-        .create_sign_ups(
-            None,               // origin
-            None,               // transfer,
-            None,               // password
-            None,               // first_name
-            None,               // last_name
-            None,               // username
-            Some(&email),       // email_address
-            None,               // phone_number
-            None,               // email_address_or_phone_number
-            None,               // unsafe_metadata
-            Some("email_code"), // strategy
-            None,               // action_complete_redirect_url
-            None,               // redirect_url
-            None,               // ticket
-            None,               // web3_wallet
-            None,               // token
-            None,               // code
-            None,               // captcha_token
-            None,               // captcha_error
-            None,               // captcha_widget_type
-            Some(true),         // legal_accepted,
-            None,               // oidc_login_hint
-            None,               // oidc_prompt
-        )
-        .await?;
+    let mut params = SignUpParams::default()
+        .email_address(email)
+        .legal_accepted(true);
+
+    // Ask for the rest of the fields the instance requires
+    for field in clerk.sign_up_requirements()?.missing(&params) {
+        let value = read_input(&format!("Please enter your {field}: "));
+        params = match field {
+            SignUpField::PhoneNumber => params.phone_number(value),
+            SignUpField::Username => params.username(value),
+            SignUpField::FirstName => params.first_name(value),
+            SignUpField::LastName => params.last_name(value),
+            SignUpField::Password => params.password(value),
+            _ => params,
+        };
+    }
 
-    let sign_up_id = sign_up_response.id;
+    // Create sign-up attempt and send the code
+    let mut sign_up = clerk.sign_up(params).await?;
+    sign_up
+        .prepare_verification(PrepareVerification::EmailCode)
+        .await?;
 
     println!("We've sent a verification code to your email.");
     println!("Please check your inbox and enter the code below.");
 
     let code = read_input("Enter verification code: ");
 
-    // Attempt first factor verification
-    let verification_response = clerk
-        .get_fapi_client()
-        .attempt_sign_ups_verification(
-            &sign_up_id,        // sign_up_id
-            None,               // origin,
-            Some("email_code"), // strategy
-            Some(&code),        // code
-            None,               // signarure
-            None,               // token
-        )
+    // Attempt email verification
+    sign_up
+        .attempt_verification(Verification::EmailCode(code))
         .await?;
 
-    if verification_response.status == Status::Complete {
+    if sign_up.is_complete() {
         println!("Sign up successful!");
     } else {
-        println!("Sign up failed. Status: {:?}", verification_response.status);
+        println!("Sign up failed. Status: {:?}", sign_up.status());
+        println!("Missing fields: {:?}", sign_up.missing_fields());
         return Ok(());
     }
 
-    // Give some time for the client to update
-    sleep(Duration::from_millis(500)).await;
-
     // Get and display user information
     if let Some(user) = clerk.user().unwrap() {
         println!("\nUser Information:");
@@ -1,11 +1,13 @@
-use clerk_fapi_rs::{models::ClientUser, Clerk, ClerkFapiConfiguration};
+use clerk_fapi_rs::{events::ClerkEvent, models::ClientUser, Clerk, ClerkFapiConfiguration};
 use dioxus::{logger::tracing::info, prelude::*};
+use futures::StreamExt;
 
 // Get the Clerk publishable key from environment
 pub const CLERK_PUBLISHABLE_KEY: &str = env!("CLERK_PUBLISHABLE_KEY");
 
 /// State for Clerk authentication
 #[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)]
 pub enum ClerkStatus {
     Loading,
     SignedIn(ClientUser),
@@ -39,11 +41,7 @@ pub fn ClerkProvider(children: Element) -> Element {
         status: clerk_status.read().clone(),
     });
 
-    // Set up callback to update status when auth state changes
-    // Note: Using callbacks with Dioxus signals would require a thread-safe storage
-    // which is more complex. For this example, we'll rely on manual refresh.
-
-    // Initialize Clerk client
+    // Initialize Clerk client and follow the auth state changes
     {
         let client = clerk_client.clone();
 
@@ -51,19 +49,29 @@ pub fn ClerkProvider(children: Element) -> Element {
             to_owned![client, clerk_status];
 
             spawn(async move {
+                // Subscribe before loading to not miss the first change
+                let mut events = client.subscribe();
+
                 // Status is already set to Loading by default
-                match client.load().await {
-                    Ok(_) => client.add_listener(move |_client, _session, user, _org| {
-                        to_owned![clerk_status];
-                        info!("Got user {:?}", user);
-                        if let Some(user) = user {
-                            clerk_status.set(ClerkStatus::SignedIn(user));
-                        } else {
-                            clerk_status.set(ClerkStatus::SignedOut);
-                        }
-                    }),
-                    Err(e) => {
-                        clerk_status.set(ClerkStatus::Error(e.to_string()));
+                if let Err(e) = client.load().await {
+                    clerk_status.set(ClerkStatus::Error(e.to_string()));
+                    return;
+                }
+                match client.user() {
+                    Ok(Some(user)) => clerk_status.set(ClerkStatus::SignedIn(user)),
+                    _ => clerk_status.set(ClerkStatus::SignedOut),
+                }
+
+                while let Some(event) = events.next().await {
+                    info!("Got event {:?}", event);
+                    match event {
+                        ClerkEvent::SignedIn { user, .. }
+                        | ClerkEvent::UserUpdated { user }
+                        | ClerkEvent::SessionChanged {
+                            user: Some(user), ..
+                        } => clerk_status.set(ClerkStatus::SignedIn(user)),
+                        ClerkEvent::SignedOut { .. } => clerk_status.set(ClerkStatus::SignedOut),
+                        _ => {}
                     }
                 }
             });
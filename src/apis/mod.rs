@@ -61,6 +61,15 @@ impl<T> From<std::io::Error> for Error<T> {
     }
 }
 
+impl<T> From<crate::clerk_http_client::ClerkHttpError> for Error<T> {
+    fn from(e: crate::clerk_http_client::ClerkHttpError) -> Self {
+        match e {
+            crate::clerk_http_client::ClerkHttpError::Reqwest(e) => Error::Reqwest(e),
+            crate::clerk_http_client::ClerkHttpError::Middleware(e) => Error::Middleware(e),
+        }
+    }
+}
+
 impl<T> From<anyhow::Error> for Error<T> {
     fn from(e: anyhow::Error) -> Self {
         Error::Middleware(e)
@@ -465,9 +465,11 @@ pub async fn create_sign_in(
 pub async fn get_sign_in(
     configuration: &configuration::Configuration,
     sign_in_id: &str,
+    rotating_token_nonce: Option<&str>,
 ) -> Result<models::ClientClientWrappedSignIn, Error<GetSignInError>> {
     // add a prefix to parameters to efficiently prevent name collisions
     let p_path_sign_in_id = sign_in_id;
+    let p_query_rotating_token_nonce = rotating_token_nonce;
 
     let uri_str = format!(
         "{}/v1/client/sign_ins/{sign_in_id}",
@@ -476,6 +478,9 @@ pub async fn get_sign_in(
     );
     let mut req_builder = configuration.client.request(reqwest::Method::GET, &uri_str);
 
+    if let Some(ref param_value) = p_query_rotating_token_nonce {
+        req_builder = req_builder.query(&[("rotating_token_nonce", &param_value.to_string())]);
+    }
     if let Some(ref apikey) = configuration.api_key {
         let key = apikey.key.clone();
         let value = match apikey.prefix {
@@ -343,9 +343,11 @@ pub async fn create_sign_ups(
 pub async fn get_sign_ups(
     configuration: &configuration::Configuration,
     sign_up_id: &str,
+    rotating_token_nonce: Option<&str>,
 ) -> Result<models::ClientClientWrappedSignUp, Error<GetSignUpsError>> {
     // add a prefix to parameters to efficiently prevent name collisions
     let p_path_sign_up_id = sign_up_id;
+    let p_query_rotating_token_nonce = rotating_token_nonce;
 
     let uri_str = format!(
         "{}/v1/client/sign_ups/{sign_up_id}",
@@ -354,6 +356,9 @@ pub async fn get_sign_ups(
     );
     let mut req_builder = configuration.client.request(reqwest::Method::GET, &uri_str);
 
+    if let Some(ref param_value) = p_query_rotating_token_nonce {
+        req_builder = req_builder.query(&[("rotating_token_nonce", &param_value.to_string())]);
+    }
     if let Some(ref apikey) = configuration.api_key {
         let key = apikey.key.clone();
         let value = match apikey.prefix {
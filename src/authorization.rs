@@ -0,0 +1,241 @@
+use crate::jwt::{unverified_claims, SessionClaims};
+use crate::models::{
+    ClientOrganization as Organization, ClientSession as Session, ClientUser as User,
+};
+use crate::reverification::ReverificationPolicy;
+
+/// Conditions checked by `Clerk::has` and `SessionClaims::has`
+///
+/// All the set conditions must hold. Roles and permissions are checked
+/// against the active organization and can be given with or without the
+/// `org:` prefix. Features can be scoped with `org:` or `user:`, without a
+/// scope either matches.
+///
+/// # Examples
+/// ```
+/// # fn example(clerk: clerk_fapi_rs::clerk::Clerk) {
+/// use clerk_fapi_rs::authorization::AuthCheck;
+/// use clerk_fapi_rs::reverification::ReverificationPolicy;
+///
+/// let can_delete = clerk.has(
+///     &AuthCheck::new()
+///         .permission("org:invoices:delete")
+///         .reverification(ReverificationPolicy::STRICT),
+/// );
+/// let is_admin = clerk.has(&AuthCheck::new().role("admin"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthCheck {
+    pub role: Option<String>,
+    pub permission: Option<String>,
+    pub feature: Option<String>,
+    pub reverification: Option<ReverificationPolicy>,
+}
+
+impl AuthCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    pub fn permission(mut self, permission: impl Into<String>) -> Self {
+        self.permission = Some(permission.into());
+        self
+    }
+
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.feature = Some(feature.into());
+        self
+    }
+
+    pub fn reverification(mut self, policy: ReverificationPolicy) -> Self {
+        self.reverification = Some(policy);
+        self
+    }
+}
+
+/// What the checks are evaluated against, gathered either from the Clerk
+/// state or from session token claims
+#[derive(Debug, Default)]
+pub(crate) struct AuthContext<'a> {
+    pub user_id: Option<&'a str>,
+    pub org_role: Option<&'a str>,
+    pub org_permissions: Option<&'a [String]>,
+    /// Features in the `fea` claim format, example `o:dashboard,u:profile`
+    pub features: Option<&'a str>,
+    pub factor_verification_age: Option<&'a [i64]>,
+}
+
+impl AuthContext<'_> {
+    pub fn check(&self, check: &AuthCheck) -> bool {
+        if self.user_id.is_none() {
+            return false;
+        }
+        if let Some(role) = &check.role {
+            if self.org_role.map(with_org_prefix) != Some(with_org_prefix(role)) {
+                return false;
+            }
+        }
+        if let Some(permission) = &check.permission {
+            let permission = with_org_prefix(permission);
+            if !self
+                .org_permissions
+                .is_some_and(|p| p.contains(&permission))
+            {
+                return false;
+            }
+        }
+        if let Some(feature) = &check.feature {
+            if !has_feature(self.features.unwrap_or_default(), feature) {
+                return false;
+            }
+        }
+        if let Some(policy) = &check.reverification {
+            if !self
+                .factor_verification_age
+                .is_some_and(|fva| policy.is_satisfied(fva))
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+pub(crate) fn with_org_prefix(value: &str) -> String {
+    if value.starts_with("org:") {
+        value.to_string()
+    } else {
+        format!("org:{value}")
+    }
+}
+
+/// Whether the `fea` claim contains the feature, the claim scopes each
+/// feature with `o` for organization, `u` for user or both
+fn has_feature(claim: &str, feature: &str) -> bool {
+    let (scope, name) = match feature.split_once(':') {
+        Some(("org", name)) => (Some('o'), name),
+        Some(("user", name)) => (Some('u'), name),
+        _ => (None, feature),
+    };
+    claim
+        .split(',')
+        .filter_map(|f| f.split_once(':'))
+        .any(|(scopes, f)| f == name && scope.is_none_or(|s| scopes.contains(s)))
+}
+
+/// Evaluates the check against the state unpacked by `ClerkState`
+pub(crate) fn check_state(
+    check: &AuthCheck,
+    session: Option<&Session>,
+    user: Option<&User>,
+    organization: Option<&Organization>,
+) -> bool {
+    let membership = organization.and_then(|org| {
+        user?
+            .organization_memberships
+            .as_ref()?
+            .iter()
+            .find(|m| m.organization.id == org.id)
+    });
+    // Features are only available in the session token
+    let claims = session
+        .and_then(|s| s.last_active_token.clone().flatten())
+        .and_then(|token| unverified_claims(&token.jwt));
+    AuthContext {
+        user_id: user.map(|u| u.id.as_str()),
+        org_role: membership.map(|m| m.role.as_str()),
+        org_permissions: membership.and_then(|m| m.permissions.as_deref()),
+        features: claims.as_ref().and_then(|c| c.fea.as_deref()),
+        factor_verification_age: session.map(|s| s.factor_verification_age.as_slice()),
+    }
+    .check(check)
+}
+
+impl SessionClaims {
+    /// Evaluates the check against the claims, use with verified tokens,
+    /// example from `Clerk::verify_token`
+    pub fn has(&self, check: &AuthCheck) -> bool {
+        AuthContext {
+            user_id: Some(&self.sub),
+            org_role: self.org_role.as_deref(),
+            org_permissions: self.org_permissions.as_deref(),
+            features: self.fea.as_deref(),
+            factor_verification_age: self.fva.as_deref(),
+        }
+        .check(check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reverification::ReverificationLevel;
+    use std::time::Duration;
+
+    fn claims(value: serde_json::Value) -> SessionClaims {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_claims_checks() {
+        let claims = claims(serde_json::json!({
+            "sub": "user_1",
+            "iss": "https://clerk.example.com",
+            "exp": 2,
+            "iat": 1,
+            "org_id": "org_1",
+            "org_role": "org:admin",
+            "org_permissions": ["org:invoices:read", "org:invoices:delete"],
+            "fea": "o:dashboard,u:profile,uo:export",
+            "fva": [5, 30]
+        }));
+
+        assert!(claims.has(&AuthCheck::new()));
+        assert!(claims.has(&AuthCheck::new().role("admin")));
+        assert!(claims.has(&AuthCheck::new().role("org:admin")));
+        assert!(!claims.has(&AuthCheck::new().role("member")));
+        assert!(claims.has(&AuthCheck::new().permission("invoices:delete")));
+        assert!(!claims.has(&AuthCheck::new().permission("org:invoices:create")));
+
+        assert!(claims.has(&AuthCheck::new().feature("dashboard")));
+        assert!(claims.has(&AuthCheck::new().feature("org:dashboard")));
+        assert!(!claims.has(&AuthCheck::new().feature("user:dashboard")));
+        assert!(claims.has(&AuthCheck::new().feature("user:export")));
+        assert!(!claims.has(&AuthCheck::new().feature("reports")));
+
+        assert!(claims.has(&AuthCheck::new().reverification(ReverificationPolicy::MODERATE)));
+        assert!(!claims.has(&AuthCheck::new().reverification(ReverificationPolicy::STRICT)));
+        assert!(
+            claims.has(&AuthCheck::new().reverification(ReverificationPolicy {
+                level: ReverificationLevel::FirstFactor,
+                max_age: Duration::from_secs(600),
+            }))
+        );
+
+        // All conditions must hold
+        assert!(!claims.has(
+            &AuthCheck::new()
+                .permission("invoices:delete")
+                .reverification(ReverificationPolicy::STRICT)
+        ));
+    }
+
+    #[test]
+    fn test_checks_without_organization() {
+        let claims = claims(serde_json::json!({
+            "sub": "user_1",
+            "iss": "https://clerk.example.com",
+            "exp": 2,
+            "iat": 1
+        }));
+        assert!(!claims.has(&AuthCheck::new().role("admin")));
+        assert!(!claims.has(&AuthCheck::new().permission("invoices:read")));
+        assert!(!claims.has(&AuthCheck::new().reverification(ReverificationPolicy::LAX)));
+    }
+}
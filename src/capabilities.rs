@@ -0,0 +1,241 @@
+use crate::models::user_settings_sign_up::Mode;
+use crate::models::{
+    ClientEnvironment, UserSettingsAttribute, UserSettingsAttributes, UserSettingsSocial,
+};
+
+/// Social login provider enabled on the instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocialProvider {
+    /// Strategy to pass to `Clerk::sign_in_with_oauth`, example `oauth_google`
+    pub strategy: String,
+    /// Human readable name, example `Google`
+    pub name: String,
+    pub logo_url: Option<String>,
+}
+
+/// Typed view of what the instance supports, read from `ClientEnvironment`
+///
+/// Settings missing from the environment read as disabled, so the view can
+/// be used to decide what to render without digging through the `Option`s.
+///
+/// # Examples
+/// ```
+/// # fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// let capabilities = clerk.capabilities()?;
+/// if capabilities.is_in_maintenance() {
+///     return Ok(());
+/// }
+/// for provider in capabilities.enabled_social_providers() {
+///     println!("Continue with {}", provider.name);
+/// }
+/// let show_password = capabilities
+///     .enabled_first_factors()
+///     .iter()
+///     .any(|f| f == "password");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    environment: ClientEnvironment,
+}
+
+impl Capabilities {
+    pub fn new(environment: ClientEnvironment) -> Self {
+        Self { environment }
+    }
+
+    /// The environment the view reads from
+    pub fn environment(&self) -> &ClientEnvironment {
+        &self.environment
+    }
+
+    fn attributes(&self) -> Option<&UserSettingsAttributes> {
+        self.environment
+            .user_settings
+            .as_ref()
+            .map(|s| s.attributes.as_ref())
+    }
+
+    /// Strategies of the enabled attributes
+    fn strategies<'a>(
+        attributes: &'a UserSettingsAttributes,
+        strategies: impl Fn(&'a UserSettingsAttribute) -> Option<&'a Vec<String>>,
+    ) -> Vec<String> {
+        let all: [&UserSettingsAttribute; 11] = [
+            &attributes.email_address,
+            &attributes.phone_number,
+            &attributes.username,
+            &attributes.web3_wallet,
+            &attributes.first_name,
+            &attributes.last_name,
+            &attributes.password,
+            &attributes.authenticator_app,
+            &attributes.ticket,
+            &attributes.backup_code,
+            &attributes.passkey,
+        ];
+        let mut unique: Vec<String> = Vec::new();
+        for strategy in all
+            .into_iter()
+            .filter(|a| a.enabled)
+            .filter_map(strategies)
+            .flatten()
+        {
+            if !unique.contains(strategy) {
+                unique.push(strategy.clone());
+            }
+        }
+        unique
+    }
+
+    /// First factor strategies the user can sign in with, example
+    /// `email_code`, `password` or `passkey`
+    ///
+    /// Social login is not included, see `enabled_social_providers`. Falls
+    /// back to `ClientAuthConfig` on environments without user settings.
+    pub fn enabled_first_factors(&self) -> Vec<String> {
+        match self.attributes() {
+            Some(attributes) => {
+                let mut factors = Self::strategies(attributes, |a| {
+                    a.used_for_first_factor.then_some(&a.first_factors)
+                });
+                // The password attribute doesn't list itself as a factor
+                let password = "password".to_string();
+                if attributes.password.enabled && !factors.contains(&password) {
+                    factors.push(password);
+                }
+                factors
+            }
+            None => self
+                .environment
+                .auth_config
+                .as_ref()
+                .map(|c| c.first_factors.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Second factor strategies the user can enroll, example `totp` or
+    /// `backup_code`
+    pub fn enabled_second_factors(&self) -> Vec<String> {
+        match self.attributes() {
+            Some(attributes) => Self::strategies(attributes, |a| {
+                a.used_for_second_factor.then_some(&a.second_factors)
+            }),
+            None => self
+                .environment
+                .auth_config
+                .as_ref()
+                .map(|c| c.second_factors.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Social providers that can be used to sign in, the ones that are only
+    /// connectable to an existing account or hidden from the sign in
+    /// buttons are left out
+    pub fn enabled_social_providers(&self) -> Vec<SocialProvider> {
+        let Some(user_settings) = &self.environment.user_settings else {
+            return Vec::new();
+        };
+        let social = &user_settings.social;
+        let providers: [&Option<Box<UserSettingsSocial>>; 31] = [
+            &social.oauth_apple,
+            &social.oauth_atlassian,
+            &social.oauth_bitbucket,
+            &social.oauth_box,
+            &social.oauth_coinbase,
+            &social.oauth_custom_mock,
+            &social.oauth_discord,
+            &social.oauth_dropbox,
+            &social.oauth_enstall,
+            &social.oauth_expressen,
+            &social.oauth_facebook,
+            &social.oauth_github,
+            &social.oauth_gitlab,
+            &social.oauth_google,
+            &social.oauth_hubspot,
+            &social.oauth_huggingface,
+            &social.oauth_instagram,
+            &social.oauth_line,
+            &social.oauth_linear,
+            &social.oauth_linkedin,
+            &social.oauth_linkedin_oidc,
+            &social.oauth_microsoft,
+            &social.oauth_mock,
+            &social.oauth_notion,
+            &social.oauth_slack,
+            &social.oauth_spotify,
+            &social.oauth_tiktok,
+            &social.oauth_twitch,
+            &social.oauth_twitter,
+            &social.oauth_x,
+            &social.oauth_xero,
+        ];
+        providers
+            .into_iter()
+            .flatten()
+            .filter(|s| s.enabled && s.authenticatable && !s.not_selectable)
+            .map(|s| SocialProvider {
+                strategy: s.strategy.clone(),
+                name: s.name.clone().unwrap_or_else(|| {
+                    s.strategy
+                        .strip_prefix("oauth_")
+                        .unwrap_or(&s.strategy)
+                        .to_string()
+                }),
+                logo_url: s.logo_url.clone().flatten(),
+            })
+            .collect()
+    }
+
+    /// Whether every user must have a second factor
+    pub fn requires_mfa(&self) -> bool {
+        self.environment
+            .user_settings
+            .as_ref()
+            .is_some_and(|s| s.sign_in.second_factor.required)
+    }
+
+    pub fn organizations_enabled(&self) -> bool {
+        self.environment
+            .organization_settings
+            .as_ref()
+            .is_some_and(|s| s.enabled)
+    }
+
+    /// Whether users can register and sign in with passkeys
+    pub fn passkeys_enabled(&self) -> bool {
+        match &self.environment.user_settings {
+            Some(user_settings) => user_settings.attributes.passkey.enabled,
+            None => self
+                .environment
+                .auth_config
+                .as_ref()
+                .is_some_and(|c| c.first_factors.iter().any(|f| f == "passkey")),
+        }
+    }
+
+    /// Whether sign ups go through the waitlist instead of creating users
+    pub fn waitlist_mode(&self) -> bool {
+        self.environment
+            .user_settings
+            .as_ref()
+            .is_some_and(|s| s.sign_up.mode == Mode::Waitlist)
+    }
+
+    /// Whether only one signed in session is allowed per client
+    pub fn single_session_mode(&self) -> bool {
+        self.environment
+            .auth_config
+            .as_ref()
+            .is_some_and(|c| c.single_session_mode)
+    }
+
+    /// Whether Clerk has the instance in maintenance mode, during which
+    /// some requests, example sign ups, can fail
+    pub fn is_in_maintenance(&self) -> bool {
+        self.environment.maintenance_mode.unwrap_or(false)
+    }
+}
@@ -1,24 +1,55 @@
 use crate::apis::configuration::Configuration as ApiConfiguration;
+use crate::authorization::{check_state, AuthCheck};
+use crate::capabilities::Capabilities;
 use crate::clerk_fapi::ClerkFapiClient;
-use crate::clerk_state::{ClerkNotLoadedError, ClerkState};
+use crate::clerk_state::{
+    now_millis, ClerkNotLoadedError, ClerkState, CLIENT_STORED_AT, DEV_BROWSER_TOKEN_ID,
+    ENVIRONMENT_STORED_AT,
+};
 use crate::configuration::{ClerkFapiConfiguration, ClientKind};
+use crate::email_link::{ClerkEmailLinkError, EmailLinkFlow};
+use crate::error::ClerkApiError;
+use crate::events::{ClerkEvent, ClerkSubscription, EventHub};
+use crate::jwt::{JwtVerificationError, JwtVerifier, JwtVerifierOptions, SessionClaims};
 use crate::models::{
     ClientClient as Client, ClientClientWrappedOrganizationMembershipsResponse,
-    ClientEnvironment as Environment, ClientOrganization as Organization,
-    ClientOrganizationMembership, ClientSession as Session, ClientUser as User,
+    ClientEnvironment as Environment, ClientOrganization as Organization, ClientOrganizationDomain,
+    ClientOrganizationInvitation, ClientOrganizationInvitationUserContext,
+    ClientOrganizationMembership, ClientOrganizationMembershipRequest,
+    ClientOrganizationSuggestion, ClientPasskey, ClientRole, ClientSession as Session,
+    ClientUser as User, ClientWeb3Wallet,
+};
+use crate::oauth::{ClerkOAuthError, OAuthFlow, OAuthRedirect};
+use crate::organization_admin::OrganizationAdmin;
+use crate::organizations::{self, ClerkOrganizationError};
+use crate::pagination::{Page, Paginator};
+use crate::passkey::{ClerkPasskeyError, PasskeyAuthenticator};
+use crate::reverification::{
+    ClerkReverificationError, ReverificationFlow, ReverificationLevel, ReverificationPolicy,
 };
+use crate::sessions::{self, ClerkSessionError, SessionInfo};
+use crate::sign_in::{ClerkSignInError, SignInFlow};
+use crate::sign_up::{ClerkSignUpError, SignUpFlow, SignUpParams, SignUpRequirements};
+use crate::token_cache::{TokenCache, TokenCacheKey, TOKEN_EXPIRY_LEEWAY};
+use crate::totp::{ClerkTotpError, TotpEnrollment};
 use crate::utils::{
     find_organization_id_from_memberships, find_target_organization, find_target_session,
     ClerkOrgFindingError, ClerkSessionFindingError,
 };
+use crate::validation::Validator;
+use crate::web3::{ClerkWeb3Error, Web3Signer};
 use futures::TryFutureExt;
+use futures_timer::Delay;
 use log::{error, warn};
 use parking_lot::{RwLock, RwLockWriteGuard};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub type Listener =
     Arc<dyn Fn(Client, Option<Session>, Option<User>, Option<Organization>) + Send + Sync>;
@@ -30,33 +61,121 @@ pub struct Clerk {
     state: Arc<RwLock<ClerkState>>,
     api_client: Arc<ClerkFapiClient>,
     listeners: Arc<RwLock<Vec<Listener>>>,
+    jwt_verifier: Arc<JwtVerifier>,
+    token_cache: Arc<TokenCache>,
+    events: Arc<EventHub>,
+    dev_browser_ready: Arc<AtomicBool>,
 }
 
+/// Loading failed, with the Clerk API error when the value couldn't be
+/// fetched and the store didn't have it either
 #[derive(Debug)]
 pub enum ClerkLoadError {
-    DevFailedToLoadDevBrowser,
-    FailedToLoadEnv,
-    FailedToLoadClient,
+    DevFailedToLoadDevBrowser(ClerkApiError),
+    FailedToLoadEnv(Option<ClerkApiError>),
+    FailedToLoadClient(Option<ClerkApiError>),
 }
 impl fmt::Display for ClerkLoadError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            ClerkLoadError::FailedToLoadEnv => write!(f, "Failed to load Clerk environment"),
-            ClerkLoadError::FailedToLoadClient => write!(f, "Failed to load Clerk client"),
-            ClerkLoadError::DevFailedToLoadDevBrowser => {
-                write!(f, "Failed to load development browser")
+            ClerkLoadError::FailedToLoadEnv(None) => write!(f, "Failed to load Clerk environment"),
+            ClerkLoadError::FailedToLoadEnv(Some(e)) => {
+                write!(f, "Failed to load Clerk environment: {e}")
+            }
+            ClerkLoadError::FailedToLoadClient(None) => write!(f, "Failed to load Clerk client"),
+            ClerkLoadError::FailedToLoadClient(Some(e)) => {
+                write!(f, "Failed to load Clerk client: {e}")
+            }
+            ClerkLoadError::DevFailedToLoadDevBrowser(e) => {
+                write!(f, "Failed to load development browser: {e}")
             }
         }
     }
 }
 impl Error for ClerkLoadError {}
 
+impl ClerkLoadError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkLoadError::DevFailedToLoadDevBrowser(e) => Some(e),
+            ClerkLoadError::FailedToLoadEnv(e) | ClerkLoadError::FailedToLoadClient(e) => {
+                e.as_ref()
+            }
+        }
+    }
+}
+
+/// Holds the listener emits while alive, on drop emits once if the state
+/// changed meanwhile, see `Clerk::hold_emits`
+pub(crate) struct EmitHold {
+    state: Arc<RwLock<ClerkState>>,
+}
+
+impl Drop for EmitHold {
+    fn drop(&mut self) {
+        let state = self.state.read();
+        if state.release_emits() {
+            state.emit_state();
+        }
+    }
+}
+
+/// How `Clerk::load_with` picks between the API and the stored values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadStrategy {
+    /// Loads from the API, falls back to the stored values if it fails
+    #[default]
+    NetworkFirst,
+    /// Uses the stored values if they were stored within `max_age`,
+    /// otherwise loads like `NetworkFirst`
+    CacheFirst { max_age: Duration },
+    /// Uses the stored values of any age and returns the revalidation in
+    /// `Loaded::revalidation`, which fetches the fresh values and calls the
+    /// listeners. Loads like `NetworkFirst` if nothing is stored
+    CacheThenRevalidate,
+    /// Uses only the stored values, never calls the API
+    CacheOnly,
+}
+
+/// Where `Clerk::load_with` got the Environment and Client from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadSource {
+    Network,
+    /// At least one of them came from the store
+    Cache,
+}
+
+/// Future fetching the fresh Environment and Client, not required to be
+/// `Send` on wasm32
+#[cfg(not(target_arch = "wasm32"))]
+pub type RevalidationFuture = Pin<Box<dyn Future<Output = Result<(), ClerkLoadError>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type RevalidationFuture = Pin<Box<dyn Future<Output = Result<(), ClerkLoadError>>>>;
+
+/// Outcome of `Clerk::load_with`
+pub struct Loaded {
+    pub source: LoadSource,
+    /// Set when `LoadStrategy::CacheThenRevalidate` loaded from the store,
+    /// spawn it on the runtime of your choice
+    pub revalidation: Option<RevalidationFuture>,
+}
+
+impl fmt::Debug for Loaded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Loaded")
+            .field("source", &self.source)
+            .field("revalidation", &self.revalidation.is_some())
+            .finish()
+    }
+}
+
 #[derive(Debug)]
 pub enum ClerkSetActiveError {
     ClerkNotLoadedError(ClerkNotLoadedError),
     ClerkOrgFindingError(ClerkOrgFindingError),
     ClerkSessionFindingError(ClerkSessionFindingError),
-    ClerkApiError,
+    ClerkApiError(ClerkApiError),
 }
 impl fmt::Display for ClerkSetActiveError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -64,27 +183,50 @@ impl fmt::Display for ClerkSetActiveError {
             ClerkSetActiveError::ClerkNotLoadedError(e) => e.fmt(f),
             ClerkSetActiveError::ClerkOrgFindingError(e) => e.fmt(f),
             ClerkSetActiveError::ClerkSessionFindingError(e) => e.fmt(f),
-            ClerkSetActiveError::ClerkApiError => write!(f, "Error calling Clerk API"),
+            ClerkSetActiveError::ClerkApiError(e) => e.fmt(f),
         }
     }
 }
 impl Error for ClerkSetActiveError {}
 
-#[derive(Debug)]
+impl ClerkSetActiveError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkSetActiveError::ClerkApiError(e) => Some(e),
+            ClerkSetActiveError::ClerkOrgFindingError(ClerkOrgFindingError::ClerkApiError(e)) => {
+                Some(e)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum ClerkGetTokenError {
     ClerkNotLoadedError(ClerkNotLoadedError),
-    ClerkApiError,
+    ClerkApiError(ClerkApiError),
 }
 impl fmt::Display for ClerkGetTokenError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             ClerkGetTokenError::ClerkNotLoadedError(e) => e.fmt(f),
-            ClerkGetTokenError::ClerkApiError => write!(f, "Error in Clerk API"),
+            ClerkGetTokenError::ClerkApiError(e) => e.fmt(f),
         }
     }
 }
 impl Error for ClerkGetTokenError {}
 
+impl ClerkGetTokenError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkGetTokenError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl Clerk {
     /// Creates a new Clerk client with the provided configuration
     ///
@@ -94,10 +236,14 @@ impl Clerk {
     pub fn new(config: ClerkFapiConfiguration) -> Self {
         let listeners = Arc::new(RwLock::new(Vec::<Listener>::new()));
 
+        let events = Arc::new(EventHub::default());
+
         let listeners_inner = listeners.clone();
+        let events_inner = events.clone();
         let state = Arc::new(RwLock::new(ClerkState::new(
             config.clone(),
             move |client, session, user, organization| {
+                events_inner.on_state(session.as_ref(), user.as_ref(), organization.as_ref());
                 let cbs = { listeners_inner.read() };
                 for cb in cbs.iter() {
                     cb(
@@ -112,11 +258,23 @@ impl Clerk {
 
         let api_client = Arc::new(ClerkFapiClient::new(config.clone(), state.clone()).unwrap());
 
+        let jwt_verifier = Arc::new(JwtVerifier::new(
+            api_client.clone(),
+            JwtVerifierOptions {
+                issuer: Some(config.issuer().to_string()),
+                ..Default::default()
+            },
+        ));
+
         Self {
             config: Arc::new(config),
             state,
             api_client,
             listeners,
+            jwt_verifier,
+            token_cache: Arc::new(TokenCache::default()),
+            events,
+            dev_browser_ready: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -140,7 +298,7 @@ impl Clerk {
     async fn load_environment_from_api(&self) -> Result<Environment, ClerkLoadError> {
         self.api_client.get_environment().await.map_err(|e| {
             error!("Clerk: Failed to load environment from API: {e}");
-            ClerkLoadError::FailedToLoadEnv
+            ClerkLoadError::FailedToLoadEnv(Some(e.into()))
         })
     }
 
@@ -163,42 +321,199 @@ impl Clerk {
             .await
             .map_err(|e| {
                 error!("Clerk: Failed to load client from API: {e}");
-                println!("Clerk: Failed to load client from API: {e}");
-                ClerkLoadError::FailedToLoadClient
+                ClerkLoadError::FailedToLoadClient(Some(e.into()))
             })?
-            .ok_or(ClerkLoadError::FailedToLoadClient)
+            .ok_or(ClerkLoadError::FailedToLoadClient(None))
     }
 
-    /// Initializes Clerk, tries to pull Environment and Client from API
-    /// in case that fails tries to pull them from cache. Example if
-    /// there wasn't internet connection
-    pub async fn load(&self) -> Result<(), ClerkLoadError> {
-        if self.config.is_development() && self.config.kind == ClientKind::Browser {
-            let dev_browser = self
-                .api_client
-                .create_dev_browser()
-                .await
-                .map_err(|_| ClerkLoadError::DevFailedToLoadDevBrowser)?;
-            self.api_client.set_dev_browser_token_id(dev_browser.id);
-        }
+    /// Whether the value behind the time key was stored within `max_age`,
+    /// values without a stored time count as stale
+    fn is_fresh_in_cache(&self, stored_at_key: &str, max_age: Duration) -> bool {
+        self.config
+            .get_store_value(stored_at_key)
+            .and_then(|v| v.as_i64())
+            .is_some_and(|stored_at| {
+                let age = now_millis().saturating_sub(stored_at).max(0) as u128;
+                age <= max_age.as_millis()
+            })
+    }
 
-        let mut environment = self.load_environment_from_api().await.ok();
-        let mut client = self.load_client_from_api().await.ok();
+    fn load_from_cache(&self, max_age: Duration) -> Option<(Environment, Client)> {
+        if !self.is_fresh_in_cache(ENVIRONMENT_STORED_AT, max_age)
+            || !self.is_fresh_in_cache(CLIENT_STORED_AT, max_age)
+        {
+            return None;
+        }
+        // The stored client can't be used without its dev browser
+        if !self.restore_dev_browser() {
+            return None;
+        }
+        Some((
+            self.load_environment_from_cache()?,
+            self.load_client_from_cache()?,
+        ))
+    }
 
-        if environment.is_none() {
-            environment = self.load_environment_from_cache();
+    /// Sets the stored dev browser to the requests when one is needed,
+    /// returns false when it's needed but the store doesn't have it
+    fn restore_dev_browser(&self) -> bool {
+        if !self.config.is_development()
+            || self.config.kind != ClientKind::Browser
+            || self.dev_browser_ready.load(Ordering::SeqCst)
+        {
+            return true;
         }
+        let Some(token_id) = self
+            .config
+            .get_store_value(DEV_BROWSER_TOKEN_ID)
+            .and_then(|v| v.as_str().map(str::to_string))
+        else {
+            return false;
+        };
+        self.api_client.set_dev_browser_token_id(token_id);
+        self.dev_browser_ready.store(true, Ordering::SeqCst);
+        true
+    }
 
-        if client.is_none() {
-            client = self.load_client_from_cache();
+    /// Restores the stored dev browser or creates and stores a new one,
+    /// so the stored client stays tied to the same dev browser
+    async fn ensure_dev_browser(&self) -> Result<(), ClerkLoadError> {
+        if self.restore_dev_browser() {
+            return Ok(());
         }
+        let dev_browser = self
+            .api_client
+            .create_dev_browser()
+            .await
+            .map_err(|e| ClerkLoadError::DevFailedToLoadDevBrowser(e.into()))?;
+        self.config
+            .set_store_value(DEV_BROWSER_TOKEN_ID, dev_browser.id.clone());
+        self.api_client.set_dev_browser_token_id(dev_browser.id);
+        self.dev_browser_ready.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn load_from_network(&self) -> Result<LoadSource, ClerkLoadError> {
+        self.ensure_dev_browser().await?;
 
-        let environment = environment.ok_or(ClerkLoadError::FailedToLoadEnv)?;
-        let client = client.ok_or(ClerkLoadError::FailedToLoadClient)?;
+        let environment = self.load_environment_from_api().await;
+        let client = self.load_client_from_api().await;
+        let env_from_cache = environment.is_err();
+        let client_from_cache = client.is_err();
 
+        // Falls back to the store, the API error is returned when the store
+        // doesn't have the value either
+        let environment = environment.or_else(|e| self.load_environment_from_cache().ok_or(e))?;
+        let client = client.or_else(|e| self.load_client_from_cache().ok_or(e))?;
+
+        self.set_loaded_keeping_stored_at(environment, client, env_from_cache, client_from_cache);
+
+        if env_from_cache || client_from_cache {
+            Ok(LoadSource::Cache)
+        } else {
+            Ok(LoadSource::Network)
+        }
+    }
+
+    /// Sets Clerk loaded while the values coming from the store keep the
+    /// time they were originally stored at
+    fn set_loaded_keeping_stored_at(
+        &self,
+        environment: Environment,
+        client: Client,
+        env_from_cache: bool,
+        client_from_cache: bool,
+    ) {
+        let env_stored_at = self.config.get_store_value(ENVIRONMENT_STORED_AT);
+        let client_stored_at = self.config.get_store_value(CLIENT_STORED_AT);
         self.set_loaded(environment, client);
+        if let (true, Some(stored_at)) = (env_from_cache, env_stored_at) {
+            self.config
+                .set_store_value(ENVIRONMENT_STORED_AT, stored_at);
+        }
+        if let (true, Some(stored_at)) = (client_from_cache, client_stored_at) {
+            self.config.set_store_value(CLIENT_STORED_AT, stored_at);
+        }
+    }
 
-        Ok(())
+    /// Initializes Clerk, tries to pull Environment and Client from API
+    /// in case that fails tries to pull them from cache. Example if
+    /// there wasn't internet connection
+    pub async fn load(&self) -> Result<(), ClerkLoadError> {
+        self.load_with(LoadStrategy::NetworkFirst).await.map(|_| ())
+    }
+
+    /// Initializes Clerk using the given strategy, returns where the
+    /// Environment and Client came from
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// use clerk_fapi_rs::clerk::LoadStrategy;
+    ///
+    /// let loaded = clerk.load_with(LoadStrategy::CacheThenRevalidate).await?;
+    /// if let Some(revalidation) = loaded.revalidation {
+    ///     // Listeners get the fresh state once it has been fetched
+    ///     tokio::spawn(revalidation);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn load_with(&self, strategy: LoadStrategy) -> Result<Loaded, ClerkLoadError> {
+        let cached = match strategy {
+            LoadStrategy::NetworkFirst => None,
+            LoadStrategy::CacheFirst { max_age } => self.load_from_cache(max_age),
+            LoadStrategy::CacheThenRevalidate => self.load_from_cache(Duration::MAX),
+            LoadStrategy::CacheOnly => {
+                // Requests made later still need the dev browser
+                self.restore_dev_browser();
+                let environment = self
+                    .load_environment_from_cache()
+                    .ok_or(ClerkLoadError::FailedToLoadEnv(None))?;
+                let client = self
+                    .load_client_from_cache()
+                    .ok_or(ClerkLoadError::FailedToLoadClient(None))?;
+                Some((environment, client))
+            }
+        };
+        match cached {
+            Some((environment, client)) => {
+                self.set_loaded_keeping_stored_at(environment, client, true, true);
+                let revalidation = (strategy == LoadStrategy::CacheThenRevalidate).then(|| {
+                    let clerk = self.clone();
+                    Box::pin(async move { clerk.refresh_from_network().await })
+                        as RevalidationFuture
+                });
+                Ok(Loaded {
+                    source: LoadSource::Cache,
+                    revalidation,
+                })
+            }
+            None => self.load_from_network().await.map(|source| Loaded {
+                source,
+                revalidation: None,
+            }),
+        }
+    }
+
+    /// Fetches the Environment and Client from the API and updates the
+    /// state, listeners are called if the Client changed
+    ///
+    /// Loads Clerk if it hasn't been loaded yet.
+    pub async fn revalidate(&self) -> Result<(), ClerkLoadError> {
+        if !self.loaded() {
+            return self.load().await;
+        }
+        self.refresh_from_network().await
+    }
+
+    async fn refresh_from_network(&self) -> Result<(), ClerkLoadError> {
+        self.ensure_dev_browser().await?;
+        let environment = self.load_environment_from_api().await?;
+        let client = self.load_client_from_api().await?;
+        self.state.write().set_environment(environment);
+        self.set_client(client)
+            .map_err(|_| ClerkLoadError::FailedToLoadClient(None))
     }
 
     /// set_loaded is public method, example in scenario where we endup
@@ -257,6 +572,32 @@ impl Clerk {
         }
     }
 
+    /// Subscribes to `ClerkEvent`s describing the state changes
+    ///
+    /// Unlike `add_listener` the current state is not replayed, only the
+    /// changes after subscribing are sent. The subscription ends when the
+    /// returned stream is dropped or unsubscribed.
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) {
+    /// use clerk_fapi_rs::events::ClerkEvent;
+    /// use futures::StreamExt;
+    ///
+    /// let mut events = clerk.subscribe();
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         ClerkEvent::SignedIn { user, .. } => println!("signed in {}", user.id),
+    ///         ClerkEvent::SignedOut { .. } => break,
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn subscribe(&self) -> ClerkSubscription {
+        self.events.subscribe()
+    }
+
     //
     // To be able to use Clerk example in Tauri app where one needs to
     // hook to the fapi request hooks in js side we expose the client
@@ -290,6 +631,15 @@ impl Clerk {
         }
     }
 
+    /// Defers the listener emits until the returned hold is dropped, so
+    /// that operations making several requests emit once
+    pub(crate) fn hold_emits(&self) -> EmitHold {
+        self.state.read().hold_emits();
+        EmitHold {
+            state: self.state.clone(),
+        }
+    }
+
     pub fn get_client_authorization_header(&self) -> Option<String> {
         self.state.write().authorization_header()
     }
@@ -376,6 +726,10 @@ impl Clerk {
     /// Creates and returns a JWT token for the current active session. The token can be
     /// optionally scoped to an organization or created with a specific template.
     ///
+    /// Tokens are cached per session, organization and template and reused until
+    /// they are about to expire. Concurrent calls for the same token share a single
+    /// request to the API.
+    ///
     /// Returns None if:
     /// - Client is not loaded
     /// - No active session exists
@@ -405,26 +759,99 @@ impl Clerk {
         organization_id: Option<&str>,
         template: Option<&str>,
     ) -> Result<Option<String>, ClerkGetTokenError> {
-        let session = match self
+        self.get_token_valid_for(organization_id, template, TOKEN_EXPIRY_LEEWAY)
+            .await
+    }
+
+    /// Returns a token that is valid at least for `min_validity` seconds,
+    /// the cached token is used if it's valid long enough
+    async fn get_token_valid_for(
+        &self,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+        min_validity: i64,
+    ) -> Result<Option<String>, ClerkGetTokenError> {
+        match self
             .session()
             .map_err(ClerkGetTokenError::ClerkNotLoadedError)?
         {
-            Some(s) => s,
-            None => {
-                // No active session -> cannot return token
-                return Ok(None);
+            Some(session) => {
+                self.session_token(&session, organization_id, template, min_validity)
+                    .await
             }
-        };
+            // No active session -> cannot return token
+            None => Ok(None),
+        }
+    }
 
-        if self
-            .user()
-            .map_err(ClerkGetTokenError::ClerkNotLoadedError)?
-            .is_none()
-        {
+    /// Get a session JWT token for any active session on the client, not
+    /// only the current one
+    ///
+    /// Works like `get_token`, useful when several accounts are signed in
+    /// at once. Returns None if the client has no active session with the
+    /// id.
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// for session in clerk.sessions()?.iter().filter(|s| s.is_active()) {
+    ///     let token = clerk.get_session_token(&session.id, None, None).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_session_token(
+        &self,
+        session_id: &str,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+    ) -> Result<Option<String>, ClerkGetTokenError> {
+        let client = self
+            .client()
+            .map_err(ClerkGetTokenError::ClerkNotLoadedError)?;
+        match sessions::find_active_session(&client, session_id) {
+            Ok(session) => {
+                self.session_token(&session, organization_id, template, TOKEN_EXPIRY_LEEWAY)
+                    .await
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn session_token(
+        &self,
+        session: &Session,
+        organization_id: Option<&str>,
+        template: Option<&str>,
+        min_validity: i64,
+    ) -> Result<Option<String>, ClerkGetTokenError> {
+        if session.user.is_none() {
             // session but no user
             return Ok(None);
         }
 
+        // Without explicit organization the token is created for the
+        // last active organization of the session
+        let key = TokenCacheKey {
+            session_id: session.id.clone(),
+            organization_id: organization_id
+                .map(str::to_string)
+                .or_else(|| session.last_active_organization_id.clone()),
+            template: template.map(str::to_string),
+        };
+
+        if let Some(jwt) = self.token_cache.get(&key, min_validity) {
+            return Ok(Some(jwt));
+        }
+
+        let lock = self.token_cache.lock(&key);
+        let _guard = lock.lock().await;
+
+        // Concurrent call might have fetched the token while we waited
+        if let Some(jwt) = self.token_cache.get(&key, min_validity) {
+            return Ok(Some(jwt));
+        }
+
         // Call appropriate token creation method based on parameters
         let result = match template {
             Some(template_name) => self
@@ -433,7 +860,7 @@ impl Clerk {
                 .await
                 .map_err(|e| {
                     error!("Failed to call create_session_token_with_template: {e}");
-                    ClerkGetTokenError::ClerkApiError
+                    ClerkGetTokenError::ClerkApiError(e.into())
                 })?,
             None => self
                 .api_client
@@ -441,18 +868,548 @@ impl Clerk {
                 .await
                 .map_err(|e| {
                     error!("Failed to call create_session_token: {e}");
-                    ClerkGetTokenError::ClerkApiError
+                    ClerkGetTokenError::ClerkApiError(e.into())
                 })?,
         };
 
+        if let Some(jwt) = result.jwt.clone() {
+            self.events.send(ClerkEvent::TokenRefreshed {
+                session_id: key.session_id.clone(),
+                organization_id: key.organization_id.clone(),
+                template: key.template.clone(),
+            });
+            self.token_cache.set(key, jwt);
+        }
+
         Ok(result.jwt)
     }
 
+    /// Keeps the active session token fresh in the background
+    ///
+    /// Refreshes the active session token every `interval` so that `get_token`
+    /// calls are served from the cache. Clerk session tokens are short lived,
+    /// clerk-js refreshes them every 50 seconds. The returned future runs until
+    /// it's dropped, spawn it on the runtime of your choice.
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(client: clerk_fapi_rs::clerk::Clerk) {
+    /// use std::time::Duration;
+    ///
+    /// let refresher = client.clone();
+    /// let handle = tokio::spawn(async move {
+    ///     refresher.run_token_refresh(Duration::from_secs(50)).await
+    /// });
+    /// // Stop refreshing
+    /// handle.abort();
+    /// # }
+    /// ```
+    pub async fn run_token_refresh(&self, interval: Duration) {
+        // Refresh when the token would expire before the next round
+        let min_validity = interval.as_secs() as i64 + TOKEN_EXPIRY_LEEWAY;
+        loop {
+            if self.loaded() {
+                if let Err(e) = self.get_token_valid_for(None, None, min_validity).await {
+                    warn!("Clerk: Failed to refresh session token: {e}");
+                }
+            }
+            Delay::new(interval).await;
+        }
+    }
+
+    /// Drops all cached session tokens
+    pub fn clear_token_cache(&self) {
+        self.token_cache.clear();
+    }
+
+    /// Drops the cached tokens of the session
+    pub(crate) fn clear_session_tokens(&self, session_id: &str) {
+        self.token_cache.remove_session(session_id);
+    }
+
+    /// Verifies a session token locally against the instance JWKS
+    ///
+    /// Checks the signature with the key matching the token `kid` and
+    /// validates `exp`, `nbf`, `iat` and that `iss` matches the Frontend API
+    /// url. The JWKS is fetched once and cached, so this can be called for
+    /// every incoming request. Use `JwtVerifier` directly to configure
+    /// authorized parties or a different issuer.
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(client: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(token) = client.get_token(None, None).await? {
+    ///     let claims = client.verify_token(&token).await?;
+    ///     println!("session {:?} for user {}", claims.sid, claims.sub);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_token(&self, token: &str) -> Result<SessionClaims, JwtVerificationError> {
+        self.jwt_verifier.verify(token).await
+    }
+
+    /// Returns the verifier used by `verify_token`
+    pub fn jwt_verifier(&self) -> &JwtVerifier {
+        &self.jwt_verifier
+    }
+
+    /// Whether the signed in user passes the check in the active
+    /// organization
+    ///
+    /// Roles and permissions come from the user's membership in the active
+    /// organization, reverification from the active session and features
+    /// from the session token. Returns false when no user is signed in or
+    /// Clerk is not loaded. Use for gating UI, the backend should check the
+    /// verified token with `has_verified`.
+    pub fn has(&self, check: &AuthCheck) -> bool {
+        let state = self.state.read();
+        match (state.session(), state.user(), state.organization()) {
+            (Ok(session), Ok(user), Ok(organization)) => check_state(
+                check,
+                session.as_ref(),
+                user.as_ref(),
+                organization.as_ref(),
+            ),
+            _ => false,
+        }
+    }
+
+    /// Verifies the session token and evaluates the check against its
+    /// claims, the same way `has` does for the local state
+    pub async fn has_verified(
+        &self,
+        token: &str,
+        check: &AuthCheck,
+    ) -> Result<bool, JwtVerificationError> {
+        Ok(self.verify_token(token).await?.has(check))
+    }
+
+    /// Starts a typed sign in flow with the given identifier
+    ///
+    /// See `SignInFlow` for preparing and attempting the factors. The created
+    /// session is set active when the sign in completes.
+    pub async fn sign_in(&self, identifier: &str) -> Result<SignInFlow, ClerkSignInError> {
+        SignInFlow::create(self, identifier).await
+    }
+
+    /// Starts a sign in flow with a ticket, example from an invitation link
+    pub async fn sign_in_with_ticket(&self, ticket: &str) -> Result<SignInFlow, ClerkSignInError> {
+        SignInFlow::create_with_ticket(self, ticket).await
+    }
+
+    /// Starts a native OAuth sign in with the strategy, example
+    /// `oauth_google`
+    ///
+    /// See `OAuthFlow` for sending the user to the provider and capturing
+    /// the redirect. A sign in for a new account is transferred to a sign up.
+    pub async fn sign_in_with_oauth(
+        &self,
+        strategy: &str,
+        redirect: OAuthRedirect,
+    ) -> Result<OAuthFlow, ClerkOAuthError> {
+        OAuthFlow::sign_in(self, strategy, redirect).await
+    }
+
+    /// Starts a native OAuth sign up with the strategy, a sign up for an
+    /// existing account is transferred to a sign in
+    pub async fn sign_up_with_oauth(
+        &self,
+        strategy: &str,
+        redirect: OAuthRedirect,
+    ) -> Result<OAuthFlow, ClerkOAuthError> {
+        OAuthFlow::sign_up(self, strategy, redirect).await
+    }
+
+    /// Starts a sign in with the email address and sends an email link that
+    /// redirects to `redirect_url` once opened
+    ///
+    /// See `EmailLinkFlow` for waiting until the link is opened or
+    /// completing the flow with the redirect.
+    pub async fn sign_in_with_email_link(
+        &self,
+        email_address: &str,
+        redirect_url: &str,
+    ) -> Result<EmailLinkFlow, ClerkEmailLinkError> {
+        EmailLinkFlow::sign_in(self, email_address, redirect_url).await
+    }
+
+    /// Starts a sign up with the params and sends an email link to verify
+    /// the email address
+    pub async fn sign_up_with_email_link(
+        &self,
+        params: SignUpParams,
+        redirect_url: &str,
+    ) -> Result<EmailLinkFlow, ClerkEmailLinkError> {
+        EmailLinkFlow::sign_up(self, params, redirect_url).await
+    }
+
+    /// Signs in with a discoverable passkey from the authenticator
+    ///
+    /// The created session is set active when the sign in completes. Use
+    /// `SignInFlow::attempt_passkey` to sign in with a passkey after the
+    /// identifier.
+    pub async fn sign_in_with_passkey(
+        &self,
+        authenticator: &(impl PasskeyAuthenticator + ?Sized),
+    ) -> Result<SignInFlow, ClerkPasskeyError> {
+        crate::passkey::sign_in_with_passkey(self, authenticator).await
+    }
+
+    /// Creates a passkey with the authenticator and adds it to the signed in
+    /// user
+    pub async fn register_passkey(
+        &self,
+        authenticator: &(impl PasskeyAuthenticator + ?Sized),
+    ) -> Result<ClientPasskey, ClerkPasskeyError> {
+        crate::passkey::register_passkey(self, authenticator).await
+    }
+
+    /// Signs in with the wallet of the signer, the message Clerk returns is
+    /// signed and the created session set active
+    pub async fn sign_in_with_web3(
+        &self,
+        signer: &(impl Web3Signer + ?Sized),
+    ) -> Result<SignInFlow, ClerkWeb3Error> {
+        crate::web3::sign_in_with_web3(self, signer).await
+    }
+
+    /// Signs up with the wallet of the signer
+    ///
+    /// The params can provide the other fields the instance requires, the
+    /// wallet is set from the signer.
+    pub async fn sign_up_with_web3(
+        &self,
+        signer: &(impl Web3Signer + ?Sized),
+        params: SignUpParams,
+    ) -> Result<SignUpFlow, ClerkWeb3Error> {
+        crate::web3::sign_up_with_web3(self, signer, params).await
+    }
+
+    /// Adds the wallet of the signer to the signed in user and verifies it
+    pub async fn link_web3_wallet(
+        &self,
+        signer: &(impl Web3Signer + ?Sized),
+    ) -> Result<ClientWeb3Wallet, ClerkWeb3Error> {
+        crate::web3::link_web3_wallet(self, signer).await
+    }
+
+    /// Starts adding TOTP as a second factor for the signed in user
+    ///
+    /// See `TotpEnrollment` for showing the URI and verifying the
+    /// enrollment.
+    pub async fn enroll_totp(&self) -> Result<TotpEnrollment, ClerkTotpError> {
+        TotpEnrollment::create(self).await
+    }
+
+    /// Organization memberships of the signed in user
+    pub fn user_organization_memberships(&self) -> Paginator<ClientOrganizationMembership> {
+        let fapi = self.api_client.clone();
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            async move {
+                let response = fapi
+                    .get_organization_memberships_with_total_count(
+                        Some(limit),
+                        Some(offset),
+                        Some(true), // paginated
+                    )
+                    .await?;
+                Ok(Page::from(response))
+            }
+        })
+    }
+
+    /// Organization suggestions of the signed in user, optionally filtered
+    /// by status
+    pub fn user_organization_suggestions(
+        &self,
+        status: Option<&str>,
+    ) -> Paginator<ClientOrganizationSuggestion> {
+        let fapi = self.api_client.clone();
+        let status = status.map(str::to_string);
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            let status = status.clone();
+            async move {
+                let response = fapi
+                    .get_organization_suggestions(Some(limit), Some(offset), status.as_deref())
+                    .await?;
+                Ok(Page {
+                    data: response.data.unwrap_or_default(),
+                    total_count: response.total_count,
+                })
+            }
+        })
+    }
+
+    /// Members of the organization, optionally filtered with a search query
+    /// or a role
+    pub fn organization_memberships(
+        &self,
+        organization_id: &str,
+        query: Option<&str>,
+        role: Option<&str>,
+    ) -> Paginator<ClientOrganizationMembership> {
+        let fapi = self.api_client.clone();
+        let organization_id = organization_id.to_string();
+        let query = query.map(str::to_string);
+        let role = role.map(str::to_string);
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            let organization_id = organization_id.clone();
+            let query = query.clone();
+            let role = role.clone();
+            async move {
+                let response = fapi
+                    .list_organization_memberships_with_total_count(
+                        &organization_id,
+                        Some(limit),
+                        Some(offset),
+                        Some(true), // paginated
+                        query.as_deref(),
+                        role.as_deref(),
+                    )
+                    .await?;
+                Ok(Page::from(response))
+            }
+        })
+    }
+
+    /// Invitations of the organization, optionally filtered by status
+    pub fn organization_invitations(
+        &self,
+        organization_id: &str,
+        status: Option<&str>,
+    ) -> Paginator<ClientOrganizationInvitation> {
+        let fapi = self.api_client.clone();
+        let organization_id = organization_id.to_string();
+        let status = status.map(str::to_string);
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            let organization_id = organization_id.clone();
+            let status = status.clone();
+            async move {
+                let response = fapi
+                    .get_organization_invitations_with_total_count(
+                        &organization_id,
+                        Some(limit),
+                        Some(offset),
+                        status.as_deref(),
+                    )
+                    .await?;
+                Ok(Page::from(response))
+            }
+        })
+    }
+
+    /// Domains of the organization, optionally filtered by verification and
+    /// enrollment mode
+    pub fn organization_domains(
+        &self,
+        organization_id: &str,
+        verified: Option<bool>,
+        enrollment_mode: Option<&str>,
+    ) -> Paginator<ClientOrganizationDomain> {
+        let fapi = self.api_client.clone();
+        let organization_id = organization_id.to_string();
+        let enrollment_mode = enrollment_mode.map(str::to_string);
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            let organization_id = organization_id.clone();
+            let enrollment_mode = enrollment_mode.clone();
+            async move {
+                let response = fapi
+                    .list_organization_domains(
+                        &organization_id,
+                        Some(limit),
+                        Some(offset),
+                        verified,
+                        enrollment_mode.as_deref(),
+                    )
+                    .await?;
+                Ok(Page {
+                    data: response.data.unwrap_or_default(),
+                    total_count: response.total_count,
+                })
+            }
+        })
+    }
+
+    /// Requests to join the organization, optionally filtered by status
+    pub fn organization_membership_requests(
+        &self,
+        organization_id: &str,
+        status: Option<&str>,
+    ) -> Paginator<ClientOrganizationMembershipRequest> {
+        let fapi = self.api_client.clone();
+        let organization_id = organization_id.to_string();
+        let status = status.map(str::to_string);
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            let organization_id = organization_id.clone();
+            let status = status.clone();
+            async move {
+                let response = fapi
+                    .list_organization_membership_requests(
+                        &organization_id,
+                        Some(limit),
+                        Some(offset),
+                        status.as_deref(),
+                    )
+                    .await?;
+                Ok(Page {
+                    data: response.data.unwrap_or_default(),
+                    total_count: response.total_count,
+                })
+            }
+        })
+    }
+
+    /// Roles that can be assigned in the organization
+    pub fn organization_roles(&self, organization_id: &str) -> Paginator<ClientRole> {
+        let fapi = self.api_client.clone();
+        let organization_id = organization_id.to_string();
+        Paginator::new(move |limit, offset| {
+            let fapi = fapi.clone();
+            let organization_id = organization_id.clone();
+            async move {
+                let response = fapi
+                    .list_organization_roles(&organization_id, Some(limit), Some(offset))
+                    .await?;
+                Ok(Page {
+                    data: response.data.unwrap_or_default(),
+                    total_count: response.total_count,
+                })
+            }
+        })
+    }
+
+    /// Handle for administering the organization as the signed in user,
+    /// see `OrganizationAdmin`
+    pub fn organization_admin(&self, organization_id: &str) -> OrganizationAdmin {
+        OrganizationAdmin::new(self, organization_id)
+    }
+
+    /// What the instance supports, example the enabled first factors and
+    /// social providers, read from the loaded environment
+    pub fn capabilities(&self) -> Result<Capabilities, ClerkNotLoadedError> {
+        Ok(Capabilities::new(self.environment()?))
+    }
+
+    /// Validator of passwords, usernames, email addresses and phone numbers
+    /// against the rules in the loaded environment, see `Validator`
+    pub fn validator(&self) -> Result<Validator, ClerkNotLoadedError> {
+        Ok(Validator::from_environment(&self.environment()?))
+    }
+
+    /// Fields the instance requires or accepts on sign up, read from the
+    /// loaded environment
+    pub fn sign_up_requirements(&self) -> Result<SignUpRequirements, ClerkNotLoadedError> {
+        Ok(SignUpRequirements::from_environment(&self.environment()?))
+    }
+
+    /// Starts a typed sign up flow with the given params
+    ///
+    /// See `SignUpFlow` for providing missing fields and verifying the
+    /// identifiers. The created session is set active when the sign up
+    /// completes.
+    pub async fn sign_up(&self, params: SignUpParams) -> Result<SignUpFlow, ClerkSignUpError> {
+        SignUpFlow::create(self, params).await
+    }
+
+    /// Whether the active session needs to be reverified on the `level`
+    /// before sensitive actions, example changing the password
+    ///
+    /// Compares the session's `factor_verification_age` to `max_age`, see
+    /// `ReverificationPolicy` for the presets. Without an active session
+    /// reverification is always needed.
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// use clerk_fapi_rs::reverification::ReverificationPolicy;
+    ///
+    /// let policy = ReverificationPolicy::STRICT;
+    /// if clerk.needs_reverification(policy.level, policy.max_age)? {
+    ///     let reverification = clerk.reverify(policy.level).await?;
+    ///     // prepare and attempt the factors
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn needs_reverification(
+        &self,
+        level: ReverificationLevel,
+        max_age: Duration,
+    ) -> Result<bool, ClerkNotLoadedError> {
+        let policy = ReverificationPolicy { level, max_age };
+        Ok(!self
+            .session()?
+            .is_some_and(|s| policy.is_satisfied(&s.factor_verification_age)))
+    }
+
+    /// Starts a step-up flow reverifying the active session on the `level`
+    ///
+    /// See `ReverificationFlow` for preparing and attempting the factors.
+    pub async fn reverify(
+        &self,
+        level: ReverificationLevel,
+    ) -> Result<ReverificationFlow, ClerkReverificationError> {
+        ReverificationFlow::start(self, level).await
+    }
+
+    /// Runs `operation`, and if Clerk rejects it because the session needs
+    /// to be reverified, starts the reverification on the level Clerk asked
+    /// for, lets `reverify` complete it and runs `operation` again
+    ///
+    /// `reverify` is called only when a step-up is needed, it gets the
+    /// started flow and returns it once it's complete, example after asking
+    /// the user for their password.
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// use clerk_fapi_rs::error::ClerkApiError;
+    /// use clerk_fapi_rs::sign_in::FirstFactor;
+    ///
+    /// let fapi = clerk.get_fapi_client();
+    /// clerk
+    ///     .with_reverification(
+    ///         || async { fapi.delete_user().await.map_err(ClerkApiError::from) },
+    ///         |mut reverification| async move {
+    ///             let password = "hunter2".to_string(); // ask the user
+    ///             reverification
+    ///                 .attempt_first_factor(FirstFactor::Password(password))
+    ///                 .await?;
+    ///             Ok(reverification)
+    ///         },
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_reverification<T, Op, OpFut, R, RFut>(
+        &self,
+        operation: Op,
+        reverify: R,
+    ) -> Result<T, ClerkReverificationError>
+    where
+        Op: Fn() -> OpFut,
+        OpFut: Future<Output = Result<T, ClerkApiError>>,
+        R: FnOnce(ReverificationFlow) -> RFut,
+        RFut: Future<Output = Result<ReverificationFlow, ClerkReverificationError>>,
+    {
+        crate::reverification::with_reverification(self, operation, reverify).await
+    }
+
     /// Signs out either a specific session or all sessions for this client
     ///
     /// This method allows signing out a single session by ID, or signing out all sessions
     /// for the current client if no session ID is provided. After successful sign-out,
     /// the client state will be updated accordingly via the callback mechanism.
+    /// When the signed out session was the active one, the most recently active
+    /// of the remaining sessions is set active, like clerk-js does.
     ///
     /// # Arguments
     /// * `session_id` - Optional session ID to sign out. If None, signs out all sessions.
@@ -462,25 +1419,102 @@ impl Clerk {
     ///
     /// # Errors
     /// Returns an error if the API call fails
-    pub async fn sign_out(&self, session_id: Option<String>) -> Result<(), String> {
+    pub async fn sign_out(&self, session_id: Option<String>) -> Result<(), ClerkApiError> {
         match session_id {
             Some(sid) => {
-                self.api_client
-                    .remove_session(&sid)
-                    .await
-                    .map_err(|e| format!("Failed to remove session: {e}"))?;
+                let was_current = self.session().ok().flatten().is_some_and(|s| s.id == sid);
+                self.api_client.remove_session(&sid).await.map_err(|e| {
+                    error!("Failed to remove session: {e}");
+                    ClerkApiError::from(e)
+                })?;
+                self.token_cache.remove_session(&sid);
+                if was_current {
+                    self.activate_next_session(&sid).await?;
+                }
             }
             None => {
                 self.api_client
                     .remove_client_sessions_and_retain_cookie()
                     .await
-                    .map_err(|e| format!("Failed to remove all sessions: {e}"))?;
+                    .map_err(|e| {
+                        error!("Failed to remove all sessions: {e}");
+                        ClerkApiError::from(e)
+                    })?;
+                self.token_cache.clear();
             }
         };
         // The remove sessions calls will update the client state via the callback
         Ok(())
     }
 
+    /// Sets the most recently active session active after the session
+    /// was signed out, unless the API already picked one
+    async fn activate_next_session(&self, signed_out: &str) -> Result<(), ClerkApiError> {
+        let Ok(client) = self.client() else {
+            return Ok(());
+        };
+        if client.last_active_session_id.is_some()
+            && client.last_active_session_id.as_deref() != Some(signed_out)
+        {
+            return Ok(());
+        }
+        if let Some(next) = sessions::next_active_session(&client, signed_out) {
+            self.api_client
+                .touch_session(&next.id, None)
+                .await
+                .map_err(|e| {
+                    error!("Failed to touch next session: {e}");
+                    ClerkApiError::from(e)
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Views of all the sessions on the client, including the ended ones
+    ///
+    /// The active session has `is_current` set. See `switch_session` for
+    /// moving between the signed in accounts.
+    pub fn sessions(&self) -> Result<Vec<SessionInfo>, ClerkNotLoadedError> {
+        Ok(sessions::sessions(&self.client()?))
+    }
+
+    /// Whether the instance allows only one signed in session per client
+    pub fn single_session_mode(&self) -> Result<bool, ClerkNotLoadedError> {
+        Ok(self.capabilities()?.single_session_mode())
+    }
+
+    /// Sets another active session on the client active, keeping the last
+    /// active organization of that session
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// let work = clerk
+    ///     .sessions()?
+    ///     .into_iter()
+    ///     .find(|s| s.is_active() && !s.is_current);
+    /// if let Some(work) = work {
+    ///     clerk.switch_session(&work.id).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn switch_session(&self, session_id: &str) -> Result<(), ClerkSessionError> {
+        let client = self
+            .client()
+            .map_err(ClerkSessionError::ClerkNotLoadedError)?;
+        let session = sessions::find_active_session(&client, session_id)?;
+        self.api_client
+            .touch_session(&session.id, session.last_active_organization_id.as_deref())
+            .await
+            .map_err(|e| {
+                error!("Failed to touch session: {e}");
+                ClerkSessionError::ClerkApiError(e.into())
+            })?;
+        // We rely on the callback mechanism to update the state
+        Ok(())
+    }
+
     /// Updates the active session and/or organization
     ///
     /// This method allows changing the active session and/or organization for the current client.
@@ -553,10 +1587,75 @@ impl Clerk {
             .await
             .map_err(|e| {
                 error!("Failed to touch session: {}", e);
-                ClerkSetActiveError::ClerkApiError
+                ClerkSetActiveError::ClerkApiError(e.into())
             })?;
 
         // We rely on the callback mechanism to update the state
         Ok(())
     }
+
+    //
+    // Active organization lifecycle, each of these emits to the listeners
+    // once and leaves `organization()` matching the session
+    //
+
+    /// Creates an organization and sets it active
+    pub async fn create_organization(
+        &self,
+        name: &str,
+    ) -> Result<Organization, ClerkOrganizationError> {
+        organizations::create_organization(self, name).await
+    }
+
+    /// Sets the organization with the id or slug active
+    ///
+    /// # Examples
+    /// ```
+    /// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+    /// let organization = clerk.switch_organization("example-corp").await?;
+    /// assert_eq!(clerk.organization()?.map(|o| o.id), Some(organization.id));
+    ///
+    /// // Back to the personal account
+    /// clerk.clear_active_organization().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn switch_organization(
+        &self,
+        organization_id_or_slug: &str,
+    ) -> Result<Organization, ClerkOrganizationError> {
+        organizations::switch_organization(self, organization_id_or_slug).await
+    }
+
+    /// Switches the session to the personal account of the user
+    pub async fn clear_active_organization(&self) -> Result<(), ClerkOrganizationError> {
+        organizations::clear_active_organization(self).await
+    }
+
+    /// Removes the user from the organization, switching to the personal
+    /// account if it was the active one
+    pub async fn leave_organization(
+        &self,
+        organization_id: &str,
+    ) -> Result<(), ClerkOrganizationError> {
+        organizations::leave_organization(self, organization_id).await
+    }
+
+    /// Accepts an organization invitation the user has received, see
+    /// `get_users_organization_invitations` on the Frontend API client
+    pub async fn accept_invitation(
+        &self,
+        invitation_id: &str,
+    ) -> Result<ClientOrganizationInvitationUserContext, ClerkOrganizationError> {
+        organizations::accept_invitation(self, invitation_id).await
+    }
+
+    /// Accepts a suggestion to join an organization, see
+    /// `user_organization_suggestions`
+    pub async fn accept_suggestion(
+        &self,
+        suggestion_id: &str,
+    ) -> Result<ClientOrganizationSuggestion, ClerkOrganizationError> {
+        organizations::accept_suggestion(self, suggestion_id).await
+    }
 }
@@ -43,7 +43,9 @@ impl ClerkFapiClient {
             .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
 
         // Create custom client
-        let client = ClerkHttpClient::new(http_client, state.clone(), config.kind);
+        let client = ClerkHttpClient::new(http_client, state.clone(), config.kind)
+            .with_retry_policy(config.retry_policy.clone())
+            .with_middlewares(config.middlewares.clone());
 
         Ok(Self {
             client: Arc::new(client),
@@ -80,6 +82,7 @@ impl ClerkFapiClient {
     }
 
     // Active Sessions API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn get_sessions(
         &self,
         clerk_session_id: Option<&str>,
@@ -87,6 +90,7 @@ impl ClerkFapiClient {
         active_sessions_api::get_sessions(&self.clerk_config(), clerk_session_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn get_users_sessions(
         &self,
         clerk_session_id: Option<&str>,
@@ -94,6 +98,7 @@ impl ClerkFapiClient {
         active_sessions_api::get_users_sessions(&self.clerk_config(), clerk_session_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn revoke_session(
         &self,
         session_id: &str,
@@ -109,6 +114,7 @@ impl ClerkFapiClient {
     }
 
     // Backup Codes API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_backup_codes(&self) -> Result<BackupCodes, Error<CreateBackupCodesError>> {
         let response = backup_codes_api::create_backup_codes(&self.clerk_config()).await?;
         self.handle_client_update(*response.client.clone());
@@ -116,6 +122,7 @@ impl ClerkFapiClient {
     }
 
     // Client API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_client_sessions(
         &self,
     ) -> Result<ClientDeleteSession, Error<DeleteClientSessionsError>> {
@@ -126,6 +133,7 @@ impl ClerkFapiClient {
         Ok(response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_client(&self) -> Result<Option<ClientClient>, Error<GetClientError>> {
         let response = client_api::get_client(&self.clerk_config()).await?;
         if let Some(client) = response.client.clone() {
@@ -134,6 +142,7 @@ impl ClerkFapiClient {
         Ok(response.response.map(|c| *c))
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn handshake_client(
         &self,
         clerk_proxy_url: Option<&str>,
@@ -155,6 +164,7 @@ impl ClerkFapiClient {
         .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn post_client(&self) -> Result<Option<ClientClient>, Error<PostClientError>> {
         let response = client_api::post_client(&self.clerk_config()).await?;
         if let Some(client) = response.client.clone() {
@@ -163,6 +173,7 @@ impl ClerkFapiClient {
         Ok(response.response.map(|c| *c))
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn put_client(&self) -> Result<Option<ClientClient>, Error<PutClientError>> {
         let response = client_api::put_client(&self.clerk_config()).await?;
         if let Some(client) = response.client.clone() {
@@ -172,16 +183,19 @@ impl ClerkFapiClient {
     }
 
     // Default API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn clear_site_data(&self) -> Result<(), Error<ClearSiteDataError>> {
         default_api::clear_site_data(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_account_portal(
         &self,
     ) -> Result<ClientAccountPortal, Error<GetAccountPortalError>> {
         default_api::get_account_portal(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_dev_browser_init(
         &self,
         origin: Option<&str>,
@@ -189,6 +203,7 @@ impl ClerkFapiClient {
         default_api::get_dev_browser_init(&self.clerk_config(), origin).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_proxy_health(
         &self,
         domain_id: &str,
@@ -206,6 +221,7 @@ impl ClerkFapiClient {
         .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn link_client(
         &self,
         clerk_token: Option<&str>,
@@ -213,12 +229,14 @@ impl ClerkFapiClient {
         default_api::link_client(&self.clerk_config(), clerk_token).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn post_dev_browser_init_set_cookie(
         &self,
     ) -> Result<(), Error<PostDevBrowserInitSetCookieError>> {
         default_api::post_dev_browser_init_set_cookie(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn sync_client(
         &self,
         link_domain: Option<&str>,
@@ -228,11 +246,13 @@ impl ClerkFapiClient {
     }
 
     // Dev Browser API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_dev_browser(&self) -> Result<DevBrowser, Error<CreateDevBrowserError>> {
         dev_browser_api::create_dev_browser(&self.clerk_config()).await
     }
 
     // Domains API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn attempt_organization_domain_verification(
         &self,
         organization_id: &str,
@@ -250,6 +270,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_organization_domain(
         &self,
         organization_id: &str,
@@ -262,6 +283,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_organization_domain(
         &self,
         organization_id: &str,
@@ -279,6 +301,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_organization_domain(
         &self,
         organization_id: &str,
@@ -291,6 +314,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn list_organization_domains(
         &self,
         organization_id: &str,
@@ -313,6 +337,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn prepare_organization_domain_verification(
         &self,
         organization_id: &str,
@@ -330,6 +355,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_organization_domain_enrollment_mode(
         &self,
         organization_id: &str,
@@ -350,6 +376,7 @@ impl ClerkFapiClient {
     }
 
     // Email Addresses API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn create_email_addresses(
         &self,
         email_address: &str,
@@ -365,6 +392,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn delete_email_address(
         &self,
         email_id: &str,
@@ -382,6 +410,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn get_email_address(
         &self,
         email_id: &str,
@@ -397,6 +426,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn get_email_addresses(
         &self,
         clerk_session_id: Option<&str>,
@@ -404,6 +434,7 @@ impl ClerkFapiClient {
         email_addresses_api::get_email_addresses(&self.clerk_config(), clerk_session_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn send_verification_email(
         &self,
         email_id: &str,
@@ -425,6 +456,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn verify_email_address(
         &self,
         email_id: &str,
@@ -443,10 +475,12 @@ impl ClerkFapiClient {
     }
 
     // Environment API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_environment(&self) -> Result<ClientEnvironment, Error<GetEnvironmentError>> {
         environment_api::get_environment(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_environment(
         &self,
         origin: &str,
@@ -455,6 +489,7 @@ impl ClerkFapiClient {
     }
 
     // External Accounts API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_external_account(
         &self,
         external_account_id: &str,
@@ -470,6 +505,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn post_o_auth_accounts(
         &self,
         strategy: &str,
@@ -499,6 +535,7 @@ impl ClerkFapiClient {
         Ok(response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn reauthorize_external_account(
         &self,
         external_account_id: &str,
@@ -522,6 +559,7 @@ impl ClerkFapiClient {
         Ok(response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn revoke_external_account_tokens(
         &self,
         external_account_id: &str,
@@ -536,11 +574,13 @@ impl ClerkFapiClient {
     }
 
     // Health API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_health(&self) -> Result<GetHealth200Response, Error<GetHealthError>> {
         health_api::get_health(&self.clerk_config()).await
     }
 
     // Invitations API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn bulk_create_organization_invitations(
         &self,
         organization_id: &str,
@@ -567,6 +607,7 @@ impl ClerkFapiClient {
         Ok(res)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_organization_invitations(
         &self,
         organization_id: &str,
@@ -584,6 +625,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_all_pending_organization_invitations(
         &self,
         organization_id: &str,
@@ -604,6 +646,7 @@ impl ClerkFapiClient {
         Ok(res)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_organization_invitations(
         &self,
         organization_id: &str,
@@ -630,6 +673,32 @@ impl ClerkFapiClient {
         Ok(res)
     }
 
+    /// Same as `get_organization_invitations`, returning the wrapped
+    /// response that keeps the `total_count` of paginated requests
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_organization_invitations_with_total_count(
+        &self,
+        organization_id: &str,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        status: Option<&str>,
+    ) -> Result<
+        ClientClientWrappedOrganizationInvitationsResponse,
+        Error<GetOrganizationInvitationsError>,
+    > {
+        let response = invitations_api::get_organization_invitations(
+            &self.clerk_config(),
+            organization_id,
+            limit,
+            offset,
+            status,
+        )
+        .await?;
+        self.handle_client_update(*response.client.clone());
+        Ok(*response.response)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn revoke_pending_organization_invitation(
         &self,
         organization_id: &str,
@@ -646,6 +715,7 @@ impl ClerkFapiClient {
     }
 
     // Members API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_organization_membership(
         &self,
         organization_id: &str,
@@ -663,6 +733,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn list_organization_memberships(
         &self,
         organization_id: &str,
@@ -692,6 +763,36 @@ impl ClerkFapiClient {
         Ok(res)
     }
 
+    /// Same as `list_organization_memberships`, returning the wrapped
+    /// response that keeps the `total_count` of paginated requests
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn list_organization_memberships_with_total_count(
+        &self,
+        organization_id: &str,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        paginated: Option<bool>,
+        query: Option<&str>,
+        role: Option<&str>,
+    ) -> Result<
+        ClientClientWrappedOrganizationMembershipsResponse,
+        Error<ListOrganizationMembershipsError>,
+    > {
+        let response = members_api::list_organization_memberships(
+            &self.clerk_config(),
+            organization_id,
+            limit,
+            offset,
+            paginated,
+            query,
+            role,
+        )
+        .await?;
+        self.handle_client_update(*response.client.clone());
+        Ok(*response.response)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn remove_organization_member(
         &self,
         organization_id: &str,
@@ -704,6 +805,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_organization_membership(
         &self,
         organization_id: &str,
@@ -722,6 +824,7 @@ impl ClerkFapiClient {
     }
 
     // Membership Requests API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn accept_organization_membership_request(
         &self,
         organization_id: &str,
@@ -738,6 +841,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn list_organization_membership_requests(
         &self,
         organization_id: &str,
@@ -760,6 +864,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn reject_organization_membership_request(
         &self,
         organization_id: &str,
@@ -777,6 +882,7 @@ impl ClerkFapiClient {
     }
 
     // OAuth2 Callbacks API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_oauth_callback(
         &self,
         scope: Option<&str>,
@@ -788,6 +894,7 @@ impl ClerkFapiClient {
             .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn post_oauth_callback(
         &self,
         code: Option<&str>,
@@ -800,6 +907,7 @@ impl ClerkFapiClient {
     }
 
     // OAuth2 Identity Provider API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn get_o_auth_consent(
         configuration: &configuration::Configuration,
         client_id: &str,
@@ -813,10 +921,12 @@ impl ClerkFapiClient {
         .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_o_auth_token(&self) -> Result<OAuthToken, Error<GetOAuthTokenError>> {
         o_auth2_identify_provider_api::get_o_auth_token(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_o_auth_token_info(
         &self,
         token: &str,
@@ -832,28 +942,33 @@ impl ClerkFapiClient {
         .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_o_auth_user_info(
         &self,
     ) -> Result<OAuthUserInfo, Error<GetOAuthUserInfoError>> {
         o_auth2_identify_provider_api::get_o_auth_user_info(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_o_auth_user_info_post(
         &self,
     ) -> Result<OAuthUserInfo, Error<GetOAuthUserInfoPostError>> {
         o_auth2_identify_provider_api::get_o_auth_user_info_post(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn request_o_auth_authorize(&self) -> Result<(), Error<RequestOAuthAuthorizeError>> {
         o_auth2_identify_provider_api::request_o_auth_authorize(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn request_o_auth_authorize_post(
         &self,
     ) -> Result<(), Error<RequestOAuthAuthorizePostError>> {
         o_auth2_identify_provider_api::request_o_auth_authorize_post(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn revoke_o_auth_token(
         &self,
         token: Option<&str>,
@@ -868,6 +983,7 @@ impl ClerkFapiClient {
     }
 
     // Organization API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_organization(
         &self,
         name: Option<&str>,
@@ -877,6 +993,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_organization(
         &self,
         organization_id: &str,
@@ -889,6 +1006,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_organization_logo(
         &self,
         organization_id: &str,
@@ -902,6 +1020,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_organization(
         &self,
         organization_id: &str,
@@ -912,6 +1031,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_organization(
         &self,
         organization_id: &str,
@@ -929,6 +1049,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_organization_logo(
         &self,
         organization_id: &str,
@@ -942,6 +1063,7 @@ impl ClerkFapiClient {
     }
 
     // Organization Memberships API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn accept_organization_invitation(
         &self,
         invitation_id: &str,
@@ -956,6 +1078,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn accept_organization_suggestion(
         &self,
         suggestion_id: &str,
@@ -969,6 +1092,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_organization_memberships(
         &self,
         organization_id: &str,
@@ -984,6 +1108,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_organization_memberships(
         &self,
         limit: Option<i64>,
@@ -1007,6 +1132,30 @@ impl ClerkFapiClient {
         Ok(res)
     }
 
+    /// Same as `get_organization_memberships`, returning the wrapped
+    /// response that keeps the `total_count` of paginated requests
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_organization_memberships_with_total_count(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        paginated: Option<bool>,
+    ) -> Result<
+        ClientClientWrappedOrganizationMembershipsResponse,
+        Error<GetOrganizationMembershipsError>,
+    > {
+        let response = organizations_memberships_api::get_organization_memberships(
+            &self.clerk_config(),
+            limit,
+            offset,
+            paginated,
+        )
+        .await?;
+        self.handle_client_update(*response.client.clone());
+        Ok(*response.response)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_organization_suggestions(
         &self,
         limit: Option<i64>,
@@ -1027,6 +1176,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_users_organization_invitations(
         &self,
         limit: Option<i64>,
@@ -1048,6 +1198,7 @@ impl ClerkFapiClient {
     }
 
     // Passkeys API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn attempt_passkey_verification(
         &self,
         passkey_id: &str,
@@ -1067,6 +1218,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_passkey(
         &self,
         passkey_id: &str,
@@ -1078,6 +1230,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn patch_passkey(
         &self,
         passkey_id: &str,
@@ -1088,6 +1241,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn post_passkey(
         &self,
         _clerk_session_id: Option<&str>,
@@ -1105,6 +1259,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn read_passkey(
         &self,
         passkey_id: &str,
@@ -1115,6 +1270,7 @@ impl ClerkFapiClient {
     }
 
     // Phone Numbers API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn delete_phone_number(
         &self,
         phone_number_id: &str,
@@ -1132,6 +1288,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn get_phone_numbers(
         &self,
         clerk_session_id: Option<&str>,
@@ -1139,6 +1296,7 @@ impl ClerkFapiClient {
         phone_numbers_api::get_phone_numbers(&self.clerk_config(), clerk_session_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn post_phone_numbers(
         &self,
         phone_number: &str,
@@ -1156,6 +1314,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn read_phone_number(
         &self,
         phone_number_id: &str,
@@ -1171,6 +1330,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn send_verification_sms(
         &self,
         phone_number_id: &str,
@@ -1188,6 +1348,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn update_phone_number(
         &self,
         phone_number_id: &str,
@@ -1207,6 +1368,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn verify_phone_number(
         &self,
         phone_number_id: &str,
@@ -1225,6 +1387,7 @@ impl ClerkFapiClient {
     }
 
     // Redirect API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn redirect_to_url(
         &self,
         redirect_url: Option<&str>,
@@ -1233,6 +1396,7 @@ impl ClerkFapiClient {
     }
 
     // Roles API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn list_organization_roles(
         &self,
         organization_id: &str,
@@ -1251,10 +1415,12 @@ impl ClerkFapiClient {
     }
 
     // SAML API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn acs(&self, saml_connection_id: &str) -> Result<(), Error<AcsError>> {
         saml_api::acs(&self.clerk_config(), saml_connection_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn saml_metadata(
         &self,
         saml_connection_id: &str,
@@ -1263,6 +1429,7 @@ impl ClerkFapiClient {
     }
 
     // Sessions API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn attempt_session_reverification_first_factor(
         &self,
         session_id: &str,
@@ -1289,6 +1456,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn attempt_session_reverification_second_factor(
         &self,
         session_id: &str,
@@ -1309,6 +1477,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn create_session_token(
         &self,
         session_id: &str,
@@ -1317,6 +1486,7 @@ impl ClerkFapiClient {
         sessions_api::create_session_token(&self.clerk_config(), session_id, organization_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn create_session_token_with_template(
         &self,
         session_id: &str,
@@ -1330,6 +1500,7 @@ impl ClerkFapiClient {
         .await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn end_session(
         &self,
         session_id: &str,
@@ -1341,6 +1512,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn get_session(
         &self,
         session_id: &str,
@@ -1352,6 +1524,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn prepare_session_reverification_first_factor(
         &self,
         session_id: &str,
@@ -1376,6 +1549,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn prepare_session_reverification_second_factor(
         &self,
         session_id: &str,
@@ -1396,6 +1570,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn remove_client_sessions_and_retain_cookie(
         &self,
     ) -> Result<Option<ClientClient>, Error<RemoveClientSessionsAndRetainCookieError>> {
@@ -1407,6 +1582,7 @@ impl ClerkFapiClient {
         Ok(response.response.map(|s| *s))
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn remove_session(
         &self,
         session_id: &str,
@@ -1418,6 +1594,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn start_session_reverification(
         &self,
         session_id: &str,
@@ -1432,6 +1609,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = session_id)))]
     pub async fn touch_session(
         &self,
         session_id: &str,
@@ -1447,6 +1625,7 @@ impl ClerkFapiClient {
     }
 
     // Sign Ins API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn accept_ticket(
         &self,
         ticket: &str,
@@ -1454,6 +1633,7 @@ impl ClerkFapiClient {
         sign_ins_api::accept_ticket(&self.clerk_config(), ticket).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn attempt_sign_in_factor_one(
         &self,
         sign_in_id: &str,
@@ -1485,6 +1665,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn attempt_sign_in_factor_two(
         &self,
         sign_in_id: &str,
@@ -1505,6 +1686,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_sign_in(
         &self,
         origin: Option<&str>,
@@ -1542,17 +1724,36 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_sign_in(
         &self,
         sign_in_id: &str,
     ) -> Result<ClientSignIn, Error<GetSignInError>> {
-        let response = sign_ins_api::get_sign_in(&self.clerk_config(), sign_in_id).await?;
+        let response = sign_ins_api::get_sign_in(&self.clerk_config(), sign_in_id, None).await?;
         if let Some(client) = response.client.clone() {
             self.handle_client_update(*client);
         };
         Ok(*response.response)
     }
 
+    /// Same as `get_sign_in`, passing the `rotating_token_nonce` received on the
+    /// OAuth redirect so that the client token is rotated for this device
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_sign_in_with_nonce(
+        &self,
+        sign_in_id: &str,
+        rotating_token_nonce: &str,
+    ) -> Result<ClientSignIn, Error<GetSignInError>> {
+        let response =
+            sign_ins_api::get_sign_in(&self.clerk_config(), sign_in_id, Some(rotating_token_nonce))
+                .await?;
+        if let Some(client) = response.client.clone() {
+            self.handle_client_update(*client);
+        };
+        Ok(*response.response)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn prepare_sign_in_factor_one(
         &self,
         sign_in_id: &str,
@@ -1588,6 +1789,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn prepare_sign_in_factor_two(
         &self,
         sign_in_id: &str,
@@ -1607,6 +1809,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn reset_password(
         &self,
         sign_in_id: &str,
@@ -1626,11 +1829,13 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn verify(&self, token: &str) -> Result<(), Error<VerifyError>> {
         sign_ins_api::verify(&self.clerk_config(), token).await
     }
 
     // Sign Ups API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn attempt_sign_ups_verification(
         &self,
         sign_up_id: &str,
@@ -1656,6 +1861,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn create_sign_ups(
         &self,
         origin: Option<&str>,
@@ -1715,17 +1921,39 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_sign_ups(
         &self,
         sign_up_id: &str,
     ) -> Result<ClientSignUp, Error<GetSignUpsError>> {
-        let response = sign_ups_api::get_sign_ups(&self.clerk_config(), sign_up_id).await?;
+        let response = sign_ups_api::get_sign_ups(&self.clerk_config(), sign_up_id, None).await?;
+        if let Some(client) = response.client.clone() {
+            self.handle_client_update(*client);
+        };
+        Ok(*response.response)
+    }
+
+    /// Same as `get_sign_ups`, with the `rotating_token_nonce` from the
+    /// redirect, see `get_sign_in_with_nonce`
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_sign_ups_with_nonce(
+        &self,
+        sign_up_id: &str,
+        rotating_token_nonce: &str,
+    ) -> Result<ClientSignUp, Error<GetSignUpsError>> {
+        let response = sign_ups_api::get_sign_ups(
+            &self.clerk_config(),
+            sign_up_id,
+            Some(rotating_token_nonce),
+        )
+        .await?;
         if let Some(client) = response.client.clone() {
             self.handle_client_update(*client);
         };
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn prepare_sign_ups_verification(
         &self,
         sign_up_id: &str,
@@ -1753,6 +1981,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_sign_ups(
         &self,
         sign_up_id: &str,
@@ -1807,6 +2036,7 @@ impl ClerkFapiClient {
     }
 
     // TOTP API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_totp(&self) -> Result<ClientDeletedObject, Error<DeleteTotpError>> {
         let response = totp_api::delete_totp(&self.clerk_config()).await?;
         if let Some(client) = response.client.clone() {
@@ -1815,12 +2045,14 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn post_totp(&self) -> Result<Totp, Error<PostTotpError>> {
         let response = totp_api::post_totp(&self.clerk_config()).await?;
         self.handle_client_update(*response.client.clone());
         Ok(response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn verify_totp(&self, code: Option<&str>) -> Result<Totp, Error<VerifyTotpError>> {
         let response = totp_api::verify_totp(&self.clerk_config(), code).await?;
         self.handle_client_update(*response.client.clone());
@@ -1828,6 +2060,7 @@ impl ClerkFapiClient {
     }
 
     // User API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn change_password(
         &self,
         current_password: Option<&str>,
@@ -1845,6 +2078,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn create_service_token(
         &self,
         service: &str,
@@ -1853,6 +2087,7 @@ impl ClerkFapiClient {
         user_api::create_service_token(&self.clerk_config(), service, _clerk_session_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_profile_image(
         &self,
     ) -> Result<ClientDeletedObject, Error<DeleteProfileImageError>> {
@@ -1863,6 +2098,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_user(&self) -> Result<ClientDeletedObject, Error<DeleteUserError>> {
         let response = user_api::delete_user(&self.clerk_config()).await?;
         if let Some(client) = response.client.clone() {
@@ -1871,12 +2107,14 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_user(&self) -> Result<ClientUser, Error<GetUserError>> {
         let response = user_api::get_user(&self.clerk_config()).await?;
         self.handle_client_update(*response.client.clone());
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn patch_user(
         &self,
         username: Option<&str>,
@@ -1902,6 +2140,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn remove_password(
         &self,
         current_password: Option<&str>,
@@ -1912,6 +2151,7 @@ impl ClerkFapiClient {
     }
 
     /// Does not work, file upload not implemented yet
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn update_profile_image(
         &self,
         file: FileData,
@@ -1924,6 +2164,7 @@ impl ClerkFapiClient {
     }
 
     // Waitlist API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn join_waitlist(
         &self,
         email_address: &str,
@@ -1932,6 +2173,7 @@ impl ClerkFapiClient {
     }
 
     // Web3 Wallets API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn attempt_web3_wallet_verification(
         &self,
         web3_wallet_id: &str,
@@ -1949,6 +2191,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn delete_web3_wallet(
         &self,
         web3_wallet_id: &str,
@@ -1961,6 +2204,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = clerk_session_id)))]
     pub async fn get_web3_wallets(
         &self,
         clerk_session_id: Option<&str>,
@@ -1968,6 +2212,7 @@ impl ClerkFapiClient {
         web3_wallets_api::get_web3_wallets(&self.clerk_config(), clerk_session_id).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(session_id = _clerk_session_id)))]
     pub async fn post_web3_wallets(
         &self,
         web3_wallet: &str,
@@ -1983,6 +2228,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn prepare_web3_wallet_verification(
         &self,
         web3_wallet_id: &str,
@@ -2002,6 +2248,7 @@ impl ClerkFapiClient {
         Ok(*response.response)
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn read_web3_wallet(
         &self,
         web3_wallet_id: &str,
@@ -2013,22 +2260,26 @@ impl ClerkFapiClient {
     }
 
     // Well Known API methods
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_android_asset_links(
         &self,
     ) -> Result<Vec<serde_json::Value>, Error<GetAndroidAssetLinksError>> {
         well_known_api::get_android_asset_links(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_apple_app_site_association(
         &self,
     ) -> Result<WellKnownAppleAppSiteAssociation, Error<GetAppleAppSiteAssociationError>> {
         well_known_api::get_apple_app_site_association(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_jwks(&self) -> Result<Jwks, Error<GetJwksError>> {
         well_known_api::get_jwks(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_o_auth2_authorization_server_metadata(
         &self,
     ) -> Result<
@@ -2038,6 +2289,7 @@ impl ClerkFapiClient {
         well_known_api::get_o_auth2_authorization_server_metadata(&self.clerk_config()).await
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_open_id_configuration(
         &self,
     ) -> Result<WellKnownOpenIdConfiguration, Error<GetOpenIdConfigurationError>> {
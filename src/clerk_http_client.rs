@@ -1,10 +1,51 @@
+use futures_timer::Delay;
 use log::{debug, error, warn};
 use parking_lot::RwLock;
 use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::{Client as ReqwestClient, Request, Response};
+use std::error::Error;
+use std::fmt;
 use std::sync::Arc;
+use web_time::Instant;
 
-use crate::{clerk_state::ClerkState, configuration::ClientKind};
+use crate::{
+    clerk_state::ClerkState,
+    configuration::ClientKind,
+    middleware::Middleware,
+    retry::{retry_after, RetryPolicy},
+};
+
+#[cfg(feature = "tracing")]
+use crate::telemetry;
+
+/// Error from `ClerkHttpClient::execute`
+#[derive(Debug)]
+pub enum ClerkHttpError {
+    Reqwest(reqwest::Error),
+    /// A middleware rejected the request or the response
+    Middleware(anyhow::Error),
+}
+impl fmt::Display for ClerkHttpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkHttpError::Reqwest(e) => e.fmt(f),
+            ClerkHttpError::Middleware(e) => e.fmt(f),
+        }
+    }
+}
+impl Error for ClerkHttpError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClerkHttpError::Reqwest(e) => Some(e),
+            ClerkHttpError::Middleware(e) => Some(e.as_ref()),
+        }
+    }
+}
+impl From<reqwest::Error> for ClerkHttpError {
+    fn from(e: reqwest::Error) -> Self {
+        ClerkHttpError::Reqwest(e)
+    }
+}
 
 /// Custom client wrapper that behaves like reqwest::Client but adds Clerk-specific functionality
 #[derive(Debug)]
@@ -13,6 +54,8 @@ pub struct ClerkHttpClient {
     state: Arc<RwLock<ClerkState>>,
     client_kind: ClientKind,
     dev_browser_token_id: RwLock<Option<String>>,
+    retry_policy: RetryPolicy,
+    middlewares: Vec<Arc<dyn Middleware>>,
 }
 
 impl std::fmt::Display for ClerkHttpClient {
@@ -38,9 +81,23 @@ impl ClerkHttpClient {
             state,
             client_kind,
             dev_browser_token_id: RwLock::new(None),
+            retry_policy: RetryPolicy::default(),
+            middlewares: Vec::new(),
         }
     }
 
+    /// Sets the policy for retrying failed requests
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets the middlewares run around every request, in order
+    pub fn with_middlewares(mut self, middlewares: Vec<Arc<dyn Middleware>>) -> Self {
+        self.middlewares = middlewares;
+        self
+    }
+
     /// When running in browser one needs "DevBrowser auth" when
     /// running against Clerk development environment
     pub fn set_dev_browser_token_id(&self, token_id: String) {
@@ -100,32 +157,93 @@ impl ClerkHttpClient {
         }
     }
 
-    pub async fn execute(&self, request: Request) -> Result<Response, reqwest::Error> {
-        // FOR DEBUG
-        // let method = request.method().clone();
-        // let url = request.url().clone();
-        // END FOR DEBUG
-
-        let processed_request = self.process_request(request);
-        let response = self.inner.execute(processed_request).await?;
-
-        // FOR DEBUG
-        // let status = response.status();
-        // let version = response.version();
-        // let headers = response.headers().clone();
-        // let resp_text = response.text().await?;
-        // println!("[DEBUG] Request {} {} -> Response {}", method, url, status);
-        // println!("[DEBUG] Response body: {}", resp_text);
-        // let mut builder = http::Response::builder().status(status).version(version);
-        // let builder_headers = builder.headers_mut().unwrap();
-        // for (key, value) in headers.iter() {
-        //     builder_headers.insert(key, value.clone());
-        // }
-        // let response = Response::from(builder.body(resp_text).unwrap());
-        // END FOR DEBUG
-
-        self.process_response(&response);
-        Ok(response)
+    pub async fn execute(&self, request: Request) -> Result<Response, ClerkHttpError> {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+            let session_id = self.state.read().session_id();
+            let span = telemetry::request_span(&request, session_id.as_deref());
+            let started = Instant::now();
+            let result = self
+                .execute_with_retries(request)
+                .instrument(span.clone())
+                .await;
+            telemetry::record_result(&span, started, result).await
+        }
+        #[cfg(not(feature = "tracing"))]
+        {
+            self.execute_with_retries(request).await
+        }
+    }
+
+    async fn execute_with_retries(&self, request: Request) -> Result<Response, ClerkHttpError> {
+        let policy = &self.retry_policy;
+        let started = Instant::now();
+        let method = request.method().clone();
+        let mut retry = 0;
+        let mut request = request;
+
+        loop {
+            // Streaming bodies can't be cloned, those are sent only once
+            let next_request = if retry < policy.max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            let mut processed_request = self.process_request(request);
+            for middleware in &self.middlewares {
+                middleware
+                    .before_request(&mut processed_request)
+                    .map_err(ClerkHttpError::Middleware)?;
+            }
+            let result = self.inner.execute(processed_request).await;
+
+            let (delay, reason) = match &result {
+                Ok(response) => {
+                    self.process_response(response);
+                    for middleware in &self.middlewares {
+                        middleware
+                            .after_response(response)
+                            .map_err(ClerkHttpError::Middleware)?;
+                    }
+                    if !policy.should_retry_response(&method, response) {
+                        return Ok(result?);
+                    }
+                    (
+                        retry_after(response).unwrap_or_else(|| policy.backoff(retry + 1)),
+                        format!("status {}", response.status()),
+                    )
+                }
+                Err(e) => {
+                    for middleware in &self.middlewares {
+                        middleware.on_error(e);
+                    }
+                    if !policy.should_retry_error(&method, e) {
+                        return Ok(result?);
+                    }
+                    (policy.backoff(retry + 1), e.to_string())
+                }
+            };
+
+            let Some(next) = next_request else {
+                return Ok(result?);
+            };
+            if started.elapsed() + delay > policy.max_elapsed {
+                warn!("ClerkHttpClient: Not retrying {method} after {reason}, out of time");
+                return Ok(result?);
+            }
+
+            retry += 1;
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("retries", retry);
+            warn!(
+                "ClerkHttpClient: Retrying {method} in {delay:?} after {reason}, retry {retry}/{}",
+                policy.max_retries
+            );
+            Delay::new(delay).await;
+            request = next;
+        }
     }
 
     pub fn request<U: reqwest::IntoUrl>(
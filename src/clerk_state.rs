@@ -8,7 +8,31 @@ use crate::{
     ClerkFapiConfiguration,
 };
 use log::{error, warn};
-use std::{error::Error, fmt, sync::Arc};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "tracing")]
+use crate::telemetry;
+use std::{
+    error::Error,
+    fmt,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    sync::Arc,
+};
+
+/// Store keys of the times the environment and client were last stored,
+/// in milliseconds since the epoch
+pub(crate) const ENVIRONMENT_STORED_AT: &str = "environment_stored_at";
+pub(crate) const CLIENT_STORED_AT: &str = "client_stored_at";
+/// Store key of the dev browser the stored client belongs to, in
+/// development instances with `ClientKind::Browser`
+pub(crate) const DEV_BROWSER_TOKEN_ID: &str = "dev_browser_token_id";
+
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
 
 pub type ClerkStateCallback = Arc<
     dyn Fn(Client, Option<Session>, Option<User>, Option<Organization>) + Send + Sync + 'static,
@@ -46,6 +70,10 @@ pub struct ClerkState {
     config: ClerkFapiConfiguration,
     /// Callback for Client state change
     callback: ClerkStateCallback,
+    /// While above zero `emit_state` only marks the emit pending, so that
+    /// operations making several requests emit once, see `hold_emits`
+    emits_held: AtomicUsize,
+    emit_pending: AtomicBool,
 }
 
 impl fmt::Debug for ClerkState {
@@ -56,7 +84,7 @@ impl fmt::Debug for ClerkState {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ClerkNotLoadedError {
     NotLoaded,
     MissingEnvironment,
@@ -89,6 +117,8 @@ impl ClerkState {
             target_organization_id: None,
             config,
             callback: Arc::new(callback),
+            emits_held: AtomicUsize::new(0),
+            emit_pending: AtomicBool::new(false),
         }
     }
     /// Doesn't matter how we end up loading the environemnt and client
@@ -115,6 +145,10 @@ impl ClerkState {
     }
 
     pub fn emit_state(&self) {
+        if self.emits_held.load(Ordering::SeqCst) > 0 {
+            self.emit_pending.store(true, Ordering::SeqCst);
+            return;
+        }
         if let Some(client) = self.client.clone() {
             (self.callback)(
                 client,
@@ -127,6 +161,19 @@ impl ClerkState {
         }
     }
 
+    /// Defers the emits until `release_emits` has been called as many
+    /// times as this
+    pub(crate) fn hold_emits(&self) {
+        self.emits_held.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Releases one hold, returns true when it was the last one and an
+    /// emit was deferred meanwhile
+    pub(crate) fn release_emits(&self) -> bool {
+        self.emits_held.fetch_sub(1, Ordering::SeqCst) == 1
+            && self.emit_pending.swap(false, Ordering::SeqCst)
+    }
+
     pub fn environment(&self) -> Result<Environment, ClerkNotLoadedError> {
         if !self.loaded {
             Err(ClerkNotLoadedError::NotLoaded)
@@ -140,6 +187,8 @@ impl ClerkState {
         self.environment = Some(environment.clone());
         if let Ok(value) = serde_json::to_value(environment.clone()) {
             self.config.set_store_value("environment", value);
+            self.config
+                .set_store_value(ENVIRONMENT_STORED_AT, now_millis());
         } else {
             error!("ClerkState: Failed to serialize environment");
         }
@@ -155,9 +204,12 @@ impl ClerkState {
         }
     }
     pub fn set_client(&mut self, client: Client) {
+        #[cfg(feature = "tracing")]
+        let previous = self.active_ids();
         self.client = Some(client.clone());
         if let Ok(value) = serde_json::to_value(client.clone()) {
             self.config.set_store_value("client", value);
+            self.config.set_store_value(CLIENT_STORED_AT, now_millis());
         } else {
             error!("ClerkState: Failed to serialize client");
         }
@@ -190,15 +242,17 @@ impl ClerkState {
                 self.target_organization_id = None;
 
                 if let Some(last_active_org_id) = org_id_target {
-                    if let Some(ref memberships) = user.organization_memberships {
-                        if let Some(active_org) = memberships
-                            .iter()
-                            .find(|m| m.organization.id == last_active_org_id.clone())
-                            .map(|m| m.organization.clone())
-                        {
-                            self.organization = Some(*active_org);
-                        }
-                    }
+                    // Without the membership the user is no longer in the
+                    // organization, example after leaving it
+                    self.organization = user
+                        .organization_memberships
+                        .as_ref()
+                        .and_then(|memberships| {
+                            memberships
+                                .iter()
+                                .find(|m| m.organization.id == last_active_org_id)
+                        })
+                        .map(|m| *m.organization.clone());
                 } else {
                     self.organization = None;
                 }
@@ -208,6 +262,24 @@ impl ClerkState {
             self.user = None;
             self.organization = None;
         }
+
+        #[cfg(feature = "tracing")]
+        telemetry::record_state_change(&previous, &self.active_ids());
+    }
+
+    #[cfg(feature = "tracing")]
+    fn active_ids(&self) -> telemetry::ActiveIds {
+        telemetry::ActiveIds {
+            session_id: self.session_id(),
+            user_id: self.user.as_ref().map(|u| u.id.clone()),
+            organization_id: self.organization.as_ref().map(|o| o.id.clone()),
+        }
+    }
+
+    /// Id of the active session if any
+    #[cfg(feature = "tracing")]
+    pub(crate) fn session_id(&self) -> Option<String> {
+        self.session.as_ref().map(|s| s.id.clone())
     }
 
     pub fn authorization_header(&mut self) -> Option<String> {
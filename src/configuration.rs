@@ -1,5 +1,8 @@
 use crate::apis::configuration::Configuration as ApiConfiguration;
 use crate::clerk_http_client::ClerkHttpClient;
+use crate::middleware::Middleware;
+use crate::retry::RetryPolicy;
+use crate::sessions::SingleSessionSignIn;
 use base64::{engine::general_purpose, Engine as _};
 use futures::future::BoxFuture;
 use parking_lot::RwLock;
@@ -22,6 +25,8 @@ const PUBLISHABLE_KEY_TEST_PREFIX: &str = "pk_test_";
 struct PublishableKey {
     instance_type: String,
     frontend_api: String,
+    /// Frontend API of the instance, even when requests go through a proxy
+    instance_frontend_api: String,
 }
 
 fn parse_publishable_key(
@@ -46,17 +51,20 @@ fn parse_publishable_key(
 
     frontend_api.pop(); // Remove the last character as it's $
 
-    if let Some(proxy) = proxy_url {
-        frontend_api = proxy;
-    } else if instance_type != "development" {
+    if instance_type != "development" {
         if let Some(d) = domain {
             frontend_api = format!("clerk.{d}");
         }
     }
+    let instance_frontend_api = frontend_api.clone();
+    if let Some(proxy) = proxy_url {
+        frontend_api = proxy;
+    }
 
     Ok(PublishableKey {
         instance_type,
         frontend_api,
+        instance_frontend_api,
     })
 }
 
@@ -144,10 +152,14 @@ pub struct ClerkFapiConfiguration {
     pub(crate) base_url: String,
     pub(crate) instance_type: String,
     pub(crate) frontend_api: String,
+    pub(crate) issuer: String,
     pub(crate) user_agent: String,
     pub(crate) store: Arc<dyn Store>,
     pub(crate) store_prefix: String,
     pub(crate) kind: ClientKind,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) single_session_sign_in: SingleSessionSignIn,
+    pub(crate) middlewares: Vec<Arc<dyn Middleware>>,
 }
 
 impl ClerkFapiConfiguration {
@@ -194,10 +206,14 @@ impl ClerkFapiConfiguration {
             },
             instance_type: parsed_key.instance_type,
             frontend_api: parsed_key.frontend_api,
+            issuer: format!("https://{}", parsed_key.instance_frontend_api),
             user_agent,
             store,
             store_prefix,
             kind,
+            retry_policy: RetryPolicy::default(),
+            single_session_sign_in: SingleSessionSignIn::default(),
+            middlewares: Vec::new(),
         })
     }
 
@@ -216,6 +232,12 @@ impl ClerkFapiConfiguration {
         &self.frontend_api
     }
 
+    /// Returns the `iss` claim of the session tokens, the Frontend API of
+    /// the instance also when the requests go through a proxy
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
     /// Returns the User-Agent string
     pub fn user_agent(&self) -> &str {
         &self.user_agent
@@ -231,6 +253,42 @@ impl ClerkFapiConfiguration {
         &self.store_prefix
     }
 
+    /// Sets the policy for retrying failed requests, see `RetryPolicy`
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Returns the policy for retrying failed requests
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Sets what signing in does in single session mode when a session is
+    /// already active, see `SingleSessionSignIn`
+    pub fn with_single_session_sign_in(mut self, behavior: SingleSessionSignIn) -> Self {
+        self.single_session_sign_in = behavior;
+        self
+    }
+
+    /// Returns what signing in does in single session mode when a session
+    /// is already active
+    pub fn single_session_sign_in(&self) -> SingleSessionSignIn {
+        self.single_session_sign_in
+    }
+
+    /// Adds a middleware run around every request, middlewares run in the
+    /// order they are added, see `Middleware`
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Returns the registered middlewares
+    pub fn middlewares(&self) -> &[Arc<dyn Middleware>] {
+        &self.middlewares
+    }
+
     /// Helper method to get prefixed key
     fn get_prefixed_key(&self, key: impl AsRef<str>) -> String {
         format!("{}{}", self.store_prefix, key.as_ref())
@@ -305,10 +363,14 @@ impl Default for ClerkFapiConfiguration {
             base_url: String::new(),
             instance_type: String::new(),
             frontend_api: String::new(),
+            issuer: String::new(),
             user_agent: format!("{NAME}/{VERSION}"),
             store: Arc::new(DefaultStore::default()),
             store_prefix: "ClerkFapi:".to_string(),
             kind: ClientKind::NonBrowser,
+            retry_policy: RetryPolicy::default(),
+            single_session_sign_in: SingleSessionSignIn::default(),
+            middlewares: Vec::new(),
         }
     }
 }
@@ -348,6 +410,7 @@ mod tests {
         )
         .unwrap();
         assert_eq!(config.base_url(), "https://proxy.example.com");
+        assert_eq!(config.issuer(), "https://clerk.example.com");
     }
 
     #[test]
@@ -360,6 +423,7 @@ mod tests {
         .unwrap();
         assert_eq!(config.frontend_api(), "clerk.example.com");
         assert_eq!(config.base_url(), "https://clerk.example.com");
+        assert_eq!(config.issuer(), "https://clerk.example.com");
     }
 
     #[test]
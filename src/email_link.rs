@@ -0,0 +1,451 @@
+use crate::clerk::{Clerk, ClerkSetActiveError};
+use crate::models::stubs_verification_link::Status as LinkStatus;
+use crate::models::{ClientSignIn, ClientSignInFirstFactorVerification, StubsVerificationLink};
+use crate::sign_in::{strategy_name, ClerkSignInError, PrepareFirstFactor, SignInFlow};
+use crate::sign_up::{
+    ClerkSignUpError, PrepareVerification, SignUpField, SignUpFlow, SignUpParams,
+};
+use futures::future::{self, Either};
+use futures::task::AtomicWaker;
+use futures_timer::Delay;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+use url::Url;
+use web_time::Instant;
+
+/// Interval of the first poll, doubled after every poll
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest interval between polls
+pub const DEFAULT_MAX_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// `__clerk_status` Clerk adds to the redirect url once the link is opened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmailLinkStatus {
+    Verified,
+    /// Verified, and the flow continues in the tab that sent the link
+    VerifiedSwitchTab,
+    Expired,
+    Failed,
+    /// The link was opened on another device and the instance requires the
+    /// same client
+    ClientMismatch,
+    /// Statuses this crate doesn't know about
+    Other(String),
+}
+
+impl EmailLinkStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EmailLinkStatus::Verified => "verified",
+            EmailLinkStatus::VerifiedSwitchTab => "verified_switch_tab",
+            EmailLinkStatus::Expired => "expired",
+            EmailLinkStatus::Failed => "failed",
+            EmailLinkStatus::ClientMismatch => "client_mismatch",
+            EmailLinkStatus::Other(status) => status,
+        }
+    }
+
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "verified" => EmailLinkStatus::Verified,
+            "verified_switch_tab" => EmailLinkStatus::VerifiedSwitchTab,
+            "expired" => EmailLinkStatus::Expired,
+            "failed" => EmailLinkStatus::Failed,
+            "client_mismatch" => EmailLinkStatus::ClientMismatch,
+            other => EmailLinkStatus::Other(other.to_string()),
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        matches!(
+            self,
+            EmailLinkStatus::Verified | EmailLinkStatus::VerifiedSwitchTab
+        )
+    }
+}
+
+impl fmt::Display for EmailLinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parameters Clerk adds to the redirect url of the email link
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmailLinkRedirect {
+    /// `__clerk_status`
+    pub status: Option<EmailLinkStatus>,
+    /// `__clerk_created_session`, the session created by the verification
+    pub created_session_id: Option<String>,
+}
+
+impl EmailLinkRedirect {
+    pub fn parse(url: &str) -> Result<Self, ClerkEmailLinkError> {
+        let url =
+            Url::parse(url).map_err(|e| ClerkEmailLinkError::InvalidRedirect(e.to_string()))?;
+        let mut redirect = Self::default();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "__clerk_status" => redirect.status = Some(EmailLinkStatus::parse(&value)),
+                "__clerk_created_session" => redirect.created_session_id = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+        Ok(redirect)
+    }
+
+    /// Whether Clerk reported that the verification didn't succeed
+    pub fn is_failed(&self) -> bool {
+        self.status.as_ref().is_some_and(|s| !s.is_verified())
+    }
+}
+
+#[derive(Debug)]
+pub enum ClerkEmailLinkError {
+    ClerkSignInError(ClerkSignInError),
+    ClerkSignUpError(ClerkSignUpError),
+    ClerkSetActiveError(ClerkSetActiveError),
+    InvalidRedirect(String),
+    /// The link expired or the verification failed, with the status
+    VerificationFailed(String),
+    /// The link wasn't opened in time
+    Timeout,
+    /// Waiting was cancelled with `EmailLinkCancel::cancel`
+    Cancelled,
+}
+impl fmt::Display for ClerkEmailLinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkEmailLinkError::ClerkSignInError(e) => e.fmt(f),
+            ClerkEmailLinkError::ClerkSignUpError(e) => e.fmt(f),
+            ClerkEmailLinkError::ClerkSetActiveError(e) => e.fmt(f),
+            ClerkEmailLinkError::InvalidRedirect(e) => {
+                write!(f, "Invalid email link redirect url: {e}")
+            }
+            ClerkEmailLinkError::VerificationFailed(status) => {
+                write!(f, "Email link verification failed, status {status}")
+            }
+            ClerkEmailLinkError::Timeout => write!(f, "Timed out waiting for the email link"),
+            ClerkEmailLinkError::Cancelled => write!(f, "Waiting for the email link was cancelled"),
+        }
+    }
+}
+impl Error for ClerkEmailLinkError {}
+
+impl ClerkEmailLinkError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&crate::error::ClerkApiError> {
+        match self {
+            ClerkEmailLinkError::ClerkSignInError(e) => e.api_error(),
+            ClerkEmailLinkError::ClerkSignUpError(e) => e.api_error(),
+            ClerkEmailLinkError::ClerkSetActiveError(e) => e.api_error(),
+            _ => None,
+        }
+    }
+}
+
+/// Cancels `EmailLinkFlow::wait` from elsewhere, example when the user
+/// goes back to pick another sign in method
+#[derive(Debug, Clone, Default)]
+pub struct EmailLinkCancel {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl EmailLinkCancel {
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.waker.wake();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once cancelled
+    async fn cancelled(&self) {
+        future::poll_fn(|cx| {
+            self.inner.waker.register(cx.waker());
+            if self.is_cancelled() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+/// Sign in or sign up the email link verifies
+#[derive(Debug, Clone)]
+pub enum EmailLinkAttempt {
+    SignIn(Box<SignInFlow>),
+    SignUp(Box<SignUpFlow>),
+}
+
+impl EmailLinkAttempt {
+    pub fn is_complete(&self) -> bool {
+        match self {
+            EmailLinkAttempt::SignIn(sign_in) => sign_in.is_complete(),
+            EmailLinkAttempt::SignUp(sign_up) => sign_up.is_complete(),
+        }
+    }
+
+    /// Session created by the sign in or sign up, set once it is complete
+    pub fn created_session_id(&self) -> Option<&str> {
+        match self {
+            EmailLinkAttempt::SignIn(sign_in) => sign_in.created_session_id(),
+            EmailLinkAttempt::SignUp(sign_up) => sign_up.created_session_id(),
+        }
+    }
+}
+
+/// Email link sign in or sign up waiting for the user to open the link
+///
+/// The flow is started with `Clerk::sign_in_with_email_link` or
+/// `Clerk::sign_up_with_email_link`, which send the email. The link can be
+/// opened on any device, `wait` polls the sign in or sign up with backoff
+/// until it's verified. When the link is opened on the same device the app
+/// receives the redirect, example as a deep link, and passes it to
+/// `complete` instead. Either way the created session is set active.
+///
+/// A sign in can still need the second factor once the link is verified.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use std::time::Duration;
+///
+/// let flow = clerk
+///     .sign_in_with_email_link("user@example.com", "myapp://email-link")
+///     .await?;
+/// let cancel = flow.cancel_handle();
+/// // Call cancel.cancel() to stop waiting, example from a back button
+/// let attempt = flow.wait(Duration::from_secs(600)).await?;
+/// assert!(attempt.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+pub struct EmailLinkFlow {
+    clerk: Clerk,
+    attempt: EmailLinkAttempt,
+    cancel: EmailLinkCancel,
+    poll_interval: Duration,
+    max_poll_interval: Duration,
+}
+
+impl fmt::Debug for EmailLinkFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EmailLinkFlow")
+            .field("attempt", &self.attempt)
+            .field("cancelled", &self.cancel.is_cancelled())
+            .finish()
+    }
+}
+
+impl EmailLinkFlow {
+    /// Starts a sign in with the email address and sends the link, which
+    /// redirects to `redirect_url` once opened
+    pub async fn sign_in(
+        clerk: &Clerk,
+        email_address: &str,
+        redirect_url: &str,
+    ) -> Result<Self, ClerkEmailLinkError> {
+        let mut sign_in = SignInFlow::create(clerk, email_address)
+            .await
+            .map_err(ClerkEmailLinkError::ClerkSignInError)?;
+        sign_in
+            .prepare_first_factor(PrepareFirstFactor::EmailLink {
+                redirect_url: redirect_url.to_string(),
+            })
+            .await
+            .map_err(ClerkEmailLinkError::ClerkSignInError)?;
+        Ok(Self::new(
+            clerk,
+            EmailLinkAttempt::SignIn(Box::new(sign_in)),
+        ))
+    }
+
+    /// Starts a sign up with the params and sends the link to verify the
+    /// email address
+    pub async fn sign_up(
+        clerk: &Clerk,
+        params: SignUpParams,
+        redirect_url: &str,
+    ) -> Result<Self, ClerkEmailLinkError> {
+        let mut sign_up = SignUpFlow::create(clerk, params)
+            .await
+            .map_err(ClerkEmailLinkError::ClerkSignUpError)?;
+        sign_up
+            .prepare_verification(PrepareVerification::EmailLink {
+                redirect_url: redirect_url.to_string(),
+            })
+            .await
+            .map_err(ClerkEmailLinkError::ClerkSignUpError)?;
+        Ok(Self::new(
+            clerk,
+            EmailLinkAttempt::SignUp(Box::new(sign_up)),
+        ))
+    }
+
+    fn new(clerk: &Clerk, attempt: EmailLinkAttempt) -> Self {
+        Self {
+            clerk: clerk.clone(),
+            attempt,
+            cancel: EmailLinkCancel::default(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_poll_interval: DEFAULT_MAX_POLL_INTERVAL,
+        }
+    }
+
+    /// Sets the interval of the first poll and the longest interval the
+    /// backoff grows to
+    pub fn with_poll_interval(mut self, initial: Duration, max: Duration) -> Self {
+        self.poll_interval = initial;
+        self.max_poll_interval = max.max(initial);
+        self
+    }
+
+    pub fn attempt(&self) -> &EmailLinkAttempt {
+        &self.attempt
+    }
+
+    /// Handle to cancel `wait` with
+    pub fn cancel_handle(&self) -> EmailLinkCancel {
+        self.cancel.clone()
+    }
+
+    /// Polls until the link is opened, on any device, and the sign in or
+    /// sign up moves on
+    pub async fn wait(
+        mut self,
+        timeout: Duration,
+    ) -> Result<EmailLinkAttempt, ClerkEmailLinkError> {
+        let started = Instant::now();
+        let mut interval = self.poll_interval;
+        loop {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(ClerkEmailLinkError::Timeout);
+            }
+            let delay = Delay::new(interval.min(remaining));
+            if let Either::Right(_) = future::select(delay, Box::pin(self.cancel.cancelled())).await
+            {
+                return Err(ClerkEmailLinkError::Cancelled);
+            }
+            if self.poll().await? {
+                return Ok(self.attempt);
+            }
+            interval = interval.saturating_mul(2).min(self.max_poll_interval);
+        }
+    }
+
+    /// Completes the flow with the url the link redirected to, example the
+    /// deep link the app was opened with
+    pub async fn complete(
+        mut self,
+        redirect_url: &str,
+    ) -> Result<EmailLinkAttempt, ClerkEmailLinkError> {
+        let redirect = EmailLinkRedirect::parse(redirect_url)?;
+        if redirect.is_failed() {
+            let status = redirect.status.map(|s| s.to_string()).unwrap_or_default();
+            return Err(ClerkEmailLinkError::VerificationFailed(status));
+        }
+        self.poll().await?;
+        if !self.attempt.is_complete() {
+            // The verification created the session on this device already
+            if let Some(session_id) = redirect.created_session_id {
+                self.clerk
+                    .set_active(Some(session_id), None)
+                    .await
+                    .map_err(ClerkEmailLinkError::ClerkSetActiveError)?;
+            }
+        }
+        Ok(self.attempt)
+    }
+
+    /// Reloads the attempt, returning whether the email link is verified
+    async fn poll(&mut self) -> Result<bool, ClerkEmailLinkError> {
+        match &mut self.attempt {
+            EmailLinkAttempt::SignIn(sign_in) => {
+                sign_in
+                    .reload()
+                    .await
+                    .map_err(ClerkEmailLinkError::ClerkSignInError)?;
+                match link_verification(sign_in.sign_in()).map(|v| v.status) {
+                    Some(status @ (LinkStatus::Failed | LinkStatus::Expired)) => Err(
+                        ClerkEmailLinkError::VerificationFailed(strategy_name(&status)),
+                    ),
+                    _ => Ok(!sign_in.needs_first_factor()),
+                }
+            }
+            EmailLinkAttempt::SignUp(sign_up) => {
+                sign_up
+                    .reload()
+                    .await
+                    .map_err(ClerkEmailLinkError::ClerkSignUpError)?;
+                Ok(sign_up.is_complete()
+                    || !sign_up
+                        .unverified_fields()
+                        .contains(&SignUpField::EmailAddress))
+            }
+        }
+    }
+}
+
+fn link_verification(sign_in: &ClientSignIn) -> Option<&StubsVerificationLink> {
+    match sign_in.first_factor_verification.as_deref()? {
+        ClientSignInFirstFactorVerification::StubsVerificationLink(v) => Some(v),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_redirect() {
+        let redirect = EmailLinkRedirect::parse(
+            "myapp://email-link?__clerk_status=verified&__clerk_created_session=sess_123",
+        )
+        .unwrap();
+        assert_eq!(redirect.status, Some(EmailLinkStatus::Verified));
+        assert_eq!(redirect.created_session_id.as_deref(), Some("sess_123"));
+        assert!(!redirect.is_failed());
+
+        let redirect =
+            EmailLinkRedirect::parse("https://example.com/?__clerk_status=client_mismatch")
+                .unwrap();
+        assert_eq!(redirect.status, Some(EmailLinkStatus::ClientMismatch));
+        assert!(redirect.is_failed());
+
+        let redirect = EmailLinkRedirect::parse("https://example.com/done").unwrap();
+        assert_eq!(redirect, EmailLinkRedirect::default());
+        assert!(!redirect.is_failed());
+
+        assert!(matches!(
+            EmailLinkRedirect::parse("not a url"),
+            Err(ClerkEmailLinkError::InvalidRedirect(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cancel() {
+        let cancel = EmailLinkCancel::default();
+        let waiting = cancel.clone();
+        let handle = tokio::spawn(async move { waiting.cancelled().await });
+        cancel.cancel();
+        handle.await.unwrap();
+        assert!(cancel.is_cancelled());
+    }
+}
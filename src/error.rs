@@ -0,0 +1,203 @@
+use crate::apis;
+use crate::models::{ClerkError, ClerkErrors};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// Error returned by the Clerk Frontend API
+///
+/// Keeps the HTTP status and the `errors` list from the response body, so
+/// that callers can branch on the Clerk error codes, example
+/// `form_password_incorrect`, and log the `clerk_trace_id`. When the request
+/// didn't get a response the transport error is available as `source()`.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) {
+/// use clerk_fapi_rs::sign_in::FirstFactor;
+///
+/// let mut sign_in = clerk.sign_in("user@example.com").await.unwrap();
+/// if let Err(e) = sign_in
+///     .attempt_first_factor(FirstFactor::Password("hunter2".to_string()))
+///     .await
+/// {
+///     match e.api_error() {
+///         Some(api) if api.is_code("form_password_incorrect") => {
+///             println!("{}", api.message().unwrap_or_default())
+///         }
+///         Some(api) => println!("Clerk error, trace id {:?}", api.clerk_trace_id()),
+///         None => println!("{e}"),
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClerkApiError {
+    status: Option<u16>,
+    errors: Vec<ClerkError>,
+    meta: Option<serde_json::Value>,
+    clerk_trace_id: Option<String>,
+    cause: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+impl ClerkApiError {
+    /// Builds the error from the response status and body
+    pub fn from_response(status: u16, content: &str) -> Self {
+        let body = serde_json::from_str::<ClerkErrors>(content).ok();
+        let (errors, meta, trace_id) = match body {
+            Some(body) => (body.errors, body.meta, body.clerk_trace_id),
+            None => (vec![], None, None),
+        };
+        let clerk_trace_id =
+            trace_id.or_else(|| errors.iter().find_map(|e| e.clerk_trace_id.clone()));
+        Self {
+            status: Some(status),
+            errors,
+            meta,
+            clerk_trace_id,
+            cause: None,
+        }
+    }
+
+    /// Builds the error from a request that didn't get a response
+    pub fn from_cause(cause: impl Error + Send + Sync + 'static) -> Self {
+        Self {
+            cause: Some(Arc::new(cause)),
+            ..Default::default()
+        }
+    }
+
+    /// HTTP status of the response, None on transport errors
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// Errors parsed from the response body
+    pub fn errors(&self) -> &[ClerkError] {
+        &self.errors
+    }
+
+    pub fn meta(&self) -> Option<&serde_json::Value> {
+        self.meta.as_ref()
+    }
+
+    /// Trace id to give to Clerk support
+    pub fn clerk_trace_id(&self) -> Option<&str> {
+        self.clerk_trace_id.as_deref()
+    }
+
+    /// Code of the first error, example `form_identifier_not_found`
+    pub fn code(&self) -> Option<&str> {
+        self.errors.first().map(|e| e.code.as_str())
+    }
+
+    /// Human readable message of the first error, prefers `long_message`
+    pub fn message(&self) -> Option<&str> {
+        self.errors.first().map(|e| {
+            if e.long_message.is_empty() {
+                e.message.as_str()
+            } else {
+                e.long_message.as_str()
+            }
+        })
+    }
+
+    /// Whether any of the errors has the code
+    pub fn is_code(&self, code: &str) -> bool {
+        self.errors.iter().any(|e| e.code == code)
+    }
+
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == Some(429) || self.is_code("too_many_requests")
+    }
+
+    /// Whether the session is no longer valid and the user needs to sign in
+    /// again
+    pub fn is_session_expired(&self) -> bool {
+        self.status == Some(401)
+            || ["session_expired", "signed_out", "session_not_found"]
+                .iter()
+                .any(|code| self.is_code(code))
+    }
+
+    /// Whether the user needs to verify their identity again before the
+    /// request is allowed, see `Clerk::with_reverification`
+    pub fn is_reverification_required(&self) -> bool {
+        self.is_code("session_reverification_required")
+    }
+
+    /// Whether the request failed without a response, example on network
+    /// errors
+    pub fn is_transport_error(&self) -> bool {
+        self.status.is_none()
+    }
+}
+
+impl fmt::Display for ClerkApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.status, &self.cause) {
+            (Some(status), _) => write!(f, "Clerk API error, status {status}")?,
+            (None, Some(cause)) => write!(f, "Clerk API request failed: {cause}")?,
+            (None, None) => write!(f, "Clerk API error")?,
+        }
+        if let Some(error) = self.errors.first() {
+            write!(f, ": {} ({})", error.message, error.code)?;
+        }
+        if let Some(trace_id) = &self.clerk_trace_id {
+            write!(f, ", trace id {trace_id}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ClerkApiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+impl<T> From<apis::Error<T>> for ClerkApiError {
+    fn from(e: apis::Error<T>) -> Self {
+        match e {
+            apis::Error::ResponseError(response) => {
+                Self::from_response(response.status.as_u16(), &response.content)
+            }
+            apis::Error::Reqwest(e) => Self::from_cause(e),
+            apis::Error::Serde(e) => Self::from_cause(e),
+            apis::Error::Io(e) => Self::from_cause(e),
+            apis::Error::Middleware(e) => Self {
+                cause: Some(Arc::from(Box::<dyn Error + Send + Sync>::from(e))),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response() {
+        let body = serde_json::json!({
+            "errors": [{
+                "message": "Password is incorrect. Try again, or use another method.",
+                "long_message": "Password is incorrect. Try again, or use another method.",
+                "code": "form_password_incorrect"
+            }],
+            "clerk_trace_id": "trace_123"
+        });
+        let e = ClerkApiError::from_response(422, &body.to_string());
+        assert_eq!(e.status(), Some(422));
+        assert!(e.is_code("form_password_incorrect"));
+        assert_eq!(e.code(), Some("form_password_incorrect"));
+        assert_eq!(e.clerk_trace_id(), Some("trace_123"));
+        assert!(!e.is_rate_limited());
+        assert!(!e.is_session_expired());
+        assert!(e.to_string().contains("trace_123"));
+
+        let e = ClerkApiError::from_response(429, "rate limited");
+        assert!(e.is_rate_limited());
+        assert!(e.errors().is_empty());
+    }
+}
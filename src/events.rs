@@ -0,0 +1,263 @@
+use crate::models::{
+    ClientOrganization as Organization, ClientSession as Session, ClientUser as User,
+};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::Stream;
+use parking_lot::Mutex;
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+
+/// Change in the Clerk state, see `Clerk::subscribe`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClerkEvent {
+    /// A session became active when there was none
+    SignedIn { session: Session, user: User },
+    /// The active session ended and no session is active anymore
+    SignedOut { session_id: String },
+    /// Another session became active
+    SessionChanged {
+        previous_session_id: String,
+        session: Session,
+        user: Option<User>,
+    },
+    /// The active organization changed, `None` when switched to the
+    /// personal account
+    OrganizationChanged { organization: Option<Organization> },
+    /// The user of the active session changed
+    UserUpdated { user: User },
+    /// A new session token was fetched from Clerk
+    TokenRefreshed {
+        session_id: String,
+        organization_id: Option<String>,
+        template: Option<String>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    session_id: Option<String>,
+    user: Option<User>,
+    organization_id: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Subscribers {
+    next_id: u64,
+    senders: Vec<(u64, UnboundedSender<ClerkEvent>)>,
+}
+
+/// Turns the state emissions into events and sends them to the subscribers
+#[derive(Debug, Default)]
+pub(crate) struct EventHub {
+    subscribers: Mutex<Subscribers>,
+    previous: Mutex<Snapshot>,
+}
+
+impl EventHub {
+    pub fn subscribe(self: &Arc<Self>) -> ClerkSubscription {
+        let (sender, receiver) = unbounded();
+        let mut subscribers = self.subscribers.lock();
+        let id = subscribers.next_id;
+        subscribers.next_id += 1;
+        subscribers.senders.push((id, sender));
+        ClerkSubscription {
+            id,
+            hub: Arc::downgrade(self),
+            receiver,
+        }
+    }
+
+    fn unsubscribe(&self, id: u64) {
+        self.subscribers
+            .lock()
+            .senders
+            .retain(|(subscriber_id, _)| *subscriber_id != id);
+    }
+
+    pub fn send(&self, event: ClerkEvent) {
+        // Subscribers that have gone away are dropped on the way
+        self.subscribers
+            .lock()
+            .senders
+            .retain(|(_, sender)| sender.unbounded_send(event.clone()).is_ok());
+    }
+
+    /// Compares the emitted state to the previous one and sends the events
+    /// describing the change
+    pub fn on_state(
+        &self,
+        session: Option<&Session>,
+        user: Option<&User>,
+        organization: Option<&Organization>,
+    ) {
+        let current = Snapshot {
+            session_id: session.map(|s| s.id.clone()),
+            user: user.cloned(),
+            organization_id: organization.map(|o| o.id.clone()),
+        };
+        let previous = std::mem::replace(&mut *self.previous.lock(), current);
+
+        match (previous.session_id, session) {
+            (None, Some(session)) => {
+                if let Some(user) = user {
+                    self.send(ClerkEvent::SignedIn {
+                        session: session.clone(),
+                        user: user.clone(),
+                    });
+                }
+            }
+            (Some(session_id), None) => self.send(ClerkEvent::SignedOut { session_id }),
+            (Some(previous_session_id), Some(session)) if previous_session_id != session.id => self
+                .send(ClerkEvent::SessionChanged {
+                    previous_session_id,
+                    session: session.clone(),
+                    user: user.cloned(),
+                }),
+            (Some(_), Some(_)) => {
+                if let Some(user) = user.filter(|u| previous.user.as_ref() != Some(*u)) {
+                    self.send(ClerkEvent::UserUpdated { user: user.clone() });
+                }
+            }
+            (None, None) => {}
+        }
+
+        if previous.organization_id.as_deref() != organization.map(|o| o.id.as_str()) {
+            self.send(ClerkEvent::OrganizationChanged {
+                organization: organization.cloned(),
+            });
+        }
+    }
+}
+
+/// Stream of `ClerkEvent`s returned by `Clerk::subscribe`
+///
+/// The subscription ends when it's dropped or `unsubscribe` is called.
+/// Events are buffered until they are read, so keep polling the stream or
+/// drop it.
+#[derive(Debug)]
+pub struct ClerkSubscription {
+    id: u64,
+    hub: Weak<EventHub>,
+    receiver: UnboundedReceiver<ClerkEvent>,
+}
+
+impl ClerkSubscription {
+    /// Stops receiving events, same as dropping the subscription
+    pub fn unsubscribe(self) {}
+}
+
+impl Stream for ClerkSubscription {
+    type Item = ClerkEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for ClerkSubscription {
+    fn drop(&mut self) {
+        if let Some(hub) = self.hub.upgrade() {
+            hub.unsubscribe(self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{FutureExt, StreamExt};
+
+    fn session(id: &str) -> Session {
+        Session {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn user(id: &str, first_name: &str) -> User {
+        User {
+            id: id.to_string(),
+            first_name: Some(first_name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn organization(id: &str) -> Organization {
+        Organization {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn pending(subscription: &mut ClerkSubscription) -> Vec<ClerkEvent> {
+        std::iter::from_fn(|| subscription.next().now_or_never().flatten()).collect()
+    }
+
+    #[test]
+    fn test_state_changes_to_events() {
+        let hub = Arc::new(EventHub::default());
+        let mut subscription = hub.subscribe();
+
+        let user_1 = user("user_1", "Ada");
+        hub.on_state(Some(&session("sess_1")), Some(&user_1), None);
+        assert_eq!(
+            pending(&mut subscription),
+            vec![ClerkEvent::SignedIn {
+                session: session("sess_1"),
+                user: user_1.clone(),
+            }]
+        );
+
+        // Same state emitted again
+        hub.on_state(Some(&session("sess_1")), Some(&user_1), None);
+        assert!(pending(&mut subscription).is_empty());
+
+        let renamed = user("user_1", "Grace");
+        let org = organization("org_1");
+        hub.on_state(Some(&session("sess_1")), Some(&renamed), Some(&org));
+        assert_eq!(
+            pending(&mut subscription),
+            vec![
+                ClerkEvent::UserUpdated {
+                    user: renamed.clone()
+                },
+                ClerkEvent::OrganizationChanged {
+                    organization: Some(org.clone())
+                },
+            ]
+        );
+
+        hub.on_state(Some(&session("sess_2")), Some(&renamed), Some(&org));
+        assert_eq!(
+            pending(&mut subscription),
+            vec![ClerkEvent::SessionChanged {
+                previous_session_id: "sess_1".to_string(),
+                session: session("sess_2"),
+                user: Some(renamed),
+            }]
+        );
+
+        hub.on_state(None, None, None);
+        assert_eq!(
+            pending(&mut subscription),
+            vec![
+                ClerkEvent::SignedOut {
+                    session_id: "sess_2".to_string()
+                },
+                ClerkEvent::OrganizationChanged { organization: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let hub = Arc::new(EventHub::default());
+        let subscription = hub.subscribe();
+        let _other = hub.subscribe();
+        assert_eq!(hub.subscribers.lock().senders.len(), 2);
+
+        subscription.unsubscribe();
+        assert_eq!(hub.subscribers.lock().senders.len(), 1);
+    }
+}
@@ -0,0 +1,447 @@
+use crate::configuration::Store;
+use log::error;
+use parking_lot::RwLock;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+type Values = HashMap<String, JsonValue>;
+
+/// Turns the stored values to file contents and back
+trait Codec: Send + Sync {
+    fn encode(&self, plain: Vec<u8>) -> io::Result<Vec<u8>>;
+    fn decode(&self, contents: Vec<u8>) -> io::Result<Vec<u8>>;
+}
+
+struct PlainCodec;
+
+impl Codec for PlainCodec {
+    fn encode(&self, plain: Vec<u8>) -> io::Result<Vec<u8>> {
+        Ok(plain)
+    }
+
+    fn decode(&self, contents: Vec<u8>) -> io::Result<Vec<u8>> {
+        Ok(contents)
+    }
+}
+
+/// Store that persists the values as a JSON file
+///
+/// Values are read to memory when the store is opened and every write
+/// rewrites the file atomically, by writing a temporary file next to it and
+/// renaming it over the old one. Writes hold an exclusive lock on a
+/// `.lock` file next to the store and merge with the values on disk, so
+/// several processes can share the file without losing each other's keys.
+/// The file is created readable only by the current user. A file that
+/// can't be decoded is moved aside to `<path>.corrupt` when it's read, on
+/// open, reload or write, and the store starts empty.
+///
+/// Every `set` and `delete` reads, merges and fsyncs the file on the
+/// calling thread. `Clerk` writes the store while holding its state lock,
+/// example on every client update, so on an async runtime each write
+/// blocks the executor thread for the duration of the disk I/O. Use
+/// `AsyncStoreAdapter` with an `AsyncStore` doing the I/O off the executor
+/// if that matters.
+///
+/// The file contains the client and the `Authorization` header in plain
+/// text, use `EncryptedFileStore` to keep them encrypted at rest.
+pub struct JsonFileStore {
+    path: PathBuf,
+    lock_path: PathBuf,
+    values: RwLock<Values>,
+    codec: Box<dyn Codec>,
+}
+
+impl std::fmt::Debug for JsonFileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("JsonFileStore")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl JsonFileStore {
+    /// Opens the store, the file is created on first write
+    ///
+    /// Fails if the file can't be read, an undecodable file is moved aside
+    /// instead.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_with_codec(path.as_ref(), Box::new(PlainCodec))
+    }
+
+    fn open_with_codec(path: &Path, codec: Box<dyn Codec>) -> io::Result<Self> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let store = Self {
+            path: path.to_path_buf(),
+            lock_path: PathBuf::from(lock_path),
+            values: RwLock::new(Values::new()),
+            codec,
+        };
+        store.reload()?;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Re-reads the values from disk, example after another process has
+    /// written to the file
+    pub fn reload(&self) -> io::Result<()> {
+        let values = {
+            let _lock = self.lock()?;
+            self.read_or_move_aside()?
+        };
+        *self.values.write() = values;
+        Ok(())
+    }
+
+    fn lock(&self) -> io::Result<File> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = private_options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.lock_path)?;
+        file.lock()?;
+        // Released when the file is closed
+        Ok(file)
+    }
+
+    fn read(&self) -> io::Result<Values> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Values::new()),
+            Err(e) => return Err(e),
+        };
+        let plain = self.codec.decode(contents)?;
+        serde_json::from_slice(&plain).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn write(&self, values: &Values) -> io::Result<()> {
+        let plain = serde_json::to_vec(values).map_err(io::Error::other)?;
+        let contents = self.codec.encode(plain)?;
+
+        let mut tmp_path = self.path.as_os_str().to_owned();
+        tmp_path.push(format!(".{}.tmp", std::process::id()));
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let result = (|| {
+            let mut file = private_options()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&tmp_path)?;
+            file.write_all(&contents)?;
+            file.sync_all()?;
+            fs::rename(&tmp_path, &self.path)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
+    }
+
+    /// Reads the values on disk, called with the lock held
+    ///
+    /// A file that can't be decoded is moved aside to `<path>.corrupt`
+    /// instead of being overwritten later. Other errors, including failing
+    /// to decrypt the file with the secret, are returned and the file is
+    /// left as it is.
+    fn read_or_move_aside(&self) -> io::Result<Values> {
+        match self.read() {
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                let mut corrupt_path = self.path.as_os_str().to_owned();
+                corrupt_path.push(".corrupt");
+                error!(
+                    "JsonFileStore: Failed to decode {}, moving it to {}: {e}",
+                    self.path.display(),
+                    Path::new(&corrupt_path).display()
+                );
+                fs::rename(&self.path, &corrupt_path)?;
+                Ok(Values::new())
+            }
+            result => result,
+        }
+    }
+
+    /// Applies the change on top of the values on disk and writes them back
+    fn update(&self, change: impl FnOnce(&mut Values)) -> io::Result<()> {
+        let _lock = self.lock()?;
+        let mut values = self.read_or_move_aside()?;
+        change(&mut values);
+        self.write(&values)?;
+        *self.values.write() = values;
+        Ok(())
+    }
+}
+
+fn private_options() -> OpenOptions {
+    #[allow(unused_mut)]
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+impl Store for JsonFileStore {
+    fn set(&self, key: &str, value: JsonValue) {
+        self.values.write().insert(key.to_string(), value.clone());
+        if let Err(e) = self.update(|values| {
+            values.insert(key.to_string(), value);
+        }) {
+            error!(
+                "JsonFileStore: Failed to write {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<JsonValue> {
+        self.values.read().get(key).cloned()
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.values.read().contains_key(key)
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        let removed = self.values.write().remove(key).is_some();
+        if let Err(e) = self.update(|values| {
+            values.remove(key);
+        }) {
+            error!(
+                "JsonFileStore: Failed to write {}: {e}",
+                self.path.display()
+            );
+        }
+        removed
+    }
+}
+
+#[cfg(feature = "encrypted-store")]
+mod encrypted {
+    use super::*;
+    use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+
+    const MAGIC: &[u8] = b"CFS1";
+    const NONCE_LEN: usize = 24;
+
+    struct EncryptedCodec {
+        cipher: XChaCha20Poly1305,
+    }
+
+    impl Codec for EncryptedCodec {
+        fn encode(&self, plain: Vec<u8>) -> io::Result<Vec<u8>> {
+            let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+            let ciphertext = self
+                .cipher
+                .encrypt(&nonce, plain.as_slice())
+                .map_err(|_| io::Error::other("Failed to encrypt store"))?;
+            Ok([MAGIC, nonce.as_slice(), &ciphertext].concat())
+        }
+
+        fn decode(&self, contents: Vec<u8>) -> io::Result<Vec<u8>> {
+            let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+            let rest = contents
+                .strip_prefix(MAGIC)
+                .ok_or_else(|| invalid("Not an encrypted store file"))?;
+            if rest.len() < NONCE_LEN {
+                return Err(invalid("Encrypted store file is truncated"));
+            }
+            let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+            // Not InvalidData, with a wrong secret every file fails to
+            // decrypt and must not be moved aside
+            self.cipher
+                .decrypt(XNonce::from_slice(nonce), ciphertext)
+                .map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        "Failed to decrypt store, wrong secret?",
+                    )
+                })
+        }
+    }
+
+    /// `JsonFileStore` that keeps the file encrypted at rest
+    ///
+    /// The file is encrypted with XChaCha20-Poly1305 using a key derived
+    /// from the given secret with HKDF-SHA256. The secret should be high
+    /// entropy, example 32 random bytes kept in the platform keychain, not a
+    /// password typed by the user.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use clerk_fapi_rs::store::EncryptedFileStore;
+    ///
+    /// let secret = [7u8; 32]; // load from the platform keychain
+    /// let store = EncryptedFileStore::open("clerk-state.bin", &secret).unwrap();
+    /// ```
+    #[derive(Debug)]
+    pub struct EncryptedFileStore {
+        inner: JsonFileStore,
+    }
+
+    impl EncryptedFileStore {
+        /// Opens the store, fails when the file exists but can't be
+        /// decrypted with the secret. Writes fail the same way and leave the
+        /// file as it is
+        pub fn open(path: impl AsRef<Path>, secret: &[u8]) -> io::Result<Self> {
+            let mut key = [0u8; 32];
+            Hkdf::<Sha256>::new(Some(b"clerk-fapi-rs store"), secret)
+                .expand(b"encryption key", &mut key)
+                .map_err(|_| io::Error::other("Failed to derive store key"))?;
+            let codec = EncryptedCodec {
+                cipher: XChaCha20Poly1305::new(&key.into()),
+            };
+            Ok(Self {
+                inner: JsonFileStore::open_with_codec(path.as_ref(), Box::new(codec))?,
+            })
+        }
+
+        pub fn path(&self) -> &Path {
+            self.inner.path()
+        }
+
+        /// Re-reads the values from disk
+        pub fn reload(&self) -> io::Result<()> {
+            self.inner.reload()
+        }
+    }
+
+    impl Store for EncryptedFileStore {
+        fn set(&self, key: &str, value: JsonValue) {
+            self.inner.set(key, value)
+        }
+
+        fn get(&self, key: &str) -> Option<JsonValue> {
+            self.inner.get(key)
+        }
+
+        fn has(&self, key: &str) -> bool {
+            self.inner.has(key)
+        }
+
+        fn delete(&self, key: &str) -> bool {
+            self.inner.delete(key)
+        }
+    }
+}
+
+#[cfg(feature = "encrypted-store")]
+pub use encrypted::EncryptedFileStore;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "clerk-fapi-rs-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        dir.join(name)
+    }
+
+    #[test]
+    fn test_json_file_store() {
+        let path = temp_path("store.json");
+        let store = JsonFileStore::open(&path).unwrap();
+        assert!(store.get("client").is_none());
+
+        store.set("client", JsonValue::from("value"));
+        store.set("environment", JsonValue::from(1));
+        assert!(store.delete("environment"));
+
+        // Another handle sees the values and doesn't drop them on write
+        let other = JsonFileStore::open(&path).unwrap();
+        assert_eq!(other.get("client"), Some(JsonValue::from("value")));
+        assert!(!other.has("environment"));
+        other.set("authorization_header", JsonValue::from("token"));
+
+        store.set("client", JsonValue::from("updated"));
+        store.reload().unwrap();
+        assert_eq!(
+            store.get("authorization_header"),
+            Some(JsonValue::from("token"))
+        );
+        assert_eq!(store.get("client"), Some(JsonValue::from("updated")));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_json_file_store_moves_corrupt_file_aside() {
+        let path = temp_path("store.json");
+        let store = JsonFileStore::open(&path).unwrap();
+        store.set("client", JsonValue::from("value"));
+
+        fs::write(&path, b"{\"client\": \"val").unwrap();
+        store.set("environment", JsonValue::from(1));
+
+        let mut corrupt_path = path.as_os_str().to_owned();
+        corrupt_path.push(".corrupt");
+        assert_eq!(fs::read(&corrupt_path).unwrap(), b"{\"client\": \"val");
+        let reopened = JsonFileStore::open(&path).unwrap();
+        assert_eq!(reopened.get("environment"), Some(JsonValue::from(1)));
+        assert!(!reopened.has("client"));
+
+        // Opening and reloading move it aside the same way
+        fs::write(&path, b"not json").unwrap();
+        store.reload().unwrap();
+        assert!(!store.has("environment"));
+        assert_eq!(fs::read(&corrupt_path).unwrap(), b"not json");
+
+        fs::write(&path, b"still not json").unwrap();
+        assert!(!JsonFileStore::open(&path).unwrap().has("environment"));
+        assert_eq!(fs::read(&corrupt_path).unwrap(), b"still not json");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[cfg(feature = "encrypted-store")]
+    #[test]
+    fn test_encrypted_file_store() {
+        let path = temp_path("store.bin");
+        let store = EncryptedFileStore::open(&path, b"secret").unwrap();
+        store.set("authorization_header", JsonValue::from("Bearer token"));
+
+        let contents = fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&contents).contains("Bearer token"));
+
+        let reopened = EncryptedFileStore::open(&path, b"secret").unwrap();
+        assert_eq!(
+            reopened.get("authorization_header"),
+            Some(JsonValue::from("Bearer token"))
+        );
+        assert!(EncryptedFileStore::open(&path, b"wrong").is_err());
+
+        // A file encrypted with another secret is never moved aside
+        let other_path = temp_path("other.bin");
+        EncryptedFileStore::open(&other_path, b"wrong")
+            .unwrap()
+            .set("client", JsonValue::from("other"));
+        let other_contents = fs::read(&other_path).unwrap();
+        fs::write(&path, &other_contents).unwrap();
+        store.set("client", JsonValue::from("value"));
+        assert!(store.reload().is_err());
+        assert_eq!(fs::read(&path).unwrap(), other_contents);
+        assert!(!path.with_extension("bin.corrupt").exists());
+
+        fs::remove_dir_all(other_path.parent().unwrap()).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,648 @@
+use crate::clerk_fapi::ClerkFapiClient;
+use crate::error::ClerkApiError;
+use crate::models::{Jwks, JwksKeysInner};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use log::{error, warn};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Actor claim present in impersonation sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActClaim {
+    pub sub: String,
+    #[serde(flatten)]
+    pub extra: HashMap<String, JsonValue>,
+}
+
+/// Compact organization claim used by version 2 session tokens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrganizationClaim {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fpm: Option<String>,
+}
+
+/// Claims of a Clerk session token
+///
+/// Both version 1 and version 2 session tokens are supported. For version 2
+/// tokens the compact `o` claim is unpacked into `org_id`, `org_role`,
+/// `org_slug` and `org_permissions` so callers can treat both versions alike.
+/// Any claims not known here, example ones added by JWT templates, are kept
+/// in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionClaims {
+    /// Session id, missing from tokens created with JWT templates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    pub sub: String,
+    pub iss: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub azp: Option<String>,
+    pub exp: i64,
+    pub iat: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub v: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org_role: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org_slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub org_permissions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActClaim>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub o: Option<OrganizationClaim>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fea: Option<String>,
+    /// Minutes since the first and the second factor were verified, -1
+    /// when not verified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fva: Option<Vec<i64>>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, JsonValue>,
+}
+
+impl SessionClaims {
+    /// Fills the version 1 organization claims from the compact version 2
+    /// `o` and `fea` claims, the permission bitmap is expanded the same way
+    /// clerk-js does it
+    fn normalize(&mut self) {
+        let Some(o) = self.o.clone() else {
+            return;
+        };
+        if self.org_id.is_none() {
+            self.org_id = Some(o.id.clone());
+        }
+        if self.org_role.is_none() {
+            self.org_role = o.rol.as_ref().map(|r| format!("org:{r}"));
+        }
+        if self.org_slug.is_none() {
+            self.org_slug = o.slg.clone();
+        }
+        if self.org_permissions.is_none() {
+            let features: Vec<&str> = self
+                .fea
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|f| f.split_once(':'))
+                .filter(|(scope, _)| scope.contains('o'))
+                .map(|(_, feature)| feature)
+                .collect();
+            let permissions: Vec<&str> = o
+                .per
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .filter(|p| !p.is_empty())
+                .collect();
+            let bitmaps: Vec<u64> = o
+                .fpm
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(|b| b.parse().unwrap_or(0))
+                .collect();
+
+            let mut org_permissions = Vec::new();
+            for (feature, bits) in features.iter().zip(bitmaps.iter()) {
+                for (index, permission) in permissions.iter().enumerate() {
+                    if index < 64 && bits & (1 << index) != 0 {
+                        org_permissions.push(format!("org:{feature}:{permission}"));
+                    }
+                }
+            }
+            self.org_permissions = Some(org_permissions);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum JwtVerificationError {
+    Malformed(String),
+    MissingKeyId,
+    UnknownKeyId(String),
+    UnsupportedAlgorithm(String),
+    InvalidKey(String),
+    InvalidSignature,
+    Expired,
+    NotYetValid,
+    IssuedInFuture,
+    InvalidIssuer(String),
+    InvalidAuthorizedParty(Option<String>),
+    FailedToLoadJwks(ClerkApiError),
+}
+impl fmt::Display for JwtVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JwtVerificationError::Malformed(e) => write!(f, "Malformed token: {e}"),
+            JwtVerificationError::MissingKeyId => write!(f, "Token header has no kid"),
+            JwtVerificationError::UnknownKeyId(kid) => write!(f, "No JWK found for kid {kid}"),
+            JwtVerificationError::UnsupportedAlgorithm(alg) => {
+                write!(f, "Unsupported algorithm {alg}")
+            }
+            JwtVerificationError::InvalidKey(e) => write!(f, "Invalid JWK: {e}"),
+            JwtVerificationError::InvalidSignature => write!(f, "Invalid token signature"),
+            JwtVerificationError::Expired => write!(f, "Token has expired"),
+            JwtVerificationError::NotYetValid => write!(f, "Token is not yet valid"),
+            JwtVerificationError::IssuedInFuture => write!(f, "Token is issued in the future"),
+            JwtVerificationError::InvalidIssuer(iss) => write!(f, "Invalid token issuer {iss}"),
+            JwtVerificationError::InvalidAuthorizedParty(azp) => {
+                write!(f, "Invalid authorized party {azp:?}")
+            }
+            JwtVerificationError::FailedToLoadJwks(e) => write!(f, "Failed to load JWKS: {e}"),
+        }
+    }
+}
+impl Error for JwtVerificationError {}
+
+/// Options for `JwtVerifier`
+#[derive(Debug, Clone)]
+pub struct JwtVerifierOptions {
+    /// Expected `iss` claim, not checked when None
+    pub issuer: Option<String>,
+    /// Allowed `azp` claims, not checked when empty
+    pub authorized_parties: Vec<String>,
+    /// Allowed clock skew in seconds for `exp`, `nbf` and `iat`
+    pub leeway: i64,
+    /// How long fetched JWKS is used before fetching it again
+    pub jwks_cache_ttl: Duration,
+    /// Minimum time between fetching the JWKS again for an unknown `kid`,
+    /// tokens with unknown keys fail with the cached set in between
+    pub jwks_refetch_interval: Duration,
+}
+
+impl Default for JwtVerifierOptions {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            authorized_parties: Vec::new(),
+            leeway: 5,
+            jwks_cache_ttl: Duration::from_secs(60 * 60),
+            jwks_refetch_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+struct CachedJwks {
+    jwks: Jwks,
+    fetched_at: Instant,
+}
+
+/// Verifies Clerk session tokens locally against the instance JWKS
+///
+/// The JWKS is fetched from `/.well-known/jwks.json` on first use and cached
+/// for `jwks_cache_ttl`. When a token is signed with a key that's not in the
+/// cached set the JWKS is fetched again to pick up rotated keys, at most
+/// once per `jwks_refetch_interval` so that forged tokens can't make the
+/// verifier hammer the API.
+pub struct JwtVerifier {
+    api_client: Option<Arc<ClerkFapiClient>>,
+    options: JwtVerifierOptions,
+    jwks: RwLock<Option<CachedJwks>>,
+    /// When the JWKS was last fetched for an unknown `kid`
+    refetched_at: Mutex<Option<Instant>>,
+}
+
+impl fmt::Debug for JwtVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JwtVerifier")
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+impl JwtVerifier {
+    /// Creates a verifier that fetches the JWKS with the given client
+    pub fn new(api_client: Arc<ClerkFapiClient>, options: JwtVerifierOptions) -> Self {
+        Self {
+            api_client: Some(api_client),
+            options,
+            jwks: RwLock::new(None),
+            refetched_at: Mutex::new(None),
+        }
+    }
+
+    /// Creates a verifier with a fixed JWKS, example when the keys are
+    /// distributed to the service out of band
+    pub fn with_jwks(jwks: Jwks, options: JwtVerifierOptions) -> Self {
+        Self {
+            api_client: None,
+            options,
+            jwks: RwLock::new(Some(CachedJwks {
+                jwks,
+                fetched_at: Instant::now(),
+            })),
+            refetched_at: Mutex::new(None),
+        }
+    }
+
+    pub fn options(&self) -> &JwtVerifierOptions {
+        &self.options
+    }
+
+    /// Verifies the token signature and claims and returns the claims
+    pub async fn verify(&self, token: &str) -> Result<SessionClaims, JwtVerificationError> {
+        let header =
+            decode_header(token).map_err(|e| JwtVerificationError::Malformed(e.to_string()))?;
+        let kid = header.kid.ok_or(JwtVerificationError::MissingKeyId)?;
+
+        let jwk = match self.cached_key(&kid) {
+            Some(jwk) => jwk,
+            None => {
+                if !self.may_refresh_jwks() {
+                    return Err(JwtVerificationError::UnknownKeyId(kid));
+                }
+                self.refresh_jwks().await?;
+                self.cached_key(&kid)
+                    .ok_or(JwtVerificationError::UnknownKeyId(kid))?
+            }
+        };
+
+        let claims = verify_signature(token, header.alg, &jwk)?;
+        validate_claims(&claims, &self.options, now_seconds())?;
+        Ok(claims)
+    }
+
+    /// Drops the cached JWKS so that the next verification fetches it again
+    pub fn clear_cache(&self) {
+        if self.api_client.is_some() {
+            *self.jwks.write() = None;
+        }
+    }
+
+    fn cached_key(&self, kid: &str) -> Option<JwksKeysInner> {
+        let cache = self.jwks.read();
+        let cached = cache.as_ref()?;
+        if self.api_client.is_some() && cached.fetched_at.elapsed() > self.options.jwks_cache_ttl {
+            return None;
+        }
+        find_key(&cached.jwks, kid)
+    }
+
+    /// Whether the JWKS can be fetched after a cache miss, always when
+    /// there's no usable cached set and otherwise once per
+    /// `jwks_refetch_interval`
+    fn may_refresh_jwks(&self) -> bool {
+        let cached_is_fresh = self
+            .jwks
+            .read()
+            .as_ref()
+            .is_some_and(|c| c.fetched_at.elapsed() <= self.options.jwks_cache_ttl);
+        if !cached_is_fresh {
+            return true;
+        }
+        let mut refetched_at = self.refetched_at.lock();
+        if refetched_at.is_some_and(|at| at.elapsed() < self.options.jwks_refetch_interval) {
+            warn!("JwtVerifier: Unknown kid, JWKS was fetched again recently");
+            return false;
+        }
+        *refetched_at = Some(Instant::now());
+        true
+    }
+
+    async fn refresh_jwks(&self) -> Result<(), JwtVerificationError> {
+        let Some(api_client) = self.api_client.as_ref() else {
+            // Static keys, nothing to refresh
+            return Ok(());
+        };
+        let jwks = api_client.get_jwks().await.map_err(|e| {
+            error!("JwtVerifier: Failed to fetch JWKS: {e}");
+            JwtVerificationError::FailedToLoadJwks(e.into())
+        })?;
+        *self.jwks.write() = Some(CachedJwks {
+            jwks,
+            fetched_at: Instant::now(),
+        });
+        Ok(())
+    }
+}
+
+pub(crate) fn now_seconds() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Reads `exp` from the token without verifying it, only to be used for
+/// deciding when to refresh tokens we've received from the API
+pub(crate) fn unverified_expiry(token: &str) -> Option<i64> {
+    jsonwebtoken::dangerous::insecure_decode::<JsonValue>(token)
+        .ok()?
+        .claims
+        .get("exp")?
+        .as_i64()
+}
+
+/// Reads the claims without verifying the token, only to be used with
+/// tokens we've received from the API
+pub(crate) fn unverified_claims(token: &str) -> Option<SessionClaims> {
+    let mut claims = jsonwebtoken::dangerous::insecure_decode::<SessionClaims>(token)
+        .ok()?
+        .claims;
+    claims.normalize();
+    Some(claims)
+}
+
+fn key_id(key: &JwksKeysInner) -> &str {
+    match key {
+        JwksKeysInner::JwksEd25519PublicKey(k) => &k.kid,
+        JwksKeysInner::JwksEcdsaPublicKey(k) => &k.kid,
+        JwksKeysInner::JwksRsaPublicKey(k) => &k.kid,
+        JwksKeysInner::JwksEd25519PrivateKey(k) => &k.kid,
+        JwksKeysInner::JwksEcdsaPrivateKey(k) => &k.kid,
+        JwksKeysInner::JwksRsaPrivateKey(k) => &k.kid,
+        JwksKeysInner::JwksSymmetricKey(k) => &k.kid,
+    }
+}
+
+fn find_key(jwks: &Jwks, kid: &str) -> Option<JwksKeysInner> {
+    jwks.keys
+        .as_ref()?
+        .iter()
+        .find(|k| key_id(k) == kid)
+        .cloned()
+}
+
+/// Builds the decoding key and checks that the algorithm in the token header
+/// matches the key type. Only the public part of the keys is used.
+fn decoding_key(alg: Algorithm, jwk: &JwksKeysInner) -> Result<DecodingKey, JwtVerificationError> {
+    let invalid_key =
+        |e: jsonwebtoken::errors::Error| JwtVerificationError::InvalidKey(e.to_string());
+    let unsupported = || JwtVerificationError::UnsupportedAlgorithm(format!("{alg:?}"));
+    match (alg, jwk) {
+        (Algorithm::RS256, JwksKeysInner::JwksRsaPublicKey(k)) => {
+            DecodingKey::from_rsa_components(&k.n, &k.e).map_err(invalid_key)
+        }
+        (Algorithm::RS256, JwksKeysInner::JwksRsaPrivateKey(k)) => {
+            DecodingKey::from_rsa_components(&k.n, &k.e).map_err(invalid_key)
+        }
+        (Algorithm::ES256, JwksKeysInner::JwksEcdsaPublicKey(k)) if k.crv == "P-256" => {
+            DecodingKey::from_ec_components(&k.x, &k.y).map_err(invalid_key)
+        }
+        (Algorithm::ES256, JwksKeysInner::JwksEcdsaPrivateKey(k)) if k.crv == "P-256" => {
+            DecodingKey::from_ec_components(&k.x, &k.y).map_err(invalid_key)
+        }
+        (Algorithm::EdDSA, JwksKeysInner::JwksEd25519PublicKey(k)) => {
+            DecodingKey::from_ed_components(&k.x).map_err(invalid_key)
+        }
+        (Algorithm::EdDSA, JwksKeysInner::JwksEd25519PrivateKey(k)) => {
+            DecodingKey::from_ed_components(&k.x).map_err(invalid_key)
+        }
+        _ => Err(unsupported()),
+    }
+}
+
+fn verify_signature(
+    token: &str,
+    alg: Algorithm,
+    jwk: &JwksKeysInner,
+) -> Result<SessionClaims, JwtVerificationError> {
+    let key = decoding_key(alg, jwk)?;
+
+    // Time based and issuer claims are validated by us, here we only
+    // check the signature
+    let mut validation = Validation::new(alg);
+    validation.required_spec_claims.clear();
+    validation.validate_exp = false;
+    validation.validate_nbf = false;
+    validation.validate_aud = false;
+
+    let mut claims = decode::<SessionClaims>(token, &key, &validation)
+        .map_err(|e| match e.kind() {
+            jsonwebtoken::errors::ErrorKind::InvalidSignature => {
+                JwtVerificationError::InvalidSignature
+            }
+            jsonwebtoken::errors::ErrorKind::InvalidAlgorithm => {
+                JwtVerificationError::UnsupportedAlgorithm(format!("{alg:?}"))
+            }
+            _ => {
+                warn!("JwtVerifier: Failed to decode token: {e}");
+                JwtVerificationError::Malformed(e.to_string())
+            }
+        })?
+        .claims;
+    claims.normalize();
+    Ok(claims)
+}
+
+fn validate_claims(
+    claims: &SessionClaims,
+    options: &JwtVerifierOptions,
+    now: i64,
+) -> Result<(), JwtVerificationError> {
+    if claims.exp + options.leeway <= now {
+        return Err(JwtVerificationError::Expired);
+    }
+    if let Some(nbf) = claims.nbf {
+        if nbf - options.leeway > now {
+            return Err(JwtVerificationError::NotYetValid);
+        }
+    }
+    if claims.iat - options.leeway > now {
+        return Err(JwtVerificationError::IssuedInFuture);
+    }
+    if let Some(issuer) = options.issuer.as_deref() {
+        if claims.iss.trim_end_matches('/') != issuer.trim_end_matches('/') {
+            return Err(JwtVerificationError::InvalidIssuer(claims.iss.clone()));
+        }
+    }
+    if !options.authorized_parties.is_empty() {
+        if let Some(azp) = claims.azp.as_ref() {
+            if !options.authorized_parties.contains(azp) {
+                return Err(JwtVerificationError::InvalidAuthorizedParty(Some(
+                    azp.clone(),
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::JwksEd25519PublicKey;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+    use ed25519_dalek::SigningKey;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    const KID: &str = "ins_test";
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
+
+    fn jwks() -> Jwks {
+        let x = URL_SAFE_NO_PAD.encode(signing_key().verifying_key().as_bytes());
+        Jwks {
+            keys: Some(vec![JwksKeysInner::JwksEd25519PublicKey(Box::new(
+                JwksEd25519PublicKey::new(
+                    KID.to_string(),
+                    "EdDSA".to_string(),
+                    "sig".to_string(),
+                    Default::default(),
+                    Default::default(),
+                    x,
+                ),
+            ))]),
+        }
+    }
+
+    fn sign(claims: JsonValue, kid: &str) -> String {
+        let der = signing_key().to_pkcs8_der().unwrap();
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(kid.to_string());
+        encode(&header, &claims, &EncodingKey::from_ed_der(der.as_bytes())).unwrap()
+    }
+
+    fn claims(now: i64) -> JsonValue {
+        serde_json::json!({
+            "sid": "sess_1",
+            "sub": "user_1",
+            "iss": "https://clerk.example.com",
+            "azp": "https://app.example.com",
+            "exp": now + 60,
+            "iat": now,
+            "nbf": now - 10,
+            "org_id": "org_1",
+            "org_role": "org:admin",
+            "org_permissions": ["org:sys_profile:manage"],
+            "act": { "sub": "user_admin" },
+            "custom": "value"
+        })
+    }
+
+    fn options() -> JwtVerifierOptions {
+        JwtVerifierOptions {
+            issuer: Some("https://clerk.example.com".to_string()),
+            authorized_parties: vec!["https://app.example.com".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_valid_token() {
+        let verifier = JwtVerifier::with_jwks(jwks(), options());
+        let token = sign(claims(now_seconds()), KID);
+
+        let claims = verifier.verify(&token).await.unwrap();
+        assert_eq!(claims.sid.as_deref(), Some("sess_1"));
+        assert_eq!(claims.sub, "user_1");
+        assert_eq!(claims.org_id.as_deref(), Some("org_1"));
+        assert_eq!(claims.org_role.as_deref(), Some("org:admin"));
+        assert_eq!(
+            claims.org_permissions,
+            Some(vec!["org:sys_profile:manage".to_string()])
+        );
+        assert_eq!(claims.act.unwrap().sub, "user_admin");
+        assert_eq!(
+            claims.extra.get("custom"),
+            Some(&serde_json::json!("value"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_unknown_kid_and_bad_signature() {
+        let verifier = JwtVerifier::with_jwks(jwks(), options());
+
+        let token = sign(claims(now_seconds()), "other_kid");
+        assert!(matches!(
+            verifier.verify(&token).await,
+            Err(JwtVerificationError::UnknownKeyId(_))
+        ));
+
+        let token = sign(claims(now_seconds()), KID);
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let tampered = URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(&serde_json::json!({"sub": "user_2"})).unwrap());
+        parts[1] = &tampered;
+        assert!(matches!(
+            verifier.verify(&parts.join(".")).await,
+            Err(JwtVerificationError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_validate_claims() {
+        let now = 1_700_000_000;
+        let valid: SessionClaims = serde_json::from_value(claims(now)).unwrap();
+        assert!(validate_claims(&valid, &options(), now).is_ok());
+
+        assert!(matches!(
+            validate_claims(&valid, &options(), now + 120),
+            Err(JwtVerificationError::Expired)
+        ));
+        assert!(matches!(
+            validate_claims(&valid, &options(), now - 60),
+            Err(JwtVerificationError::NotYetValid)
+        ));
+
+        let mut wrong_issuer = valid.clone();
+        wrong_issuer.iss = "https://evil.example.com".to_string();
+        assert!(matches!(
+            validate_claims(&wrong_issuer, &options(), now),
+            Err(JwtVerificationError::InvalidIssuer(_))
+        ));
+
+        let mut wrong_azp = valid.clone();
+        wrong_azp.azp = Some("https://evil.example.com".to_string());
+        assert!(matches!(
+            validate_claims(&wrong_azp, &options(), now),
+            Err(JwtVerificationError::InvalidAuthorizedParty(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_v2_organization_claims() {
+        let mut claims: SessionClaims = serde_json::from_value(serde_json::json!({
+            "sid": "sess_1",
+            "sub": "user_1",
+            "iss": "https://clerk.example.com",
+            "exp": 2,
+            "iat": 1,
+            "v": 2,
+            "fea": "o:dashboard,o:billing,u:profile",
+            "o": {
+                "id": "org_1",
+                "rol": "admin",
+                "slg": "acme",
+                "per": "read,manage",
+                "fpm": "3,1"
+            }
+        }))
+        .unwrap();
+        claims.normalize();
+
+        assert_eq!(claims.org_id.as_deref(), Some("org_1"));
+        assert_eq!(claims.org_role.as_deref(), Some("org:admin"));
+        assert_eq!(claims.org_slug.as_deref(), Some("acme"));
+        assert_eq!(
+            claims.org_permissions,
+            Some(vec![
+                "org:dashboard:read".to_string(),
+                "org:dashboard:manage".to_string(),
+                "org:billing:read".to_string(),
+            ])
+        );
+    }
+}
@@ -1,16 +1,49 @@
 #![allow(unused_imports)]
 #![allow(clippy::too_many_arguments)]
+#![allow(clippy::derivable_impls)]
 #![recursion_limit = "256"]
 
 // We make everything public
 pub mod apis;
+pub mod authorization;
+pub mod capabilities;
 pub mod clerk;
 pub mod clerk_fapi;
 pub mod clerk_http_client;
 pub mod clerk_state;
 pub mod configuration;
+pub mod email_link;
+pub mod error;
+pub mod events;
+#[cfg(all(feature = "file-store", not(target_arch = "wasm32")))]
+mod file_store;
+pub mod jwt;
+#[cfg(all(feature = "local-web3-signer", not(target_arch = "wasm32")))]
+mod local_web3_signer;
+pub mod middleware;
 pub mod models;
+pub mod oauth;
+pub mod organization_admin;
+pub mod organizations;
+pub mod pagination;
+pub mod passkey;
+pub mod retry;
+pub mod reverification;
+pub mod sessions;
+pub mod sign_in;
+pub mod sign_up;
+#[cfg(all(feature = "software-passkey", not(target_arch = "wasm32")))]
+mod software_authenticator;
+pub mod store;
+#[cfg(feature = "tracing")]
+mod telemetry;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
+mod token_cache;
+pub mod totp;
 mod utils;
+pub mod validation;
+pub mod web3;
 
 // Re-export main types
 pub use clerk::Clerk;
@@ -0,0 +1,174 @@
+use crate::web3::{SignerFuture, Web3Signer, Web3Strategy};
+use anyhow::{anyhow, bail};
+use k256::ecdsa::SigningKey;
+use rand_core::OsRng;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// Web3 signer with a secp256k1 key held in memory
+///
+/// Signs like an Ethereum wallet, the messages are prefixed and hashed as
+/// EIP-191 `personal_sign` does. Meant for tests and for backend tooling
+/// signing in with keys it holds itself.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::web3::LocalWeb3Signer;
+///
+/// let signer = LocalWeb3Signer::from_hex(
+///     "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+/// )?;
+/// let sign_in = clerk.sign_in_with_web3(&signer).await?;
+/// assert!(sign_in.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LocalWeb3Signer {
+    key: SigningKey,
+    address: String,
+    strategy: Web3Strategy,
+}
+
+// The key is kept out of logs
+impl fmt::Debug for LocalWeb3Signer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LocalWeb3Signer")
+            .field("address", &self.address)
+            .field("strategy", &self.strategy)
+            .finish()
+    }
+}
+
+impl LocalWeb3Signer {
+    /// Signer with a new random key
+    pub fn random() -> Self {
+        Self::from_key(SigningKey::random(&mut OsRng))
+    }
+
+    /// Signer with the 32 byte private key
+    pub fn from_bytes(private_key: &[u8]) -> anyhow::Result<Self> {
+        let key = SigningKey::from_slice(private_key).map_err(|e| anyhow!("Invalid key: {e}"))?;
+        Ok(Self::from_key(key))
+    }
+
+    /// Signer with the hex encoded private key, the 0x prefix is optional
+    pub fn from_hex(private_key: &str) -> anyhow::Result<Self> {
+        Self::from_bytes(&decode_hex(private_key)?)
+    }
+
+    /// Uses the strategy of another wallet, MetaMask by default
+    pub fn with_strategy(mut self, strategy: Web3Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    fn from_key(key: SigningKey) -> Self {
+        let point = key.verifying_key().to_encoded_point(false);
+        // Address is the last 20 bytes of the hash of the uncompressed key
+        // without the 0x04 tag
+        let hash = Keccak256::digest(&point.as_bytes()[1..]);
+        Self {
+            address: checksum_address(&hash[12..]),
+            key,
+            strategy: Web3Strategy::Metamask,
+        }
+    }
+
+    fn sign(&self, message: &str) -> anyhow::Result<String> {
+        let digest =
+            Keccak256::new_with_prefix(format!("\x19Ethereum Signed Message:\n{}", message.len()))
+                .chain_update(message);
+        let (signature, recovery_id) = self
+            .key
+            .sign_digest_recoverable(digest)
+            .map_err(|e| anyhow!("Failed to sign: {e}"))?;
+        let mut bytes = signature.to_bytes().to_vec();
+        bytes.push(27 + recovery_id.to_byte());
+        Ok(format!("0x{}", encode_hex(&bytes)))
+    }
+}
+
+impl Web3Signer for LocalWeb3Signer {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    fn strategy(&self) -> Web3Strategy {
+        self.strategy
+    }
+
+    fn sign_message<'a>(&'a self, message: &'a str) -> SignerFuture<'a, anyhow::Result<String>> {
+        Box::pin(async move { self.sign(message) })
+    }
+}
+
+/// EIP-55 mixed case address
+fn checksum_address(address: &[u8]) -> String {
+    let lower = encode_hex(address);
+    let hash = Keccak256::digest(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{checksummed}")
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex(hex: &str) -> anyhow::Result<Vec<u8>> {
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    if !hex.len().is_multiple_of(2) {
+        bail!("Hex has odd length");
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| anyhow!("Invalid hex"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_message() {
+        let signer = LocalWeb3Signer::from_hex(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        )
+        .unwrap();
+        assert_eq!(
+            signer.address(),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+        assert_eq!(
+            signer.sign("Some data").unwrap(),
+            "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c"
+        );
+        assert_eq!(signer.strategy(), Web3Strategy::Metamask);
+        let signer = signer.with_strategy(Web3Strategy::OkxWallet);
+        assert_eq!(signer.strategy(), Web3Strategy::OkxWallet);
+
+        assert!(LocalWeb3Signer::from_hex("0x123").is_err());
+        assert!(LocalWeb3Signer::from_hex("zz").is_err());
+        assert_ne!(
+            LocalWeb3Signer::random().address(),
+            LocalWeb3Signer::random().address()
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use log::{log, Level};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Request, Response};
+use std::fmt;
+use url::Url;
+
+/// Hooks run around every request `ClerkHttpClient` sends
+///
+/// Middlewares are registered with `ClerkFapiConfiguration::with_middleware`
+/// and run in the order they were added, after the Clerk specific request
+/// processing. They run for every attempt, so a retried request passes
+/// through `before_request` again. Returning an error from `before_request`
+/// or `after_response` fails the call with `apis::Error::Middleware`.
+///
+/// # Examples
+/// ```
+/// use clerk_fapi_rs::configuration::ClerkFapiConfiguration;
+/// use clerk_fapi_rs::middleware::Middleware;
+///
+/// #[derive(Debug)]
+/// struct DenyLegacyApi;
+///
+/// impl Middleware for DenyLegacyApi {
+///     fn before_request(&self, request: &mut reqwest::Request) -> anyhow::Result<()> {
+///         if request.url().path().starts_with("/v1/me") {
+///             anyhow::bail!("Use the client endpoints instead");
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let config = ClerkFapiConfiguration::new(
+///     "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+///     None,
+///     None,
+/// )
+/// .unwrap()
+/// .with_middleware(DenyLegacyApi);
+/// ```
+pub trait Middleware: Send + Sync + fmt::Debug {
+    /// Called before the request is sent, can modify the request
+    fn before_request(&self, _request: &mut Request) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called when a response is received, including error statuses
+    fn after_response(&self, _response: &Response) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Called when the request fails without a response
+    fn on_error(&self, _error: &reqwest::Error) {}
+}
+
+/// Logs requests and responses with the `log` crate
+///
+/// Query parameters are left out as they can contain the dev browser token.
+#[derive(Debug, Clone)]
+pub struct LoggingMiddleware {
+    level: Level,
+}
+
+impl LoggingMiddleware {
+    pub fn new(level: Level) -> Self {
+        Self { level }
+    }
+}
+
+impl Default for LoggingMiddleware {
+    fn default() -> Self {
+        Self::new(Level::Debug)
+    }
+}
+
+fn redacted_url(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_query(None);
+    url.to_string()
+}
+
+impl Middleware for LoggingMiddleware {
+    fn before_request(&self, request: &mut Request) -> anyhow::Result<()> {
+        log!(
+            self.level,
+            "Clerk request {} {}",
+            request.method(),
+            redacted_url(request.url())
+        );
+        Ok(())
+    }
+
+    fn after_response(&self, response: &Response) -> anyhow::Result<()> {
+        log!(
+            self.level,
+            "Clerk response {} {}",
+            response.status(),
+            redacted_url(response.url())
+        );
+        Ok(())
+    }
+
+    fn on_error(&self, error: &reqwest::Error) {
+        log!(
+            self.level,
+            "Clerk request {} failed: {error}",
+            error.url().map(redacted_url).unwrap_or_default()
+        );
+    }
+}
+
+/// Adds a unique id header to every request, `x-request-id` by default
+#[derive(Debug, Clone)]
+pub struct RequestIdMiddleware {
+    header: HeaderName,
+}
+
+impl RequestIdMiddleware {
+    pub fn new(header: HeaderName) -> Self {
+        Self { header }
+    }
+}
+
+impl Default for RequestIdMiddleware {
+    fn default() -> Self {
+        Self::new(HeaderName::from_static("x-request-id"))
+    }
+}
+
+impl Middleware for RequestIdMiddleware {
+    fn before_request(&self, request: &mut Request) -> anyhow::Result<()> {
+        let id = uuid::Uuid::new_v4().to_string();
+        request
+            .headers_mut()
+            .insert(self.header.clone(), HeaderValue::from_str(&id)?);
+        Ok(())
+    }
+}
+
+/// Adds the given headers to every request, replacing existing values
+#[derive(Debug, Clone, Default)]
+pub struct HeadersMiddleware {
+    headers: HeaderMap,
+}
+
+impl HeadersMiddleware {
+    pub fn new(headers: HeaderMap) -> Self {
+        Self { headers }
+    }
+
+    /// Adds a header, fails when the name or value is not a valid header
+    pub fn header(mut self, name: &str, value: &str) -> anyhow::Result<Self> {
+        self.headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+        Ok(self)
+    }
+}
+
+impl Middleware for HeadersMiddleware {
+    fn before_request(&self, request: &mut Request) -> anyhow::Result<()> {
+        for (name, value) in &self.headers {
+            request.headers_mut().insert(name.clone(), value.clone());
+        }
+        Ok(())
+    }
+}
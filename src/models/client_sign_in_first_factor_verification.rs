@@ -15,7 +15,6 @@ use serde::{Deserialize, Serialize};
 #[serde(untagged)]
 pub enum ClientSignInFirstFactorVerification {
     StubsVerificationPassword(Box<models::StubsVerificationPassword>),
-    StubsVerificationOauth(Box<models::StubsVerificationOauth>),
     StubsVerificationOtp(Box<models::StubsVerificationOtp>),
     StubsVerificationLink(Box<models::StubsVerificationLink>),
     StubsVerificationWeb3Signature(Box<models::StubsVerificationWeb3Signature>),
@@ -23,6 +22,7 @@ pub enum ClientSignInFirstFactorVerification {
     StubsVerificationSaml(Box<models::StubsVerificationSaml>),
     StubsVerificationPasskey(Box<models::StubsVerificationPasskey>),
     StubsVerificationGoogleOneTap(Box<models::StubsVerificationGoogleOneTap>),
+    StubsVerificationOauth(Box<models::StubsVerificationOauth>),
 }
 
 impl Default for ClientSignInFirstFactorVerification {
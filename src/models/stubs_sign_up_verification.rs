@@ -17,6 +17,9 @@ pub struct StubsSignUpVerification {
     pub next_action: NextAction,
     #[serde(rename = "supported_strategies")]
     pub supported_strategies: Vec<String>,
+    /// Message to sign for web3 wallet verifications, set once prepared
+    #[serde(rename = "message", skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 impl StubsSignUpVerification {
@@ -27,6 +30,7 @@ impl StubsSignUpVerification {
         StubsSignUpVerification {
             next_action,
             supported_strategies,
+            message: None,
         }
     }
 }
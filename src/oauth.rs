@@ -0,0 +1,616 @@
+use crate::clerk::Clerk;
+use crate::models::stubs_verification_oauth::Status as VerificationStatus;
+use crate::models::{
+    ClientSignIn, ClientSignInFirstFactorVerification, ClientSignUp,
+    ClientSignUpVerificationsExternalAccount, StubsVerificationOauth,
+};
+use crate::sessions::before_sign_in;
+use crate::sign_in::{strategy_name, ClerkSignInError, SignInFlow};
+use crate::sign_up::{ClerkSignUpError, SignUpFlow, SignUpParams};
+use log::error;
+use std::error::Error;
+use std::fmt;
+use url::Url;
+
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    futures::channel::oneshot,
+    futures::future::{self, Either},
+    futures_timer::Delay,
+    log::warn,
+    std::io::{self, BufRead, BufReader, Write},
+    std::net::{Ipv4Addr, TcpListener, TcpStream},
+    std::time::Duration,
+};
+
+/// Path the loopback listener expects the redirect on
+pub const LOOPBACK_CALLBACK_PATH: &str = "/oauth-callback";
+
+/// Where the user is sent back to once the OAuth provider is done
+///
+/// The redirect url needs to be allowed in the Clerk dashboard for
+/// production instances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OAuthRedirect {
+    /// HTTP listener on `127.0.0.1`, the redirect url is
+    /// `http://127.0.0.1:{port}/oauth-callback`. Port 0 picks a free port
+    #[cfg(not(target_arch = "wasm32"))]
+    Loopback { port: u16 },
+    /// Url the app receives itself, example `myapp://oauth-callback`. The
+    /// received url is passed to `OAuthFlow::complete`
+    DeepLink(String),
+}
+
+/// Parameters Clerk adds to the redirect url
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OAuthCallback {
+    /// Nonce to fetch the sign in or sign up with, so that the client token
+    /// is rotated for this device
+    pub rotating_token_nonce: Option<String>,
+    /// `__clerk_status`, example `verified` or `expired`
+    pub status: Option<String>,
+    /// `__clerk_created_session`
+    pub created_session_id: Option<String>,
+}
+
+impl OAuthCallback {
+    pub fn parse(url: &str) -> Result<Self, ClerkOAuthError> {
+        let url = Url::parse(url).map_err(|e| ClerkOAuthError::InvalidCallback(e.to_string()))?;
+        let mut callback = Self::default();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "rotating_token_nonce" => callback.rotating_token_nonce = Some(value.into_owned()),
+                "__clerk_status" => callback.status = Some(value.into_owned()),
+                "__clerk_created_session" => callback.created_session_id = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+        Ok(callback)
+    }
+
+    /// Whether Clerk reported that the verification didn't succeed
+    pub fn is_failed(&self) -> bool {
+        matches!(
+            self.status.as_deref(),
+            Some("failed" | "expired" | "client_mismatch")
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ClerkOAuthError {
+    ClerkSignInError(ClerkSignInError),
+    ClerkSignUpError(ClerkSignUpError),
+    /// The API didn't return the url to send the user to, example when the
+    /// strategy is not enabled for the instance
+    MissingAuthorizationUrl,
+    InvalidCallback(String),
+    /// The provider or Clerk rejected the verification, with the status
+    VerificationFailed(String),
+    /// The opener failed to open the authorization url
+    Open(Box<dyn Error + Send + Sync>),
+    /// The flow redirects to a deep link, use `OAuthFlow::complete`
+    NoLoopbackListener,
+    Io(std::io::Error),
+    /// No redirect was received in time
+    Timeout,
+}
+impl fmt::Display for ClerkOAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkOAuthError::ClerkSignInError(e) => e.fmt(f),
+            ClerkOAuthError::ClerkSignUpError(e) => e.fmt(f),
+            ClerkOAuthError::MissingAuthorizationUrl => {
+                write!(f, "Clerk didn't return the OAuth authorization url")
+            }
+            ClerkOAuthError::InvalidCallback(e) => write!(f, "Invalid OAuth callback url: {e}"),
+            ClerkOAuthError::VerificationFailed(status) => {
+                write!(f, "OAuth verification failed, status {status}")
+            }
+            ClerkOAuthError::Open(e) => write!(f, "Failed to open the authorization url: {e}"),
+            ClerkOAuthError::NoLoopbackListener => {
+                write!(f, "OAuth flow has no loopback listener to wait on")
+            }
+            ClerkOAuthError::Io(e) => write!(f, "OAuth loopback listener failed: {e}"),
+            ClerkOAuthError::Timeout => write!(f, "Timed out waiting for the OAuth redirect"),
+        }
+    }
+}
+impl Error for ClerkOAuthError {}
+
+impl ClerkOAuthError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&crate::error::ClerkApiError> {
+        match self {
+            ClerkOAuthError::ClerkSignInError(e) => e.api_error(),
+            ClerkOAuthError::ClerkSignUpError(e) => e.api_error(),
+            _ => None,
+        }
+    }
+}
+
+/// Sign in or sign up the OAuth flow ended up with
+///
+/// A sign in for a new account is transferred to a sign up, and a sign up
+/// for an existing account to a sign in. The sign up can still miss fields,
+/// example a required username, that are provided with `SignUpFlow::update`.
+#[derive(Debug, Clone)]
+pub enum OAuthAttempt {
+    SignIn(Box<SignInFlow>),
+    SignUp(Box<SignUpFlow>),
+}
+
+impl OAuthAttempt {
+    pub fn is_complete(&self) -> bool {
+        match self {
+            OAuthAttempt::SignIn(sign_in) => sign_in.is_complete(),
+            OAuthAttempt::SignUp(sign_up) => sign_up.is_complete(),
+        }
+    }
+
+    /// Session created by the sign in or sign up, set once it is complete
+    pub fn created_session_id(&self) -> Option<&str> {
+        match self {
+            OAuthAttempt::SignIn(sign_in) => sign_in.created_session_id(),
+            OAuthAttempt::SignUp(sign_up) => sign_up.created_session_id(),
+        }
+    }
+}
+
+/// Native OAuth flow for clients that can't follow the browser redirects
+///
+/// The flow is started with `Clerk::sign_in_with_oauth` or
+/// `Clerk::sign_up_with_oauth`. The user is sent to `authorization_url`, and
+/// once the provider redirects back the redirect is captured either with the
+/// loopback listener or passed in with `complete`. The sign in or sign up is
+/// then reloaded and transferred if needed, and when complete the created
+/// session is set active.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::oauth::OAuthRedirect;
+/// use std::time::Duration;
+///
+/// let oauth = clerk
+///     .sign_in_with_oauth("oauth_google", OAuthRedirect::Loopback { port: 0 })
+///     .await?;
+/// let attempt = oauth
+///     .authenticate(
+///         |url| std::process::Command::new("open").arg(url).status().map(|_| ()),
+///         Duration::from_secs(300),
+///     )
+///     .await?;
+/// assert!(attempt.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+pub struct OAuthFlow {
+    clerk: Clerk,
+    attempt: OAuthAttempt,
+    redirect_url: String,
+    authorization_url: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    listener: Option<LoopbackListener>,
+}
+
+impl fmt::Debug for OAuthFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OAuthFlow")
+            .field("attempt", &self.attempt)
+            .field("redirect_url", &self.redirect_url)
+            .finish()
+    }
+}
+
+impl OAuthFlow {
+    /// Starts a sign in with the OAuth strategy, example `oauth_google`
+    pub async fn sign_in(
+        clerk: &Clerk,
+        strategy: &str,
+        redirect: OAuthRedirect,
+    ) -> Result<Self, ClerkOAuthError> {
+        let redirect = Redirect::prepare(redirect)?;
+        before_sign_in(clerk)
+            .await
+            .map_err(ClerkOAuthError::ClerkSignInError)?;
+        let sign_in = clerk
+            .get_fapi_client()
+            .create_sign_in(
+                None,                // origin
+                Some(strategy),      // strategy
+                None,                // identifier
+                None,                // password
+                None,                // ticket
+                Some(&redirect.url), // redirect_url
+                None,                // action_complete_redirect_url
+                None,                // transfer
+                None,                // code
+                None,                // token
+                None,                // oidc_login_hint
+                None,                // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to create OAuth sign in: {e}");
+                ClerkOAuthError::ClerkSignInError(ClerkSignInError::ClerkApiError(e.into()))
+            })?;
+        let authorization_url = sign_in_verification(&sign_in)
+            .and_then(|v| v.external_verification_redirect_url.clone().flatten());
+        let sign_in = SignInFlow::from_sign_in(clerk, sign_in)
+            .await
+            .map_err(ClerkOAuthError::ClerkSignInError)?;
+        Self::new(
+            clerk,
+            OAuthAttempt::SignIn(Box::new(sign_in)),
+            redirect,
+            authorization_url,
+        )
+    }
+
+    /// Starts a sign up with the OAuth strategy, example `oauth_github`
+    pub async fn sign_up(
+        clerk: &Clerk,
+        strategy: &str,
+        redirect: OAuthRedirect,
+    ) -> Result<Self, ClerkOAuthError> {
+        let redirect = Redirect::prepare(redirect)?;
+        let sign_up = clerk
+            .get_fapi_client()
+            .create_sign_ups(
+                None,                // origin
+                None,                // transfer
+                None,                // password
+                None,                // first_name
+                None,                // last_name
+                None,                // username
+                None,                // email_address
+                None,                // phone_number
+                None,                // email_address_or_phone_number
+                None,                // unsafe_metadata
+                Some(strategy),      // strategy
+                None,                // action_complete_redirect_url
+                Some(&redirect.url), // redirect_url
+                None,                // ticket
+                None,                // web3_wallet
+                None,                // token
+                None,                // code
+                None,                // captcha_token
+                None,                // captcha_error
+                None,                // captcha_widget_type
+                None,                // legal_accepted
+                None,                // oidc_login_hint
+                None,                // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to create OAuth sign up: {e}");
+                ClerkOAuthError::ClerkSignUpError(ClerkSignUpError::ClerkApiError(e.into()))
+            })?;
+        let authorization_url = sign_up_verification(&sign_up)
+            .and_then(|v| v.external_verification_redirect_url.clone().flatten());
+        let sign_up = SignUpFlow::from_sign_up(clerk, sign_up)
+            .await
+            .map_err(ClerkOAuthError::ClerkSignUpError)?;
+        Self::new(
+            clerk,
+            OAuthAttempt::SignUp(Box::new(sign_up)),
+            redirect,
+            authorization_url,
+        )
+    }
+
+    fn new(
+        clerk: &Clerk,
+        attempt: OAuthAttempt,
+        redirect: Redirect,
+        authorization_url: Option<String>,
+    ) -> Result<Self, ClerkOAuthError> {
+        Ok(Self {
+            clerk: clerk.clone(),
+            attempt,
+            redirect_url: redirect.url,
+            authorization_url: authorization_url.ok_or(ClerkOAuthError::MissingAuthorizationUrl)?,
+            #[cfg(not(target_arch = "wasm32"))]
+            listener: redirect.listener,
+        })
+    }
+
+    /// Url to open for the user, the provider's consent screen
+    pub fn authorization_url(&self) -> &str {
+        &self.authorization_url
+    }
+
+    /// Url the provider redirects back to
+    pub fn redirect_url(&self) -> &str {
+        &self.redirect_url
+    }
+
+    pub fn attempt(&self) -> &OAuthAttempt {
+        &self.attempt
+    }
+
+    /// Hands the authorization url to `open`, example to launch the system
+    /// browser, and waits for the redirect on the loopback listener
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn authenticate<F, E>(
+        self,
+        open: F,
+        timeout: Duration,
+    ) -> Result<OAuthAttempt, ClerkOAuthError>
+    where
+        F: FnOnce(&str) -> Result<(), E>,
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        open(&self.authorization_url).map_err(|e| ClerkOAuthError::Open(e.into()))?;
+        self.wait_for_redirect(timeout).await
+    }
+
+    /// Waits for the redirect on the loopback listener and completes the
+    /// flow with it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn wait_for_redirect(
+        mut self,
+        timeout: Duration,
+    ) -> Result<OAuthAttempt, ClerkOAuthError> {
+        let listener = self
+            .listener
+            .take()
+            .ok_or(ClerkOAuthError::NoLoopbackListener)?;
+        let callback_url = listener.accept(timeout).await?;
+        self.complete(&callback_url).await
+    }
+
+    /// Completes the flow with the url the provider redirected to, example
+    /// the deep link the app was opened with
+    pub async fn complete(self, callback_url: &str) -> Result<OAuthAttempt, ClerkOAuthError> {
+        let callback = OAuthCallback::parse(callback_url)?;
+        if callback.is_failed() {
+            return Err(ClerkOAuthError::VerificationFailed(
+                callback.status.unwrap_or_default(),
+            ));
+        }
+        let nonce = callback.rotating_token_nonce.as_deref();
+        match self.attempt {
+            OAuthAttempt::SignIn(mut sign_in) => {
+                match nonce {
+                    Some(nonce) => sign_in.reload_with_nonce(nonce).await,
+                    None => sign_in.reload().await,
+                }
+                .map_err(ClerkOAuthError::ClerkSignInError)?;
+                match sign_in_verification(sign_in.sign_in()).map(|v| v.status) {
+                    // No account yet, continue as a sign up
+                    Some(VerificationStatus::Transferable) => {
+                        let sign_up =
+                            SignUpFlow::create(&self.clerk, SignUpParams::default().transfer(true))
+                                .await
+                                .map_err(ClerkOAuthError::ClerkSignUpError)?;
+                        Ok(OAuthAttempt::SignUp(Box::new(sign_up)))
+                    }
+                    Some(status @ (VerificationStatus::Failed | VerificationStatus::Expired)) => {
+                        Err(ClerkOAuthError::VerificationFailed(strategy_name(&status)))
+                    }
+                    _ => Ok(OAuthAttempt::SignIn(sign_in)),
+                }
+            }
+            OAuthAttempt::SignUp(mut sign_up) => {
+                match nonce {
+                    Some(nonce) => sign_up.reload_with_nonce(nonce).await,
+                    None => sign_up.reload().await,
+                }
+                .map_err(ClerkOAuthError::ClerkSignUpError)?;
+                match sign_up_verification(sign_up.sign_up()).map(|v| v.status) {
+                    // The account already exists, continue as a sign in
+                    Some(VerificationStatus::Transferable) => {
+                        let sign_in = SignInFlow::create_with_transfer(&self.clerk)
+                            .await
+                            .map_err(ClerkOAuthError::ClerkSignInError)?;
+                        Ok(OAuthAttempt::SignIn(Box::new(sign_in)))
+                    }
+                    Some(status @ (VerificationStatus::Failed | VerificationStatus::Expired)) => {
+                        Err(ClerkOAuthError::VerificationFailed(strategy_name(&status)))
+                    }
+                    _ => Ok(OAuthAttempt::SignUp(sign_up)),
+                }
+            }
+        }
+    }
+}
+
+/// Redirect url, and the listener capturing it when redirecting to loopback
+struct Redirect {
+    url: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    listener: Option<LoopbackListener>,
+}
+
+impl Redirect {
+    fn prepare(redirect: OAuthRedirect) -> Result<Self, ClerkOAuthError> {
+        match redirect {
+            #[cfg(not(target_arch = "wasm32"))]
+            OAuthRedirect::Loopback { port } => {
+                let listener = LoopbackListener::bind(port).map_err(ClerkOAuthError::Io)?;
+                Ok(Self {
+                    url: listener.redirect_url(),
+                    listener: Some(listener),
+                })
+            }
+            OAuthRedirect::DeepLink(url) => Ok(Self {
+                url,
+                #[cfg(not(target_arch = "wasm32"))]
+                listener: None,
+            }),
+        }
+    }
+}
+
+fn sign_in_verification(sign_in: &ClientSignIn) -> Option<&StubsVerificationOauth> {
+    match sign_in.first_factor_verification.as_deref()? {
+        ClientSignInFirstFactorVerification::StubsVerificationOauth(v) => Some(v),
+        _ => None,
+    }
+}
+
+fn sign_up_verification(sign_up: &ClientSignUp) -> Option<&StubsVerificationOauth> {
+    match sign_up.verifications.external_account.as_deref()? {
+        ClientSignUpVerificationsExternalAccount::StubsVerificationOauth(v) => Some(v),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[cfg(not(target_arch = "wasm32"))]
+const CALLBACK_PAGE: &str = "<!doctype html><html><body>\
+    <p>Signed in, you can close this window.</p>\
+    </body></html>";
+
+/// Single use HTTP listener capturing the OAuth redirect
+///
+/// Accepting is blocking so it's done on its own thread, polling so that the
+/// thread stops once the waiting side has given up.
+#[cfg(not(target_arch = "wasm32"))]
+struct LoopbackListener {
+    listener: TcpListener,
+    port: u16,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl LoopbackListener {
+    fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        Ok(Self { listener, port })
+    }
+
+    fn redirect_url(&self) -> String {
+        format!("http://127.0.0.1:{}{LOOPBACK_CALLBACK_PATH}", self.port)
+    }
+
+    /// Waits for the redirect and returns the full url it was made to
+    async fn accept(self, timeout: Duration) -> Result<String, ClerkOAuthError> {
+        let (sender, receiver) = oneshot::channel();
+        std::thread::spawn(move || self.serve(sender));
+        match future::select(receiver, Delay::new(timeout)).await {
+            Either::Left((Ok(result), _)) => result.map_err(ClerkOAuthError::Io),
+            Either::Left((Err(_), _)) => Err(ClerkOAuthError::Io(io::Error::other(
+                "loopback listener stopped",
+            ))),
+            Either::Right(_) => Err(ClerkOAuthError::Timeout),
+        }
+    }
+
+    fn serve(self, sender: oneshot::Sender<io::Result<String>>) {
+        loop {
+            if sender.is_canceled() {
+                return;
+            }
+            match self.listener.accept() {
+                Ok((stream, _)) => match self.handle(stream) {
+                    Ok(Some(url)) => {
+                        let _ = sender.send(Ok(url));
+                        return;
+                    }
+                    // Other requests, example the browser asking for favicon
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to handle OAuth loopback request: {e}"),
+                },
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(POLL_INTERVAL)
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> io::Result<Option<String>> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Read the headers so that the browser gets the response cleanly
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 && line != "\r\n" && line != "\n" {
+            line.clear();
+        }
+
+        let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+        let path = target.split('?').next().unwrap_or_default();
+        if path != LOOPBACK_CALLBACK_PATH {
+            stream.write_all(
+                b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            )?;
+            return Ok(None);
+        }
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{CALLBACK_PAGE}",
+            CALLBACK_PAGE.len()
+        )?;
+        Ok(Some(format!("http://127.0.0.1:{}{target}", self.port)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_callback() {
+        let callback = OAuthCallback::parse(
+            "myapp://oauth-callback?rotating_token_nonce=abc%3D&__clerk_status=verified",
+        )
+        .unwrap();
+        assert_eq!(callback.rotating_token_nonce.as_deref(), Some("abc="));
+        assert_eq!(callback.status.as_deref(), Some("verified"));
+        assert!(!callback.is_failed());
+
+        let callback =
+            OAuthCallback::parse("myapp://oauth-callback?__clerk_status=expired").unwrap();
+        assert!(callback.is_failed());
+        assert!(callback.rotating_token_nonce.is_none());
+
+        assert!(OAuthCallback::parse("not a url").is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_loopback_listener() {
+        let listener = LoopbackListener::bind(0).unwrap();
+        let port = listener.port;
+        assert_eq!(
+            listener.redirect_url(),
+            format!("http://127.0.0.1:{port}/oauth-callback")
+        );
+
+        let browser = std::thread::spawn(move || {
+            let request = |path: &str| {
+                let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+                write!(stream, "GET {path} HTTP/1.1\r\nHost: 127.0.0.1\r\n\r\n").unwrap();
+                let mut response = String::new();
+                BufReader::new(stream).read_line(&mut response).unwrap();
+                response
+            };
+            assert!(request("/favicon.ico").contains("404"));
+            assert!(request("/oauth-callback?rotating_token_nonce=abc").contains("200"));
+        });
+
+        let url = futures::executor::block_on(listener.accept(Duration::from_secs(5))).unwrap();
+        assert_eq!(
+            url,
+            format!("http://127.0.0.1:{port}/oauth-callback?rotating_token_nonce=abc")
+        );
+        browser.join().unwrap();
+
+        let listener = LoopbackListener::bind(0).unwrap();
+        let result = futures::executor::block_on(listener.accept(Duration::from_millis(100)));
+        assert!(matches!(result, Err(ClerkOAuthError::Timeout)));
+    }
+}
@@ -0,0 +1,500 @@
+use crate::authorization::with_org_prefix;
+use crate::clerk::Clerk;
+use crate::clerk_state::ClerkNotLoadedError;
+use crate::error::ClerkApiError;
+use crate::models::{
+    ClientOrganizationDomain, ClientOrganizationInvitation, ClientOrganizationMembership,
+    ClientOrganizationMembershipRequest, ClientRole,
+};
+use crate::pagination::Paginator;
+use futures::TryStreamExt;
+use log::{error, warn};
+use parking_lot::Mutex;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// Permission to invite and remove members, change their roles and handle
+/// membership requests
+pub const MANAGE_MEMBERSHIPS: &str = "org:sys_memberships:manage";
+/// Permission to add, verify and configure the organization domains
+pub const MANAGE_DOMAINS: &str = "org:sys_domains:manage";
+
+#[derive(Debug)]
+pub enum ClerkOrganizationAdminError {
+    ClerkNotLoadedError(ClerkNotLoadedError),
+    ClerkApiError(ClerkApiError),
+    /// The signed in user doesn't have the permission in the organization
+    MissingPermission(String),
+    /// The organization has no role with the key
+    UnknownRole(String),
+    /// The bulk invitation succeeded without an invitation for the email
+    /// address
+    NotInvited(String),
+}
+impl fmt::Display for ClerkOrganizationAdminError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkOrganizationAdminError::ClerkNotLoadedError(e) => e.fmt(f),
+            ClerkOrganizationAdminError::ClerkApiError(e) => e.fmt(f),
+            ClerkOrganizationAdminError::MissingPermission(permission) => {
+                write!(f, "Missing permission {permission}")
+            }
+            ClerkOrganizationAdminError::UnknownRole(role) => write!(f, "Unknown role {role}"),
+            ClerkOrganizationAdminError::NotInvited(email_address) => {
+                write!(f, "No invitation was created for {email_address}")
+            }
+        }
+    }
+}
+impl Error for ClerkOrganizationAdminError {}
+
+impl ClerkOrganizationAdminError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkOrganizationAdminError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// How users with an email address on a verified domain join the
+/// organization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainEnrollmentMode {
+    /// Admins invite the users
+    ManualInvitation,
+    /// The users are invited automatically
+    AutomaticInvitation,
+    /// The users are suggested to request to join
+    AutomaticSuggestion,
+}
+
+impl DomainEnrollmentMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainEnrollmentMode::ManualInvitation => "manual_invitation",
+            DomainEnrollmentMode::AutomaticInvitation => "automatic_invitation",
+            DomainEnrollmentMode::AutomaticSuggestion => "automatic_suggestion",
+        }
+    }
+}
+
+/// Outcome of one of the addresses given to `OrganizationAdmin::invite_many`
+#[derive(Debug)]
+pub struct InviteResult {
+    pub email_address: String,
+    pub result: Result<ClientOrganizationInvitation, ClerkOrganizationAdminError>,
+}
+
+/// Administration of an organization on behalf of the signed in user
+///
+/// Created with `Clerk::organization_admin`. Every operation checks that
+/// the user has the needed permission in the organization before calling
+/// the API, and roles are validated against the roles of the organization,
+/// which are fetched once per handle.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// let admin = clerk.organization_admin("org_123");
+/// let results = admin
+///     .invite_many(&["jane@example.com", "john@example.com"], "org:member")
+///     .await?;
+/// for row in results {
+///     if let Err(e) = row.result {
+///         println!("Failed to invite {}: {e}", row.email_address);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct OrganizationAdmin {
+    clerk: Clerk,
+    organization_id: String,
+    roles: Arc<Mutex<Option<Vec<ClientRole>>>>,
+}
+
+impl fmt::Debug for OrganizationAdmin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OrganizationAdmin")
+            .field("organization_id", &self.organization_id)
+            .finish()
+    }
+}
+
+impl OrganizationAdmin {
+    pub fn new(clerk: &Clerk, organization_id: &str) -> Self {
+        Self {
+            clerk: clerk.clone(),
+            organization_id: organization_id.to_string(),
+            roles: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn organization_id(&self) -> &str {
+        &self.organization_id
+    }
+
+    /// Membership of the signed in user in the organization
+    fn membership(&self) -> Result<Option<ClientOrganizationMembership>, ClerkNotLoadedError> {
+        Ok(self.clerk.user()?.and_then(|user| {
+            user.organization_memberships?
+                .into_iter()
+                .find(|m| m.organization.id == self.organization_id)
+        }))
+    }
+
+    /// Whether the signed in user has the permission in the organization
+    pub fn has_permission(&self, permission: &str) -> Result<bool, ClerkNotLoadedError> {
+        let permission = with_org_prefix(permission);
+        Ok(self.membership()?.is_some_and(|m| {
+            m.permissions
+                .unwrap_or_default()
+                .iter()
+                .any(|p| with_org_prefix(p) == permission)
+        }))
+    }
+
+    fn require(&self, permission: &str) -> Result<(), ClerkOrganizationAdminError> {
+        if self
+            .has_permission(permission)
+            .map_err(ClerkOrganizationAdminError::ClerkNotLoadedError)?
+        {
+            Ok(())
+        } else {
+            warn!("Clerk: Missing permission {permission}");
+            Err(ClerkOrganizationAdminError::MissingPermission(
+                permission.to_string(),
+            ))
+        }
+    }
+
+    /// Roles of the organization, fetched on the first call
+    pub async fn roles(&self) -> Result<Vec<ClientRole>, ClerkOrganizationAdminError> {
+        if let Some(roles) = self.roles.lock().clone() {
+            return Ok(roles);
+        }
+        let roles: Vec<ClientRole> = self
+            .clerk
+            .organization_roles(&self.organization_id)
+            .stream()
+            .try_collect()
+            .await
+            .map_err(|e| {
+                error!("Failed to list organization roles: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e)
+            })?;
+        *self.roles.lock() = Some(roles.clone());
+        Ok(roles)
+    }
+
+    /// Key of the organization role, the `org:` prefix is optional
+    async fn role_key(&self, role: &str) -> Result<String, ClerkOrganizationAdminError> {
+        let key = with_org_prefix(role);
+        self.roles()
+            .await?
+            .into_iter()
+            .find(|r| with_org_prefix(&r.key) == key)
+            .map(|r| r.key)
+            .ok_or_else(|| ClerkOrganizationAdminError::UnknownRole(role.to_string()))
+    }
+
+    //
+    // Invitations
+    //
+
+    /// Invites the email address to the organization with the role
+    pub async fn invite(
+        &self,
+        email_address: &str,
+        role: &str,
+    ) -> Result<ClientOrganizationInvitation, ClerkOrganizationAdminError> {
+        self.require(MANAGE_MEMBERSHIPS)?;
+        let role = self.role_key(role).await?;
+        self.create_invitation(email_address, &role).await
+    }
+
+    async fn create_invitation(
+        &self,
+        email_address: &str,
+        role: &str,
+    ) -> Result<ClientOrganizationInvitation, ClerkOrganizationAdminError> {
+        self.clerk
+            .get_fapi_client()
+            .create_organization_invitations(&self.organization_id, email_address, role)
+            .await
+            .map_err(|e| {
+                error!("Failed to create organization invitation: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Invites all the email addresses with the role, returning a result
+    /// for each address in the same order
+    ///
+    /// The addresses are invited with one request. If the API rejects it,
+    /// example because one address is invalid or already invited, they are
+    /// invited one by one to tell which ones failed. Rate limiting and auth
+    /// failures are returned as the error, every individual invite would
+    /// fail the same way.
+    ///
+    /// Addresses missing from a successful bulk response are reported with
+    /// `ClerkOrganizationAdminError::NotInvited` instead of being invited
+    /// again.
+    pub async fn invite_many(
+        &self,
+        email_addresses: &[&str],
+        role: &str,
+    ) -> Result<Vec<InviteResult>, ClerkOrganizationAdminError> {
+        self.require(MANAGE_MEMBERSHIPS)?;
+        let role = self.role_key(role).await?;
+        let bulk = self
+            .clerk
+            .get_fapi_client()
+            .bulk_create_organization_invitations(
+                &self.organization_id,
+                email_addresses.iter().map(|e| e.to_string()).collect(),
+                &role,
+            )
+            .await
+            .map_err(ClerkApiError::from);
+
+        let mut results = Vec::with_capacity(email_addresses.len());
+        match bulk {
+            Ok(invitations) => {
+                for email_address in email_addresses {
+                    let invitation = invitations
+                        .iter()
+                        .find(|i| i.email_address.eq_ignore_ascii_case(email_address))
+                        .cloned();
+                    results.push(InviteResult {
+                        email_address: email_address.to_string(),
+                        result: invitation.ok_or_else(|| {
+                            ClerkOrganizationAdminError::NotInvited(email_address.to_string())
+                        }),
+                    });
+                }
+            }
+            Err(e)
+                if e.status().is_some_and(|s| (400..500).contains(&s))
+                    && !e.is_rate_limited()
+                    && !matches!(e.status(), Some(401 | 403)) =>
+            {
+                warn!("Clerk: Bulk invitation rejected, inviting one by one: {e}");
+                for email_address in email_addresses {
+                    results.push(InviteResult {
+                        email_address: email_address.to_string(),
+                        result: self.create_invitation(email_address, &role).await,
+                    });
+                }
+            }
+            Err(e) => {
+                error!("Failed to create organization invitations: {e}");
+                return Err(ClerkOrganizationAdminError::ClerkApiError(e));
+            }
+        }
+        Ok(results)
+    }
+
+    /// Revokes a pending invitation
+    pub async fn revoke_invitation(
+        &self,
+        invitation_id: &str,
+    ) -> Result<ClientOrganizationInvitation, ClerkOrganizationAdminError> {
+        self.require(MANAGE_MEMBERSHIPS)?;
+        self.clerk
+            .get_fapi_client()
+            .revoke_pending_organization_invitation(&self.organization_id, invitation_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to revoke organization invitation: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Invitations of the organization, optionally filtered by status
+    pub fn invitations(&self, status: Option<&str>) -> Paginator<ClientOrganizationInvitation> {
+        self.clerk
+            .organization_invitations(&self.organization_id, status)
+    }
+
+    //
+    // Membership requests
+    //
+
+    /// Accepts a request to join the organization
+    pub async fn accept_request(
+        &self,
+        request_id: &str,
+    ) -> Result<ClientOrganizationMembershipRequest, ClerkOrganizationAdminError> {
+        self.require(MANAGE_MEMBERSHIPS)?;
+        self.clerk
+            .get_fapi_client()
+            .accept_organization_membership_request(&self.organization_id, request_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to accept membership request: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Rejects a request to join the organization
+    pub async fn reject_request(
+        &self,
+        request_id: &str,
+    ) -> Result<ClientOrganizationMembershipRequest, ClerkOrganizationAdminError> {
+        self.require(MANAGE_MEMBERSHIPS)?;
+        self.clerk
+            .get_fapi_client()
+            .reject_organization_membership_request(&self.organization_id, request_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to reject membership request: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Requests to join the organization, optionally filtered by status
+    pub fn membership_requests(
+        &self,
+        status: Option<&str>,
+    ) -> Paginator<ClientOrganizationMembershipRequest> {
+        self.clerk
+            .organization_membership_requests(&self.organization_id, status)
+    }
+
+    //
+    // Members
+    //
+
+    /// Changes the role of a member
+    pub async fn update_member_role(
+        &self,
+        user_id: &str,
+        role: &str,
+    ) -> Result<ClientOrganizationMembership, ClerkOrganizationAdminError> {
+        self.require(MANAGE_MEMBERSHIPS)?;
+        let role = self.role_key(role).await?;
+        self.clerk
+            .get_fapi_client()
+            .update_organization_membership(&self.organization_id, user_id, Some(&role))
+            .await
+            .map_err(|e| {
+                error!("Failed to update organization membership: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    //
+    // Domains
+    //
+
+    /// Adds a domain to the organization, it needs to be verified before
+    /// the enrollment mode applies
+    pub async fn add_domain(
+        &self,
+        name: &str,
+    ) -> Result<ClientOrganizationDomain, ClerkOrganizationAdminError> {
+        self.require(MANAGE_DOMAINS)?;
+        self.clerk
+            .get_fapi_client()
+            .create_organization_domain(&self.organization_id, name)
+            .await
+            .map_err(|e| {
+                error!("Failed to create organization domain: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Sends a verification code to an email address on the domain
+    pub async fn prepare_domain_verification(
+        &self,
+        domain_id: &str,
+        affiliation_email_address: &str,
+    ) -> Result<ClientOrganizationDomain, ClerkOrganizationAdminError> {
+        self.require(MANAGE_DOMAINS)?;
+        self.clerk
+            .get_fapi_client()
+            .prepare_organization_domain_verification(
+                &self.organization_id,
+                domain_id,
+                affiliation_email_address,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to prepare organization domain verification: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Verifies the domain with the code sent to the email address
+    pub async fn verify_domain(
+        &self,
+        domain_id: &str,
+        code: &str,
+    ) -> Result<ClientOrganizationDomain, ClerkOrganizationAdminError> {
+        self.require(MANAGE_DOMAINS)?;
+        self.clerk
+            .get_fapi_client()
+            .attempt_organization_domain_verification(&self.organization_id, domain_id, code)
+            .await
+            .map_err(|e| {
+                error!("Failed to verify organization domain: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Sets how users on the domain join, `delete_pending` removes the
+    /// pending invitations and suggestions of the previous mode
+    pub async fn set_domain_enrollment_mode(
+        &self,
+        domain_id: &str,
+        mode: DomainEnrollmentMode,
+        delete_pending: bool,
+    ) -> Result<ClientOrganizationDomain, ClerkOrganizationAdminError> {
+        self.require(MANAGE_DOMAINS)?;
+        self.clerk
+            .get_fapi_client()
+            .update_organization_domain_enrollment_mode(
+                &self.organization_id,
+                domain_id,
+                mode.as_str(),
+                Some(delete_pending),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to update organization domain enrollment mode: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })
+    }
+
+    /// Removes the domain from the organization
+    pub async fn delete_domain(&self, domain_id: &str) -> Result<(), ClerkOrganizationAdminError> {
+        self.require(MANAGE_DOMAINS)?;
+        self.clerk
+            .get_fapi_client()
+            .delete_organization_domain(&self.organization_id, domain_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete organization domain: {e}");
+                ClerkOrganizationAdminError::ClerkApiError(e.into())
+            })?;
+        Ok(())
+    }
+
+    /// Domains of the organization, optionally filtered
+    pub fn domains(
+        &self,
+        verified: Option<bool>,
+        enrollment_mode: Option<DomainEnrollmentMode>,
+    ) -> Paginator<ClientOrganizationDomain> {
+        self.clerk.organization_domains(
+            &self.organization_id,
+            verified,
+            enrollment_mode.map(|m| m.as_str()),
+        )
+    }
+}
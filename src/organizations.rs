@@ -0,0 +1,217 @@
+use crate::clerk::Clerk;
+use crate::clerk_state::ClerkNotLoadedError;
+use crate::error::ClerkApiError;
+use crate::models::{
+    ClientOrganization as Organization, ClientOrganizationInvitationUserContext,
+    ClientOrganizationSuggestion, ClientSession as Session,
+};
+use crate::utils::find_organization_id_from_memberships;
+use futures::TryStreamExt;
+use log::{error, warn};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ClerkOrganizationError {
+    ClerkNotLoadedError(ClerkNotLoadedError),
+    ClerkApiError(ClerkApiError),
+    /// There is no signed in user
+    NoSession,
+    /// The user is not a member of the organization with the id or slug
+    NoMatchFound(String),
+}
+impl fmt::Display for ClerkOrganizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkOrganizationError::ClerkNotLoadedError(e) => e.fmt(f),
+            ClerkOrganizationError::ClerkApiError(e) => e.fmt(f),
+            ClerkOrganizationError::NoSession => write!(f, "No signed in user"),
+            ClerkOrganizationError::NoMatchFound(org) => {
+                write!(f, "Not a member of organization {org}")
+            }
+        }
+    }
+}
+impl Error for ClerkOrganizationError {}
+
+impl ClerkOrganizationError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkOrganizationError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+fn active_session(clerk: &Clerk) -> Result<Session, ClerkOrganizationError> {
+    clerk
+        .session()
+        .map_err(ClerkOrganizationError::ClerkNotLoadedError)?
+        .ok_or(ClerkOrganizationError::NoSession)
+}
+
+/// Sets the organization active in the session, None is the personal
+/// account
+async fn activate(
+    clerk: &Clerk,
+    session_id: &str,
+    organization_id: Option<&str>,
+) -> Result<(), ClerkOrganizationError> {
+    // The API switches to the personal account with an empty id
+    clerk
+        .get_fapi_client()
+        .touch_session(session_id, Some(organization_id.unwrap_or("")))
+        .await
+        .map_err(|e| {
+            error!("Failed to touch session: {e}");
+            ClerkOrganizationError::ClerkApiError(e.into())
+        })?;
+
+    let active = clerk.organization().ok().flatten().map(|o| o.id);
+    if active.as_deref() != organization_id {
+        // The user in the response didn't have the membership yet
+        warn!("Clerk: Active organization not in the client, reloading client");
+        let client = clerk.get_fapi_client().get_client().await.map_err(|e| {
+            error!("Failed to get client: {e}");
+            ClerkOrganizationError::ClerkApiError(e.into())
+        })?;
+        if let Some(client) = client {
+            clerk
+                .set_client(client)
+                .map_err(ClerkOrganizationError::ClerkNotLoadedError)?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates an organization and sets it active
+pub async fn create_organization(
+    clerk: &Clerk,
+    name: &str,
+) -> Result<Organization, ClerkOrganizationError> {
+    let session = active_session(clerk)?;
+    let _hold = clerk.hold_emits();
+    let organization = clerk
+        .get_fapi_client()
+        .create_organization(Some(name))
+        .await
+        .map_err(|e| {
+            error!("Failed to create organization: {e}");
+            ClerkOrganizationError::ClerkApiError(e.into())
+        })?;
+    activate(clerk, &session.id, Some(&organization.id)).await?;
+    Ok(organization)
+}
+
+/// Sets the organization with the id or slug active
+///
+/// The organization is looked up from the memberships of the signed in
+/// user, and only if it's not there from the membership pages.
+pub async fn switch_organization(
+    clerk: &Clerk,
+    organization_id_or_slug: &str,
+) -> Result<Organization, ClerkOrganizationError> {
+    let session = active_session(clerk)?;
+    let _hold = clerk.hold_emits();
+    let loaded = session
+        .user
+        .as_ref()
+        .and_then(|u| u.organization_memberships.clone())
+        .and_then(|memberships| {
+            find_organization_id_from_memberships(memberships, organization_id_or_slug.to_string())
+        });
+    let membership = match loaded {
+        Some(membership) => membership,
+        None => {
+            let mut memberships = clerk.user_organization_memberships().stream();
+            loop {
+                let membership = memberships.try_next().await.map_err(|e| {
+                    error!("Failed to get organization memberships: {e}");
+                    ClerkOrganizationError::ClerkApiError(e)
+                })?;
+                match membership {
+                    Some(m)
+                        if m.organization.id == organization_id_or_slug
+                            || m.organization.slug == organization_id_or_slug =>
+                    {
+                        break m
+                    }
+                    Some(_) => continue,
+                    None => {
+                        return Err(ClerkOrganizationError::NoMatchFound(
+                            organization_id_or_slug.to_string(),
+                        ))
+                    }
+                }
+            }
+        }
+    };
+    activate(clerk, &session.id, Some(&membership.organization.id)).await?;
+    Ok(*membership.organization)
+}
+
+/// Switches the session to the personal account of the user
+pub async fn clear_active_organization(clerk: &Clerk) -> Result<(), ClerkOrganizationError> {
+    let session = active_session(clerk)?;
+    let _hold = clerk.hold_emits();
+    activate(clerk, &session.id, None).await
+}
+
+/// Removes the user from the organization, switching to the personal
+/// account if it was the active one
+pub async fn leave_organization(
+    clerk: &Clerk,
+    organization_id: &str,
+) -> Result<(), ClerkOrganizationError> {
+    let session = active_session(clerk)?;
+    let was_active = clerk
+        .organization()
+        .ok()
+        .flatten()
+        .is_some_and(|o| o.id == organization_id);
+    let _hold = clerk.hold_emits();
+    clerk
+        .get_fapi_client()
+        .delete_organization_memberships(organization_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to leave organization: {e}");
+            ClerkOrganizationError::ClerkApiError(e.into())
+        })?;
+    if was_active {
+        activate(clerk, &session.id, None).await?;
+    }
+    Ok(())
+}
+
+/// Accepts an invitation the user has received to an organization
+pub async fn accept_invitation(
+    clerk: &Clerk,
+    invitation_id: &str,
+) -> Result<ClientOrganizationInvitationUserContext, ClerkOrganizationError> {
+    clerk
+        .get_fapi_client()
+        .accept_organization_invitation(invitation_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to accept organization invitation: {e}");
+            ClerkOrganizationError::ClerkApiError(e.into())
+        })
+}
+
+/// Accepts a suggestion to join an organization, depending on the
+/// organization the membership is created right away or requested
+pub async fn accept_suggestion(
+    clerk: &Clerk,
+    suggestion_id: &str,
+) -> Result<ClientOrganizationSuggestion, ClerkOrganizationError> {
+    clerk
+        .get_fapi_client()
+        .accept_organization_suggestion(suggestion_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to accept organization suggestion: {e}");
+            ClerkOrganizationError::ClerkApiError(e.into())
+        })
+}
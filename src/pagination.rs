@@ -0,0 +1,280 @@
+use crate::error::ClerkApiError;
+use crate::models::{
+    ClientClientWrappedOrganizationInvitationsResponse,
+    ClientClientWrappedOrganizationMembershipsResponse, ClientOrganizationInvitation,
+    ClientOrganizationMembership,
+};
+use futures::future::{self, Either, TryFutureExt};
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Page size used unless `Paginator::page_size` is set
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// One page of a list endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    /// Total number of items, when the endpoint returns it
+    pub total_count: Option<i64>,
+}
+
+impl From<ClientClientWrappedOrganizationMembershipsResponse>
+    for Page<ClientOrganizationMembership>
+{
+    fn from(response: ClientClientWrappedOrganizationMembershipsResponse) -> Self {
+        use ClientClientWrappedOrganizationMembershipsResponse as Response;
+        match response {
+            Response::ArrayVecmodelsClientOrganizationMembership(data) => Page {
+                data,
+                total_count: None,
+            },
+            Response::ClientClientWrappedOrganizationMembershipsResponseOneOf(res) => Page {
+                data: res.data.unwrap_or_default(),
+                total_count: res.total_count,
+            },
+        }
+    }
+}
+
+impl From<ClientClientWrappedOrganizationInvitationsResponse>
+    for Page<ClientOrganizationInvitation>
+{
+    fn from(response: ClientClientWrappedOrganizationInvitationsResponse) -> Self {
+        use ClientClientWrappedOrganizationInvitationsResponse as Response;
+        match response {
+            Response::ArrayVecmodelsClientOrganizationInvitation(data) => Page {
+                data,
+                total_count: None,
+            },
+            Response::ClientClientWrappedOrganizationInvitationsResponseOneOf(res) => Page {
+                data: res.data.unwrap_or_default(),
+                total_count: res.total_count,
+            },
+        }
+    }
+}
+
+/// Future of a page fetched by `Paginator`, `Send` except on wasm32
+#[cfg(not(target_arch = "wasm32"))]
+pub type PageFuture<T> = Pin<Box<dyn Future<Output = Result<Page<T>, ClerkApiError>> + Send>>;
+#[cfg(target_arch = "wasm32")]
+pub type PageFuture<T> = Pin<Box<dyn Future<Output = Result<Page<T>, ClerkApiError>>>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type FetchPage<T> = Arc<dyn Fn(i64, i64) -> PageFuture<T> + Send + Sync>;
+#[cfg(target_arch = "wasm32")]
+type FetchPage<T> = Arc<dyn Fn(i64, i64) -> PageFuture<T>>;
+
+#[derive(Debug)]
+pub enum ClerkPaginationError {
+    ClerkApiError(ClerkApiError),
+    /// There are more items than the cap given to `collect_all`
+    TooManyItems(usize),
+}
+impl fmt::Display for ClerkPaginationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkPaginationError::ClerkApiError(e) => e.fmt(f),
+            ClerkPaginationError::TooManyItems(max) => {
+                write!(f, "List has more than {max} items")
+            }
+        }
+    }
+}
+impl Error for ClerkPaginationError {}
+
+impl ClerkPaginationError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkPaginationError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Lazily pages through a list endpoint with `limit` and `offset`
+///
+/// Pages are fetched as the stream is polled. The paging stops on a short
+/// or empty page, or once `total_count` items have been fetched.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use futures::TryStreamExt;
+///
+/// let mut members = clerk
+///     .organization_memberships("org_123", None, None)
+///     .page_size(100)
+///     .stream();
+/// while let Some(membership) = members.try_next().await? {
+///     println!("{}", membership.id);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Paginator<T> {
+    fetch: FetchPage<T>,
+    page_size: i64,
+}
+
+impl<T> Clone for Paginator<T> {
+    fn clone(&self) -> Self {
+        Self {
+            fetch: self.fetch.clone(),
+            page_size: self.page_size,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Paginator<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Paginator")
+            .field("page_size", &self.page_size)
+            .finish()
+    }
+}
+
+impl<T: 'static> Paginator<T> {
+    /// Paginator calling `fetch` with the limit and offset of each page
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new<F, Fut>(fetch: F) -> Self
+    where
+        F: Fn(i64, i64) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Page<T>, ClerkApiError>> + Send + 'static,
+    {
+        Self {
+            fetch: Arc::new(move |limit, offset| Box::pin(fetch(limit, offset))),
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+
+    /// Paginator calling `fetch` with the limit and offset of each page
+    #[cfg(target_arch = "wasm32")]
+    pub fn new<F, Fut>(fetch: F) -> Self
+    where
+        F: Fn(i64, i64) -> Fut + 'static,
+        Fut: Future<Output = Result<Page<T>, ClerkApiError>> + 'static,
+    {
+        Self {
+            fetch: Arc::new(move |limit, offset| Box::pin(fetch(limit, offset))),
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+
+    /// Number of items fetched per request, at least 1
+    pub fn page_size(mut self, page_size: i64) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Fetches a single page starting from the offset
+    pub async fn page(&self, offset: i64) -> Result<Page<T>, ClerkApiError> {
+        (self.fetch)(self.page_size, offset).await
+    }
+
+    /// Stream of the pages, fetched one at a time as the stream is polled
+    pub fn pages(&self) -> impl Stream<Item = Result<Page<T>, ClerkApiError>> + Unpin + 'static {
+        let fetch = self.fetch.clone();
+        let page_size = self.page_size;
+        stream::try_unfold(Some(0), move |offset| match offset {
+            None => Either::Left(future::ready(Ok(None))),
+            Some(offset) => Either::Right(fetch(page_size, offset).map_ok(move |page| {
+                let fetched = offset + page.data.len() as i64;
+                let is_last = (page.data.len() as i64) < page_size
+                    || page.total_count.is_some_and(|total| fetched >= total);
+                Some((page, (!is_last).then_some(fetched)))
+            })),
+        })
+    }
+
+    /// Stream of the items across all the pages
+    pub fn stream(&self) -> impl Stream<Item = Result<T, ClerkApiError>> + Unpin + 'static {
+        self.pages()
+            .map_ok(|page| stream::iter(page.data.into_iter().map(Ok)))
+            .try_flatten()
+    }
+
+    /// Fetches all the items, failing once there are more than `max`
+    pub async fn collect_all(&self, max: usize) -> Result<Vec<T>, ClerkPaginationError> {
+        let mut items = Vec::new();
+        let mut stream = self.stream();
+        while let Some(item) = stream.next().await {
+            if items.len() == max {
+                return Err(ClerkPaginationError::TooManyItems(max));
+            }
+            items.push(item.map_err(ClerkPaginationError::ClerkApiError)?);
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use parking_lot::Mutex;
+
+    type Calls = Arc<Mutex<Vec<(i64, i64)>>>;
+
+    fn numbers(total: i64, with_total_count: bool) -> (Paginator<i64>, Calls) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let paginator = Paginator::new({
+            let calls = calls.clone();
+            move |limit, offset| {
+                calls.lock().push((limit, offset));
+                let data = (offset..(offset + limit).min(total)).collect();
+                let total_count = with_total_count.then_some(total);
+                async move { Ok(Page { data, total_count }) }
+            }
+        });
+        (paginator, calls)
+    }
+
+    #[test]
+    fn test_stream_pages_lazily() {
+        let (paginator, calls) = numbers(7, false);
+        let paginator = paginator.page_size(3);
+
+        let mut stream = paginator.stream();
+        assert_eq!(block_on(stream.next()).unwrap().unwrap(), 0);
+        assert_eq!(*calls.lock(), vec![(3, 0)]);
+
+        let rest: Vec<i64> = block_on(stream.try_collect()).unwrap();
+        assert_eq!(rest, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(*calls.lock(), vec![(3, 0), (3, 3), (3, 6)]);
+    }
+
+    #[test]
+    fn test_stops_at_total_count() {
+        let (paginator, calls) = numbers(6, true);
+        let items = block_on(paginator.page_size(3).collect_all(10)).unwrap();
+        assert_eq!(items, vec![0, 1, 2, 3, 4, 5]);
+        // A full last page doesn't need an extra request with the count
+        assert_eq!(*calls.lock(), vec![(3, 0), (3, 3)]);
+    }
+
+    #[test]
+    fn test_collect_all_cap() {
+        let (paginator, _) = numbers(6, false);
+        let result = block_on(paginator.page_size(2).collect_all(5));
+        assert!(matches!(result, Err(ClerkPaginationError::TooManyItems(5))));
+
+        let (paginator, _) = numbers(5, false);
+        assert_eq!(block_on(paginator.collect_all(5)).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_errors_end_the_stream() {
+        let paginator: Paginator<i64> =
+            Paginator::new(|_, _| async { Err(ClerkApiError::from_response(500, "")) });
+        let items: Vec<_> = block_on(paginator.stream().collect());
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+    }
+}
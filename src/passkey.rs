@@ -0,0 +1,419 @@
+use crate::clerk::Clerk;
+use crate::error::ClerkApiError;
+use crate::models::{ClientPasskey, ClientSignInFirstFactorVerification};
+use crate::sessions::before_sign_in;
+use crate::sign_in::{ClerkSignInError, FirstFactor, PrepareFirstFactor, SignInFlow, SignInStatus};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_with::base64::{Base64, UrlSafe};
+use serde_with::formats::Unpadded;
+use serde_with::serde_as;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+#[cfg(all(feature = "software-passkey", not(target_arch = "wasm32")))]
+pub use crate::software_authenticator::SoftwareAuthenticator;
+
+/// COSE algorithm identifier of ES256, ECDSA with P-256 and SHA-256
+pub const ES256: i64 = -7;
+
+/// Relying party the credential is created for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyCredentialRpEntity {
+    /// Domain of the relying party, the origin's domain when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+}
+
+/// User the credential is created for
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialUserEntity {
+    /// User handle, returned on sign in to identify the user
+    #[serde_as(as = "Base64<UrlSafe, Unpadded>")]
+    pub id: Vec<u8>,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyCredentialParameters {
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// COSE algorithm identifier, example `ES256`
+    pub alg: i64,
+}
+
+/// Existing credential, to exclude on registration or to allow on sign in
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyCredentialDescriptor {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde_as(as = "Base64<UrlSafe, Unpadded>")]
+    pub id: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelectionCriteria {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authenticator_attachment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resident_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_resident_key: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_verification: Option<String>,
+}
+
+/// Options for creating a credential, `navigator.credentials.create`
+///
+/// Parsed from the nonce of the passkey verification when registering a
+/// passkey.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialCreationOptions {
+    pub rp: PublicKeyCredentialRpEntity,
+    pub user: PublicKeyCredentialUserEntity,
+    #[serde_as(as = "Base64<UrlSafe, Unpadded>")]
+    pub challenge: Vec<u8>,
+    pub pub_key_cred_params: Vec<PublicKeyCredentialParameters>,
+    /// Milliseconds the user has to complete the ceremony
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_credentials: Vec<PublicKeyCredentialDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authenticator_selection: Option<AuthenticatorSelectionCriteria>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<String>,
+}
+
+impl PublicKeyCredentialCreationOptions {
+    pub fn from_nonce(nonce: &str) -> Result<Self, ClerkPasskeyError> {
+        serde_json::from_str(nonce).map_err(ClerkPasskeyError::InvalidOptions)
+    }
+}
+
+/// Options for getting an assertion, `navigator.credentials.get`
+///
+/// Parsed from the nonce of the sign in passkey verification.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialRequestOptions {
+    #[serde_as(as = "Base64<UrlSafe, Unpadded>")]
+    pub challenge: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rp_id: Option<String>,
+    /// Credentials that can be used, any discoverable credential when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_credentials: Vec<PublicKeyCredentialDescriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_verification: Option<String>,
+}
+
+impl PublicKeyCredentialRequestOptions {
+    pub fn from_nonce(nonce: &str) -> Result<Self, ClerkPasskeyError> {
+        serde_json::from_str(nonce).map_err(ClerkPasskeyError::InvalidOptions)
+    }
+}
+
+/// Credential created by the authenticator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationCredential {
+    pub raw_id: Vec<u8>,
+    /// `platform` or `cross-platform`
+    pub authenticator_attachment: Option<String>,
+    pub client_data_json: Vec<u8>,
+    pub attestation_object: Vec<u8>,
+    pub transports: Vec<String>,
+}
+
+impl RegistrationCredential {
+    /// The `public_key_credential` JSON the API expects
+    pub fn to_json(&self) -> String {
+        let id = URL_SAFE_NO_PAD.encode(&self.raw_id);
+        serde_json::json!({
+            "id": id,
+            "rawId": id,
+            "type": "public-key",
+            "authenticatorAttachment": self.authenticator_attachment,
+            "response": {
+                "clientDataJSON": URL_SAFE_NO_PAD.encode(&self.client_data_json),
+                "attestationObject": URL_SAFE_NO_PAD.encode(&self.attestation_object),
+                "transports": self.transports,
+            },
+        })
+        .to_string()
+    }
+}
+
+/// Assertion signed by the authenticator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionCredential {
+    pub raw_id: Vec<u8>,
+    pub authenticator_attachment: Option<String>,
+    pub client_data_json: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub user_handle: Option<Vec<u8>>,
+}
+
+impl AssertionCredential {
+    /// The `public_key_credential` JSON the API expects
+    pub fn to_json(&self) -> String {
+        let id = URL_SAFE_NO_PAD.encode(&self.raw_id);
+        serde_json::json!({
+            "id": id,
+            "rawId": id,
+            "type": "public-key",
+            "authenticatorAttachment": self.authenticator_attachment,
+            "response": {
+                "clientDataJSON": URL_SAFE_NO_PAD.encode(&self.client_data_json),
+                "authenticatorData": URL_SAFE_NO_PAD.encode(&self.authenticator_data),
+                "signature": URL_SAFE_NO_PAD.encode(&self.signature),
+                "userHandle": self.user_handle.as_ref().map(|h| URL_SAFE_NO_PAD.encode(h)),
+            },
+        })
+        .to_string()
+    }
+}
+
+/// Future returned by `PasskeyAuthenticator`, not required to be `Send` on
+/// wasm32 so that the browser WebAuthn APIs can be used
+#[cfg(not(target_arch = "wasm32"))]
+pub type AuthenticatorFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+pub type AuthenticatorFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Performs the WebAuthn ceremonies, example with the platform passkey APIs
+/// or a security key
+///
+/// `SoftwareAuthenticator`, behind the `software-passkey` feature, keeps the
+/// keys in memory and can be used for testing.
+pub trait PasskeyAuthenticator: Send + Sync {
+    /// Creates a new credential, like `navigator.credentials.create`
+    fn create<'a>(
+        &'a self,
+        options: &'a PublicKeyCredentialCreationOptions,
+    ) -> AuthenticatorFuture<'a, anyhow::Result<RegistrationCredential>>;
+
+    /// Signs the challenge with an existing credential, like
+    /// `navigator.credentials.get`
+    fn get<'a>(
+        &'a self,
+        options: &'a PublicKeyCredentialRequestOptions,
+    ) -> AuthenticatorFuture<'a, anyhow::Result<AssertionCredential>>;
+}
+
+#[derive(Debug)]
+pub enum ClerkPasskeyError {
+    ClerkApiError(ClerkApiError),
+    ClerkSignInError(ClerkSignInError),
+    /// The API didn't return the WebAuthn options
+    MissingOptions,
+    InvalidOptions(serde_json::Error),
+    Authenticator(anyhow::Error),
+}
+impl fmt::Display for ClerkPasskeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkPasskeyError::ClerkApiError(e) => e.fmt(f),
+            ClerkPasskeyError::ClerkSignInError(e) => e.fmt(f),
+            ClerkPasskeyError::MissingOptions => write!(f, "Clerk didn't return passkey options"),
+            ClerkPasskeyError::InvalidOptions(e) => write!(f, "Invalid passkey options: {e}"),
+            ClerkPasskeyError::Authenticator(e) => write!(f, "Passkey authenticator failed: {e}"),
+        }
+    }
+}
+impl Error for ClerkPasskeyError {}
+
+impl ClerkPasskeyError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkPasskeyError::ClerkApiError(e) => Some(e),
+            ClerkPasskeyError::ClerkSignInError(e) => e.api_error(),
+            _ => None,
+        }
+    }
+}
+
+/// Registers a new passkey for the signed in user
+pub async fn register_passkey(
+    clerk: &Clerk,
+    authenticator: &(impl PasskeyAuthenticator + ?Sized),
+) -> Result<ClientPasskey, ClerkPasskeyError> {
+    let fapi = clerk.get_fapi_client();
+    let passkey = fapi
+        .post_passkey(
+            None, // _clerk_session_id
+            None, // origin
+            None, // x_original_host
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to create passkey: {e}");
+            ClerkPasskeyError::ClerkApiError(e.into())
+        })?;
+    let nonce = passkey
+        .verification
+        .as_ref()
+        .and_then(|v| v.nonce.as_deref())
+        .ok_or(ClerkPasskeyError::MissingOptions)?;
+    let options = PublicKeyCredentialCreationOptions::from_nonce(nonce)?;
+    let credential = authenticator
+        .create(&options)
+        .await
+        .map_err(ClerkPasskeyError::Authenticator)?;
+
+    fapi.attempt_passkey_verification(
+        &passkey.id,
+        None,            // origin
+        Some("passkey"), // strategy
+        Some(&credential.to_json()),
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to verify passkey: {e}");
+        ClerkPasskeyError::ClerkApiError(e.into())
+    })
+}
+
+/// Signs in with a discoverable passkey, the user is picked by the
+/// authenticator
+pub async fn sign_in_with_passkey(
+    clerk: &Clerk,
+    authenticator: &(impl PasskeyAuthenticator + ?Sized),
+) -> Result<SignInFlow, ClerkPasskeyError> {
+    before_sign_in(clerk)
+        .await
+        .map_err(ClerkPasskeyError::ClerkSignInError)?;
+    let sign_in = clerk
+        .get_fapi_client()
+        .create_sign_in(
+            None,            // origin
+            Some("passkey"), // strategy
+            None,            // identifier
+            None,            // password
+            None,            // ticket
+            None,            // redirect_url
+            None,            // action_complete_redirect_url
+            None,            // transfer
+            None,            // code
+            None,            // token
+            None,            // oidc_login_hint
+            None,            // oidc_prompt
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to create passkey sign in: {e}");
+            ClerkPasskeyError::ClerkApiError(e.into())
+        })?;
+    let mut flow = SignInFlow::from_sign_in(clerk, sign_in)
+        .await
+        .map_err(ClerkPasskeyError::ClerkSignInError)?;
+    flow.attempt_passkey(authenticator).await?;
+    Ok(flow)
+}
+
+impl SignInFlow {
+    /// Attempts the first factor with a passkey, preparing it first when the
+    /// sign in has no passkey challenge yet
+    pub async fn attempt_passkey(
+        &mut self,
+        authenticator: &(impl PasskeyAuthenticator + ?Sized),
+    ) -> Result<SignInStatus, ClerkPasskeyError> {
+        if self.passkey_nonce().is_none() {
+            self.prepare_first_factor(PrepareFirstFactor::Passkey)
+                .await
+                .map_err(ClerkPasskeyError::ClerkSignInError)?;
+        }
+        let nonce = self
+            .passkey_nonce()
+            .ok_or(ClerkPasskeyError::MissingOptions)?;
+        let options = PublicKeyCredentialRequestOptions::from_nonce(nonce)?;
+        let credential = authenticator
+            .get(&options)
+            .await
+            .map_err(ClerkPasskeyError::Authenticator)?;
+        self.attempt_first_factor(FirstFactor::Passkey(credential.to_json()))
+            .await
+            .map_err(ClerkPasskeyError::ClerkSignInError)
+    }
+
+    fn passkey_nonce(&self) -> Option<&str> {
+        match self.first_factor_verification()? {
+            ClientSignInFirstFactorVerification::StubsVerificationPasskey(v) => v.nonce.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_options() {
+        let creation = PublicKeyCredentialCreationOptions::from_nonce(
+            &serde_json::json!({
+                "rp": { "id": "example.com", "name": "Example" },
+                "user": { "id": "dXNlcl8x", "name": "john", "displayName": "John" },
+                "challenge": "Y2hhbGxlbmdl",
+                "pubKeyCredParams": [{ "type": "public-key", "alg": -7 }],
+                "timeout": 60000,
+                "excludeCredentials": [{ "type": "public-key", "id": "AQID" }],
+                "authenticatorSelection": { "residentKey": "required", "userVerification": "required" },
+                "attestation": "none"
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(creation.user.id, b"user_1");
+        assert_eq!(creation.challenge, b"challenge");
+        assert_eq!(creation.pub_key_cred_params[0].alg, ES256);
+        assert_eq!(creation.exclude_credentials[0].id, vec![1, 2, 3]);
+
+        let request = PublicKeyCredentialRequestOptions::from_nonce(
+            r#"{"challenge":"Y2hhbGxlbmdl","rpId":"example.com","userVerification":"required"}"#,
+        )
+        .unwrap();
+        assert_eq!(request.challenge, b"challenge");
+        assert_eq!(request.rp_id.as_deref(), Some("example.com"));
+        assert!(request.allow_credentials.is_empty());
+
+        assert!(PublicKeyCredentialRequestOptions::from_nonce("{}").is_err());
+    }
+
+    #[test]
+    fn test_credential_json() {
+        let credential = AssertionCredential {
+            raw_id: vec![1, 2, 3],
+            authenticator_attachment: Some("platform".to_string()),
+            client_data_json: b"{}".to_vec(),
+            authenticator_data: vec![4],
+            signature: vec![5],
+            user_handle: None,
+        };
+        let json: serde_json::Value = serde_json::from_str(&credential.to_json()).unwrap();
+        assert_eq!(json["id"], "AQID");
+        assert_eq!(json["rawId"], "AQID");
+        assert_eq!(json["type"], "public-key");
+        assert_eq!(json["response"]["clientDataJSON"], "e30");
+        assert_eq!(json["response"]["userHandle"], serde_json::Value::Null);
+    }
+}
@@ -0,0 +1,173 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, Response, StatusCode};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Retry policy for requests to the Clerk Frontend API
+///
+/// Requests are retried when they fail to connect, when Clerk responds with
+/// 429 and, for idempotent methods, on timeouts and 5xx responses. The delay
+/// between attempts grows exponentially from `initial_backoff` up to
+/// `max_backoff` with full jitter, or follows the `Retry-After` header when
+/// the response has one. No new attempt is started once `max_elapsed` would
+/// be exceeded.
+///
+/// # Examples
+/// ```
+/// use clerk_fapi_rs::configuration::ClerkFapiConfiguration;
+/// use clerk_fapi_rs::retry::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let config = ClerkFapiConfiguration::new(
+///     "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+///     None,
+///     None,
+/// )
+/// .unwrap()
+/// .with_retry_policy(RetryPolicy {
+///     max_retries: 5,
+///     max_elapsed: Duration::from_secs(60),
+///     ..Default::default()
+/// });
+/// assert_eq!(config.retry_policy().max_retries, 5);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, 0 disables retrying
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Total time budget for all the attempts and the delays between them
+    pub max_elapsed: Duration,
+    /// Randomize the backoff to avoid clients retrying in lockstep
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            max_elapsed: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that sends every request exactly once
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Backoff before the given retry, starting from 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter {
+            exponential.mul_f64(random_fraction())
+        } else {
+            exponential
+        }
+    }
+
+    /// Whether the failed request should be retried
+    pub(crate) fn should_retry_error(&self, method: &Method, error: &reqwest::Error) -> bool {
+        // Connection errors mean the request never reached Clerk
+        is_connect_error(error) || (error.is_timeout() && is_idempotent(method))
+    }
+
+    /// Whether the response should be retried
+    pub(crate) fn should_retry_response(&self, method: &Method, response: &Response) -> bool {
+        let status = response.status();
+        status == StatusCode::TOO_MANY_REQUESTS
+            || (status.is_server_error() && is_idempotent(method))
+    }
+}
+
+pub(crate) fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn is_connect_error(error: &reqwest::Error) -> bool {
+    error.is_connect()
+}
+
+// The fetch API doesn't tell connection errors apart, all request errors
+// happen before a response
+#[cfg(target_arch = "wasm32")]
+fn is_connect_error(error: &reqwest::Error) -> bool {
+    error.is_request()
+}
+
+/// Delay asked by the `Retry-After` header, either seconds or an HTTP date
+pub(crate) fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    let now = std::time::UNIX_EPOCH + SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Random number in [0, 1) for the jitter, doesn't need to be more than
+/// different between clients and calls
+fn random_fraction() -> f64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    // splitmix64
+    let mut x = nanos.wrapping_add(
+        COUNTER
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15),
+    );
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^= x >> 31;
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(30), Duration::from_secs(5));
+
+        let policy = RetryPolicy::default();
+        for retry in 1..10 {
+            assert!(policy.backoff(retry) <= policy.max_backoff);
+        }
+    }
+
+    #[test]
+    fn test_idempotent_methods() {
+        assert!(is_idempotent(&Method::GET));
+        assert!(is_idempotent(&Method::DELETE));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+    }
+}
@@ -0,0 +1,560 @@
+use crate::clerk::Clerk;
+use crate::clerk_state::ClerkNotLoadedError;
+use crate::error::ClerkApiError;
+use crate::models::client_session_reverification::Status;
+use crate::models::{
+    ClientSessionReverification, ClientSessionReverificationFirstFactorVerification,
+    ClientSessionReverificationSecondFactorVerification, StubsSignInFactor,
+};
+use crate::sign_in::{
+    strategy_name, FirstFactor, PrepareFirstFactor, PrepareSecondFactor, SecondFactor,
+};
+use log::error;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+pub use crate::models::client_session_reverification::Status as ReverificationStatus;
+
+/// Factors the user needs to verify again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReverificationLevel {
+    FirstFactor,
+    /// The second factor, or the first factor when the user has no second
+    /// factor
+    SecondFactor,
+    /// Both factors, or only the first factor when the user has no second
+    /// factor
+    MultiFactor,
+}
+
+impl ReverificationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReverificationLevel::FirstFactor => "first_factor",
+            ReverificationLevel::SecondFactor => "second_factor",
+            ReverificationLevel::MultiFactor => "multi_factor",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "first_factor" => Some(ReverificationLevel::FirstFactor),
+            "second_factor" => Some(ReverificationLevel::SecondFactor),
+            "multi_factor" => Some(ReverificationLevel::MultiFactor),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ReverificationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How recently the user must have verified which factors
+///
+/// The presets match the ones of the Clerk SDKs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReverificationPolicy {
+    pub level: ReverificationLevel,
+    pub max_age: Duration,
+}
+
+impl ReverificationPolicy {
+    /// Second factor verified within the last 10 minutes
+    pub const STRICT: Self = Self::minutes(ReverificationLevel::SecondFactor, 10);
+    /// Both factors verified within the last 10 minutes
+    pub const STRICT_MFA: Self = Self::minutes(ReverificationLevel::MultiFactor, 10);
+    /// Second factor verified within the last hour
+    pub const MODERATE: Self = Self::minutes(ReverificationLevel::SecondFactor, 60);
+    /// Second factor verified within the last day
+    pub const LAX: Self = Self::minutes(ReverificationLevel::SecondFactor, 24 * 60);
+
+    const fn minutes(level: ReverificationLevel, minutes: u64) -> Self {
+        Self {
+            level,
+            max_age: Duration::from_secs(minutes * 60),
+        }
+    }
+
+    /// Preset by its name, example `strict_mfa`
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "strict" => Some(Self::STRICT),
+            "strict_mfa" => Some(Self::STRICT_MFA),
+            "moderate" => Some(Self::MODERATE),
+            "lax" => Some(Self::LAX),
+            _ => None,
+        }
+    }
+
+    /// Whether the session's `factor_verification_age` satisfies the policy
+    ///
+    /// The ages are the minutes since the first and the second factor were
+    /// verified, -1 when the factor hasn't been verified.
+    pub fn is_satisfied(&self, factor_verification_age: &[i64]) -> bool {
+        let max_age = (self.max_age.as_secs() / 60) as i64;
+        let fresh = |age: Option<&i64>| match age {
+            Some(&age) if age >= 0 => Some(age < max_age),
+            _ => None,
+        };
+        let first = fresh(factor_verification_age.first());
+        let second = fresh(factor_verification_age.get(1));
+        let satisfied = match (self.level, second) {
+            (ReverificationLevel::FirstFactor, _) => first,
+            (ReverificationLevel::SecondFactor, Some(second)) => Some(second),
+            (ReverificationLevel::SecondFactor, None) => first,
+            (ReverificationLevel::MultiFactor, Some(second)) => first.map(|first| first && second),
+            (ReverificationLevel::MultiFactor, None) => first,
+        };
+        satisfied.unwrap_or(false)
+    }
+
+    /// Policy the API asked for when it rejected a request with
+    /// `session_reverification_required`, `STRICT` when the error doesn't
+    /// tell
+    pub fn from_api_error(error: &ClerkApiError) -> Option<Self> {
+        if !error.is_reverification_required() {
+            return None;
+        }
+        let hint = error
+            .errors()
+            .iter()
+            .filter_map(|e| e.meta.as_ref())
+            .chain(error.meta())
+            .find_map(|meta| meta.get("reverification"));
+        let level = hint
+            .and_then(|h| h.get("level"))
+            .and_then(|l| l.as_str())
+            .and_then(ReverificationLevel::parse);
+        let minutes = hint
+            .and_then(|h| h.get("after_minutes").or_else(|| h.get("afterMinutes")))
+            .and_then(|m| m.as_u64());
+        Some(match (level, minutes) {
+            (Some(level), Some(minutes)) => Self::minutes(level, minutes),
+            (Some(level), None) => Self {
+                level,
+                ..Self::STRICT
+            },
+            _ => Self::STRICT,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ClerkReverificationError {
+    ClerkNotLoadedError(ClerkNotLoadedError),
+    /// There is no active session to reverify
+    NoSession,
+    ClerkApiError(ClerkApiError),
+    UnsupportedFactor(String),
+    /// The reverification finished without completing
+    NotCompleted,
+}
+impl fmt::Display for ClerkReverificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkReverificationError::ClerkNotLoadedError(e) => e.fmt(f),
+            ClerkReverificationError::NoSession => write!(f, "No active session to reverify"),
+            ClerkReverificationError::ClerkApiError(e) => e.fmt(f),
+            ClerkReverificationError::UnsupportedFactor(strategy) => {
+                write!(
+                    f,
+                    "Factor {strategy} is not supported for this reverification"
+                )
+            }
+            ClerkReverificationError::NotCompleted => write!(f, "Reverification not completed"),
+        }
+    }
+}
+impl Error for ClerkReverificationError {}
+
+impl ClerkReverificationError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkReverificationError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Typed step-up flow on top of the session reverification endpoints
+///
+/// The flow is started with `Clerk::reverify`. The first factor, and for the
+/// second factor levels the second factor, are prepared and attempted until
+/// the reverification is complete. The session's `factor_verification_age`
+/// is updated with the client returned by the API.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::reverification::ReverificationLevel;
+/// use clerk_fapi_rs::sign_in::{FirstFactor, SecondFactor};
+///
+/// let mut reverification = clerk.reverify(ReverificationLevel::SecondFactor).await?;
+/// if reverification.needs_first_factor() {
+///     reverification
+///         .attempt_first_factor(FirstFactor::Password("hunter2".to_string()))
+///         .await?;
+/// }
+/// if reverification.needs_second_factor() {
+///     reverification
+///         .attempt_second_factor(SecondFactor::Totp("123456".to_string()))
+///         .await?;
+/// }
+/// assert!(reverification.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ReverificationFlow {
+    clerk: Clerk,
+    session_id: String,
+    reverification: ClientSessionReverification,
+}
+
+impl fmt::Debug for ReverificationFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReverificationFlow")
+            .field("session_id", &self.session_id)
+            .field("level", &self.reverification.level)
+            .field("status", &self.reverification.status)
+            .finish()
+    }
+}
+
+impl ReverificationFlow {
+    /// Starts reverifying the active session on the given level
+    pub async fn start(
+        clerk: &Clerk,
+        level: ReverificationLevel,
+    ) -> Result<Self, ClerkReverificationError> {
+        let session_id = clerk
+            .session()
+            .map_err(ClerkReverificationError::ClerkNotLoadedError)?
+            .ok_or(ClerkReverificationError::NoSession)?
+            .id;
+        let reverification = clerk
+            .get_fapi_client()
+            .start_session_reverification(&session_id, level.as_str())
+            .await
+            .map_err(|e| {
+                error!("Failed to start session reverification: {e}");
+                ClerkReverificationError::ClerkApiError(e.into())
+            })?;
+        Ok(Self {
+            clerk: clerk.clone(),
+            session_id,
+            reverification,
+        })
+    }
+
+    /// The current reverification as returned by the API
+    pub fn reverification(&self) -> &ClientSessionReverification {
+        &self.reverification
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn level(&self) -> Option<ReverificationLevel> {
+        ReverificationLevel::parse(&self.reverification.level)
+    }
+
+    pub fn status(&self) -> ReverificationStatus {
+        self.reverification.status
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.reverification.status == Status::Complete
+    }
+
+    /// Cached tokens carry the factor verification age from when they were
+    /// issued, once reverified the next `get_token` fetches a fresh one
+    fn clear_tokens_if_complete(&self) {
+        if self.is_complete() {
+            self.clerk.clear_session_tokens(&self.session_id);
+        }
+    }
+
+    pub fn needs_first_factor(&self) -> bool {
+        self.reverification.status == Status::NeedsFirstFactor
+    }
+
+    pub fn needs_second_factor(&self) -> bool {
+        self.reverification.status == Status::NeedsSecondFactor
+    }
+
+    pub fn supported_first_factors(&self) -> &[StubsSignInFactor] {
+        self.reverification
+            .supported_first_factors
+            .as_deref()
+            .unwrap_or(&[])
+    }
+
+    pub fn supported_second_factors(&self) -> &[StubsSignInFactor] {
+        self.reverification
+            .supported_second_factors
+            .as_deref()
+            .unwrap_or(&[])
+    }
+
+    /// Returns the supported first factor for the strategy, example to show
+    /// the `safe_identifier` the code was sent to
+    pub fn first_factor(&self, strategy: &str) -> Option<&StubsSignInFactor> {
+        self.supported_first_factors()
+            .iter()
+            .find(|f| strategy_name(&f.strategy) == strategy)
+    }
+
+    pub fn second_factor(&self, strategy: &str) -> Option<&StubsSignInFactor> {
+        self.supported_second_factors()
+            .iter()
+            .find(|f| strategy_name(&f.strategy) == strategy)
+    }
+
+    /// Verification of the first factor, contains example the passkey
+    /// challenge after the factor has been prepared
+    pub fn first_factor_verification(
+        &self,
+    ) -> Option<&ClientSessionReverificationFirstFactorVerification> {
+        self.reverification.first_factor_verification.as_deref()
+    }
+
+    pub fn second_factor_verification(
+        &self,
+    ) -> Option<&ClientSessionReverificationSecondFactorVerification> {
+        self.reverification.second_factor_verification.as_deref()
+    }
+
+    /// Prepares the first factor, example sends the email or SMS code
+    pub async fn prepare_first_factor(
+        &mut self,
+        factor: PrepareFirstFactor,
+    ) -> Result<ReverificationStatus, ClerkReverificationError> {
+        let strategy = factor.strategy();
+        let supported = self
+            .first_factor(strategy)
+            .cloned()
+            .ok_or_else(|| ClerkReverificationError::UnsupportedFactor(strategy.to_string()))?;
+
+        self.reverification = self
+            .clerk
+            .get_fapi_client()
+            .prepare_session_reverification_first_factor(
+                &self.session_id,
+                None, // origin
+                Some(strategy),
+                supported.email_address_id.as_deref(),
+                supported.phone_number_id.as_deref(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to prepare reverification first factor: {e}");
+                ClerkReverificationError::ClerkApiError(e.into())
+            })?;
+        Ok(self.status())
+    }
+
+    /// Attempts the first factor, codes, passwords and passkeys are
+    /// supported
+    pub async fn attempt_first_factor(
+        &mut self,
+        factor: FirstFactor,
+    ) -> Result<ReverificationStatus, ClerkReverificationError> {
+        let mut code = None;
+        let mut password = None;
+        let mut public_key_credential = None;
+        match &factor {
+            FirstFactor::EmailCode(c) | FirstFactor::PhoneCode(c) => code = Some(c.as_str()),
+            FirstFactor::Password(p) => password = Some(p.as_str()),
+            FirstFactor::Passkey(c) => public_key_credential = Some(c.as_str()),
+            _ => {
+                return Err(ClerkReverificationError::UnsupportedFactor(
+                    factor.strategy().to_string(),
+                ))
+            }
+        }
+
+        self.reverification = self
+            .clerk
+            .get_fapi_client()
+            .attempt_session_reverification_first_factor(
+                &self.session_id,
+                factor.strategy(),
+                None, // origin
+                code,
+                password,
+                public_key_credential,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to attempt reverification first factor: {e}");
+                ClerkReverificationError::ClerkApiError(e.into())
+            })?;
+        self.clear_tokens_if_complete();
+        Ok(self.status())
+    }
+
+    /// Prepares the second factor, example sends the SMS code
+    pub async fn prepare_second_factor(
+        &mut self,
+        factor: PrepareSecondFactor,
+    ) -> Result<ReverificationStatus, ClerkReverificationError> {
+        let strategy = factor.strategy();
+        let supported = self
+            .second_factor(strategy)
+            .cloned()
+            .ok_or_else(|| ClerkReverificationError::UnsupportedFactor(strategy.to_string()))?;
+
+        self.reverification = self
+            .clerk
+            .get_fapi_client()
+            .prepare_session_reverification_second_factor(
+                &self.session_id,
+                Some(strategy),
+                supported.phone_number_id.as_deref(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to prepare reverification second factor: {e}");
+                ClerkReverificationError::ClerkApiError(e.into())
+            })?;
+        Ok(self.status())
+    }
+
+    pub async fn attempt_second_factor(
+        &mut self,
+        factor: SecondFactor,
+    ) -> Result<ReverificationStatus, ClerkReverificationError> {
+        self.reverification = self
+            .clerk
+            .get_fapi_client()
+            .attempt_session_reverification_second_factor(
+                &self.session_id,
+                Some(factor.strategy()),
+                Some(factor.code()),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to attempt reverification second factor: {e}");
+                ClerkReverificationError::ClerkApiError(e.into())
+            })?;
+        self.clear_tokens_if_complete();
+        Ok(self.status())
+    }
+}
+
+/// Runs the operation, and when the API rejects it with
+/// `session_reverification_required` lets `reverify` complete the step-up
+/// and runs the operation once more
+pub(crate) async fn with_reverification<T, Op, OpFut, R, RFut>(
+    clerk: &Clerk,
+    operation: Op,
+    reverify: R,
+) -> Result<T, ClerkReverificationError>
+where
+    Op: Fn() -> OpFut,
+    OpFut: Future<Output = Result<T, ClerkApiError>>,
+    R: FnOnce(ReverificationFlow) -> RFut,
+    RFut: Future<Output = Result<ReverificationFlow, ClerkReverificationError>>,
+{
+    let error = match operation().await {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    let Some(policy) = ReverificationPolicy::from_api_error(&error) else {
+        return Err(ClerkReverificationError::ClerkApiError(error));
+    };
+
+    let flow = ReverificationFlow::start(clerk, policy.level).await?;
+    let flow = if flow.is_complete() {
+        flow
+    } else {
+        reverify(flow).await?
+    };
+    if !flow.is_complete() {
+        return Err(ClerkReverificationError::NotCompleted);
+    }
+    flow.clear_tokens_if_complete();
+    operation()
+        .await
+        .map_err(ClerkReverificationError::ClerkApiError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policies() {
+        let strict = ReverificationPolicy::STRICT;
+        assert!(strict.is_satisfied(&[5, 5]));
+        assert!(!strict.is_satisfied(&[5, 10]));
+        // Without second factor the first factor counts
+        assert!(strict.is_satisfied(&[5, -1]));
+        assert!(!strict.is_satisfied(&[-1, -1]));
+        assert!(!strict.is_satisfied(&[]));
+
+        let strict_mfa = ReverificationPolicy::STRICT_MFA;
+        assert!(!strict_mfa.is_satisfied(&[30, 5]));
+        assert!(strict_mfa.is_satisfied(&[5, 5]));
+        assert!(strict_mfa.is_satisfied(&[5, -1]));
+
+        let first_factor = ReverificationPolicy {
+            level: ReverificationLevel::FirstFactor,
+            max_age: Duration::from_secs(600),
+        };
+        assert!(first_factor.is_satisfied(&[5, 100]));
+
+        assert!(ReverificationPolicy::MODERATE.is_satisfied(&[0, 59]));
+        assert!(!ReverificationPolicy::MODERATE.is_satisfied(&[0, 60]));
+        assert!(ReverificationPolicy::LAX.is_satisfied(&[0, 1000]));
+        assert_eq!(
+            ReverificationPolicy::preset("strict_mfa"),
+            Some(ReverificationPolicy::STRICT_MFA)
+        );
+    }
+
+    #[test]
+    fn test_policy_from_api_error() {
+        let error = ClerkApiError::from_response(
+            403,
+            &serde_json::json!({
+                "errors": [{
+                    "message": "Reverification required",
+                    "long_message": "You need to provide additional verification to perform this operation",
+                    "code": "session_reverification_required",
+                    "meta": {
+                        "reverification": { "level": "multi_factor", "after_minutes": 60 }
+                    }
+                }]
+            })
+            .to_string(),
+        );
+        assert_eq!(
+            ReverificationPolicy::from_api_error(&error),
+            Some(ReverificationPolicy {
+                level: ReverificationLevel::MultiFactor,
+                max_age: Duration::from_secs(3600),
+            })
+        );
+
+        let error = ClerkApiError::from_response(
+            403,
+            r#"{"errors":[{"message":"","long_message":"","code":"session_reverification_required"}]}"#,
+        );
+        assert_eq!(
+            ReverificationPolicy::from_api_error(&error),
+            Some(ReverificationPolicy::STRICT)
+        );
+
+        let error = ClerkApiError::from_response(403, "{}");
+        assert_eq!(ReverificationPolicy::from_api_error(&error), None);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::clerk::Clerk;
+use crate::clerk_state::{now_millis, ClerkNotLoadedError};
+use crate::error::ClerkApiError;
+use crate::models::{ClientClient as Client, ClientSession as Session, ClientUser as User};
+use crate::sign_in::ClerkSignInError;
+use log::{error, warn};
+use std::error::Error;
+use std::fmt;
+
+pub use crate::models::client_session::Status as SessionStatus;
+
+/// What signing in does when the instance is in single session mode and
+/// the client already has an active session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SingleSessionSignIn {
+    /// Fails the sign in with `ClerkSignInError::SessionExists`
+    #[default]
+    Block,
+    /// Signs out the active session before signing in
+    Replace,
+}
+
+#[derive(Debug)]
+pub enum ClerkSessionError {
+    ClerkNotLoadedError(ClerkNotLoadedError),
+    ClerkApiError(ClerkApiError),
+    /// The client has no session with the id
+    NoMatchFound(String),
+    /// The session is not active, example it has ended or expired
+    NotActive(String),
+}
+impl fmt::Display for ClerkSessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkSessionError::ClerkNotLoadedError(e) => e.fmt(f),
+            ClerkSessionError::ClerkApiError(e) => e.fmt(f),
+            ClerkSessionError::NoMatchFound(id) => write!(f, "No session {id} on the client"),
+            ClerkSessionError::NotActive(id) => write!(f, "Session {id} is not active"),
+        }
+    }
+}
+impl Error for ClerkSessionError {}
+
+impl ClerkSessionError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkSessionError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// One of the sessions on the client, returned by `Clerk::sessions`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInfo {
+    pub id: String,
+    pub status: SessionStatus,
+    pub user: Option<User>,
+    pub last_active_organization_id: Option<String>,
+    /// Milliseconds since the epoch
+    pub last_active_at: i64,
+    /// Milliseconds since the epoch
+    pub expire_at: i64,
+    /// Whether this is the active session of the client
+    pub is_current: bool,
+}
+
+impl SessionInfo {
+    fn new(session: &Session, current_session_id: Option<&str>) -> Self {
+        Self {
+            id: session.id.clone(),
+            status: session.status,
+            user: session.user.as_deref().cloned(),
+            last_active_organization_id: session.last_active_organization_id.clone(),
+            last_active_at: session.last_active_at,
+            expire_at: session.expire_at,
+            is_current: current_session_id == Some(session.id.as_str()),
+        }
+    }
+
+    /// Whether the session is active and hasn't expired, only those can be
+    /// switched to and used for tokens
+    pub fn is_active(&self) -> bool {
+        self.status == SessionStatus::Active && !self.is_expired()
+    }
+
+    /// Whether `expire_at` has passed
+    pub fn is_expired(&self) -> bool {
+        self.expire_at <= now_millis()
+    }
+}
+
+/// Views of all the sessions on the client
+pub(crate) fn sessions(client: &Client) -> Vec<SessionInfo> {
+    let current = client.last_active_session_id.as_deref();
+    client
+        .sessions
+        .iter()
+        .map(|s| SessionInfo::new(s, current))
+        .collect()
+}
+
+/// The most recently active session other than `except`, the one clerk-js
+/// moves to after the active session is signed out
+pub(crate) fn next_active_session(client: &Client, except: &str) -> Option<SessionInfo> {
+    sessions(client)
+        .into_iter()
+        .filter(|s| s.id != except && s.is_active())
+        .max_by_key(|s| s.last_active_at)
+}
+
+/// Active session with the id
+pub(crate) fn find_active_session(
+    client: &Client,
+    session_id: &str,
+) -> Result<Session, ClerkSessionError> {
+    let session = client
+        .sessions
+        .iter()
+        .find(|s| s.id == session_id)
+        .ok_or_else(|| ClerkSessionError::NoMatchFound(session_id.to_string()))?;
+    if SessionInfo::new(session, None).is_active() {
+        Ok(session.clone())
+    } else {
+        Err(ClerkSessionError::NotActive(session_id.to_string()))
+    }
+}
+
+/// Applies `SingleSessionSignIn` before a sign in is created
+///
+/// Does nothing when the instance allows multiple sessions, there is no
+/// active session or the environment isn't loaded, in which case the API
+/// has the final word.
+pub(crate) async fn before_sign_in(clerk: &Clerk) -> Result<(), ClerkSignInError> {
+    if !clerk.single_session_mode().unwrap_or(false) {
+        return Ok(());
+    }
+    let Some(session) = clerk.session().ok().flatten() else {
+        return Ok(());
+    };
+    match clerk.config().single_session_sign_in() {
+        SingleSessionSignIn::Block => {
+            warn!("Clerk: Already signed in and single session mode is on");
+            Err(ClerkSignInError::SessionExists(session.id))
+        }
+        SingleSessionSignIn::Replace => clerk.sign_out(Some(session.id)).await.map_err(|e| {
+            error!("Failed to sign out the replaced session: {e}");
+            ClerkSignInError::ClerkApiError(e)
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::client_session::Status;
+
+    fn session(id: &str, status: Status, last_active_at: i64) -> Session {
+        Session {
+            id: id.to_string(),
+            status,
+            last_active_at,
+            expire_at: now_millis() + 60_000,
+            ..Default::default()
+        }
+    }
+
+    fn client(sessions: Vec<Session>, current: &str) -> Client {
+        Client {
+            sessions,
+            last_active_session_id: Some(current.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sessions() {
+        let mut expired = session("sess_expired", Status::Active, 4);
+        expired.expire_at = now_millis() - 1;
+        let client = client(
+            vec![
+                session("sess_1", Status::Active, 1),
+                session("sess_2", Status::Active, 2),
+                session("sess_ended", Status::Ended, 3),
+                expired,
+            ],
+            "sess_1",
+        );
+
+        let infos = sessions(&client);
+        assert_eq!(infos.len(), 4);
+        assert!(infos[0].is_current && infos[0].is_active());
+        assert!(!infos[1].is_current);
+        assert!(!infos[2].is_active());
+        assert!(infos[3].is_expired() && !infos[3].is_active());
+
+        assert_eq!(next_active_session(&client, "sess_1").unwrap().id, "sess_2");
+        assert!(next_active_session(&client, "sess_2").is_some());
+
+        assert!(find_active_session(&client, "sess_2").is_ok());
+        assert!(matches!(
+            find_active_session(&client, "sess_ended"),
+            Err(ClerkSessionError::NotActive(_))
+        ));
+        assert!(matches!(
+            find_active_session(&client, "sess_expired"),
+            Err(ClerkSessionError::NotActive(_))
+        ));
+        assert!(matches!(
+            find_active_session(&client, "sess_nope"),
+            Err(ClerkSessionError::NoMatchFound(_))
+        ));
+    }
+}
@@ -0,0 +1,609 @@
+use crate::clerk::{Clerk, ClerkSetActiveError};
+use crate::error::ClerkApiError;
+use crate::models::client_sign_in::Status;
+use crate::models::{
+    ClientSignIn, ClientSignInFirstFactorVerification, ClientSignInSecondFactorVerification,
+    StubsSignInFactor,
+};
+use crate::sessions::before_sign_in;
+use log::error;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+pub use crate::models::client_sign_in::Status as SignInStatus;
+
+/// Web3 wallet signature strategies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Web3Strategy {
+    Metamask,
+    CoinbaseWallet,
+    OkxWallet,
+    Base,
+}
+
+impl Web3Strategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Web3Strategy::Metamask => "web3_metamask_signature",
+            Web3Strategy::CoinbaseWallet => "web3_coinbase_wallet_signature",
+            Web3Strategy::OkxWallet => "web3_okx_wallet_signature",
+            Web3Strategy::Base => "web3_base_signature",
+        }
+    }
+}
+
+/// First factor to prepare before attempting it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrepareFirstFactor {
+    EmailCode,
+    /// Email link that redirects to the given url after verification
+    EmailLink {
+        redirect_url: String,
+    },
+    PhoneCode,
+    Passkey,
+    Web3(Web3Strategy),
+    ResetPasswordEmailCode,
+    ResetPasswordPhoneCode,
+}
+
+impl PrepareFirstFactor {
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            PrepareFirstFactor::EmailCode => "email_code",
+            PrepareFirstFactor::EmailLink { .. } => "email_link",
+            PrepareFirstFactor::PhoneCode => "phone_code",
+            PrepareFirstFactor::Passkey => "passkey",
+            PrepareFirstFactor::Web3(web3) => web3.as_str(),
+            PrepareFirstFactor::ResetPasswordEmailCode => "reset_password_email_code",
+            PrepareFirstFactor::ResetPasswordPhoneCode => "reset_password_phone_code",
+        }
+    }
+}
+
+/// First factor attempt with the value to verify
+#[derive(Clone, PartialEq, Eq)]
+pub enum FirstFactor {
+    EmailCode(String),
+    PhoneCode(String),
+    Password(String),
+    Ticket(String),
+    /// The public key credential JSON returned by the authenticator
+    Passkey(String),
+    Web3 {
+        strategy: Web3Strategy,
+        signature: String,
+    },
+    ResetPasswordEmailCode(String),
+    ResetPasswordPhoneCode(String),
+}
+
+impl FirstFactor {
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            FirstFactor::EmailCode(_) => "email_code",
+            FirstFactor::PhoneCode(_) => "phone_code",
+            FirstFactor::Password(_) => "password",
+            FirstFactor::Ticket(_) => "ticket",
+            FirstFactor::Passkey(_) => "passkey",
+            FirstFactor::Web3 { strategy, .. } => strategy.as_str(),
+            FirstFactor::ResetPasswordEmailCode(_) => "reset_password_email_code",
+            FirstFactor::ResetPasswordPhoneCode(_) => "reset_password_phone_code",
+        }
+    }
+}
+
+// Codes and passwords are kept out of logs
+impl fmt::Debug for FirstFactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FirstFactor({})", self.strategy())
+    }
+}
+
+/// Second factor to prepare before attempting it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrepareSecondFactor {
+    PhoneCode,
+    EmailCode,
+}
+
+impl PrepareSecondFactor {
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            PrepareSecondFactor::PhoneCode => "phone_code",
+            PrepareSecondFactor::EmailCode => "email_code",
+        }
+    }
+}
+
+/// Second factor attempt with the code to verify
+#[derive(Clone, PartialEq, Eq)]
+pub enum SecondFactor {
+    PhoneCode(String),
+    EmailCode(String),
+    Totp(String),
+    BackupCode(String),
+}
+
+impl SecondFactor {
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            SecondFactor::PhoneCode(_) => "phone_code",
+            SecondFactor::EmailCode(_) => "email_code",
+            SecondFactor::Totp(_) => "totp",
+            SecondFactor::BackupCode(_) => "backup_code",
+        }
+    }
+
+    pub(crate) fn code(&self) -> &str {
+        match self {
+            SecondFactor::PhoneCode(code)
+            | SecondFactor::EmailCode(code)
+            | SecondFactor::Totp(code)
+            | SecondFactor::BackupCode(code) => code,
+        }
+    }
+}
+
+impl fmt::Debug for SecondFactor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecondFactor({})", self.strategy())
+    }
+}
+
+#[derive(Debug)]
+pub enum ClerkSignInError {
+    ClerkApiError(ClerkApiError),
+    UnsupportedFactor(String),
+    ClerkSetActiveError(ClerkSetActiveError),
+    /// The instance is in single session mode and the session with the id
+    /// is already active, see `SingleSessionSignIn`
+    SessionExists(String),
+}
+impl fmt::Display for ClerkSignInError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkSignInError::ClerkApiError(e) => e.fmt(f),
+            ClerkSignInError::UnsupportedFactor(strategy) => {
+                write!(f, "Factor {strategy} is not supported for this sign in")
+            }
+            ClerkSignInError::ClerkSetActiveError(e) => e.fmt(f),
+            ClerkSignInError::SessionExists(id) => {
+                write!(f, "Already signed in with session {id}")
+            }
+        }
+    }
+}
+impl Error for ClerkSignInError {}
+
+impl ClerkSignInError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkSignInError::ClerkApiError(e) => Some(e),
+            ClerkSignInError::ClerkSetActiveError(e) => e.api_error(),
+            _ => None,
+        }
+    }
+}
+
+/// Strategy of the factor as it's serialized by the API
+pub(crate) fn strategy_name<T: Serialize>(strategy: &T) -> String {
+    serde_json::to_value(strategy)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Typed sign in flow on top of the sign in endpoints
+///
+/// The flow is started with `Clerk::sign_in` or `Clerk::sign_in_with_ticket`.
+/// After that the first factor, and if the instance requires it the second
+/// factor, are prepared and attempted until the sign in is complete. When the
+/// sign in completes the created session is set active automatically.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::sign_in::{FirstFactor, PrepareFirstFactor, SecondFactor};
+///
+/// let mut sign_in = clerk.sign_in("user@example.com").await?;
+/// sign_in.prepare_first_factor(PrepareFirstFactor::EmailCode).await?;
+/// sign_in
+///     .attempt_first_factor(FirstFactor::EmailCode("424242".to_string()))
+///     .await?;
+/// if sign_in.needs_second_factor() {
+///     sign_in
+///         .attempt_second_factor(SecondFactor::Totp("123456".to_string()))
+///         .await?;
+/// }
+/// assert!(sign_in.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SignInFlow {
+    clerk: Clerk,
+    sign_in: ClientSignIn,
+    /// Session we've already set active, to avoid touching it again on reload
+    activated_session_id: Option<String>,
+}
+
+impl fmt::Debug for SignInFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SignInFlow")
+            .field("id", &self.sign_in.id)
+            .field("status", &self.sign_in.status)
+            .finish()
+    }
+}
+
+impl SignInFlow {
+    /// Starts a sign in with the given identifier, example email address,
+    /// phone number or username
+    pub async fn create(clerk: &Clerk, identifier: &str) -> Result<Self, ClerkSignInError> {
+        before_sign_in(clerk).await?;
+        let sign_in = clerk
+            .get_fapi_client()
+            .create_sign_in(
+                None,             // origin
+                None,             // strategy
+                Some(identifier), // identifier
+                None,             // password
+                None,             // ticket
+                None,             // redirect_url
+                None,             // action_complete_redirect_url
+                None,             // transfer
+                None,             // code
+                None,             // token
+                None,             // oidc_login_hint
+                None,             // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to create sign in: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        Self::from_sign_in(clerk, sign_in).await
+    }
+
+    /// Starts a sign in with a ticket, example from an invitation
+    pub async fn create_with_ticket(clerk: &Clerk, ticket: &str) -> Result<Self, ClerkSignInError> {
+        before_sign_in(clerk).await?;
+        let sign_in = clerk
+            .get_fapi_client()
+            .create_sign_in(
+                None,           // origin
+                Some("ticket"), // strategy
+                None,           // identifier
+                None,           // password
+                Some(ticket),   // ticket
+                None,           // redirect_url
+                None,           // action_complete_redirect_url
+                None,           // transfer
+                None,           // code
+                None,           // token
+                None,           // oidc_login_hint
+                None,           // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to create sign in with ticket: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        Self::from_sign_in(clerk, sign_in).await
+    }
+
+    /// Starts a sign in from a transferable sign up, example when an OAuth
+    /// sign up finds that the account already exists
+    pub async fn create_with_transfer(clerk: &Clerk) -> Result<Self, ClerkSignInError> {
+        before_sign_in(clerk).await?;
+        let sign_in = clerk
+            .get_fapi_client()
+            .create_sign_in(
+                None,       // origin
+                None,       // strategy
+                None,       // identifier
+                None,       // password
+                None,       // ticket
+                None,       // redirect_url
+                None,       // action_complete_redirect_url
+                Some(true), // transfer
+                None,       // code
+                None,       // token
+                None,       // oidc_login_hint
+                None,       // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to transfer sign up to sign in: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        Self::from_sign_in(clerk, sign_in).await
+    }
+
+    /// Continues an existing sign in, example the one in `Client::sign_in`
+    pub async fn from_sign_in(
+        clerk: &Clerk,
+        sign_in: ClientSignIn,
+    ) -> Result<Self, ClerkSignInError> {
+        let mut flow = Self {
+            clerk: clerk.clone(),
+            sign_in: sign_in.clone(),
+            activated_session_id: None,
+        };
+        flow.update(sign_in).await?;
+        Ok(flow)
+    }
+
+    /// The current sign in as returned by the API
+    pub fn sign_in(&self) -> &ClientSignIn {
+        &self.sign_in
+    }
+
+    pub fn id(&self) -> &str {
+        &self.sign_in.id
+    }
+
+    pub fn status(&self) -> SignInStatus {
+        self.sign_in.status
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.sign_in.status == Status::Complete
+    }
+
+    pub fn needs_first_factor(&self) -> bool {
+        self.sign_in.status == Status::NeedsFirstFactor
+    }
+
+    pub fn needs_second_factor(&self) -> bool {
+        self.sign_in.status == Status::NeedsSecondFactor
+    }
+
+    pub fn needs_new_password(&self) -> bool {
+        self.sign_in.status == Status::NeedsNewPassword
+    }
+
+    /// Session created by the sign in, set once the sign in is complete
+    pub fn created_session_id(&self) -> Option<&str> {
+        self.sign_in.created_session_id.as_deref()
+    }
+
+    pub fn supported_first_factors(&self) -> &[StubsSignInFactor] {
+        self.sign_in
+            .supported_first_factors
+            .as_deref()
+            .unwrap_or(&[])
+    }
+
+    pub fn supported_second_factors(&self) -> &[StubsSignInFactor] {
+        self.sign_in
+            .supported_second_factors
+            .as_deref()
+            .unwrap_or(&[])
+    }
+
+    /// Returns the supported first factor for the strategy, example to show
+    /// the `safe_identifier` the code was sent to
+    pub fn first_factor(&self, strategy: &str) -> Option<&StubsSignInFactor> {
+        self.supported_first_factors()
+            .iter()
+            .find(|f| strategy_name(&f.strategy) == strategy)
+    }
+
+    pub fn second_factor(&self, strategy: &str) -> Option<&StubsSignInFactor> {
+        self.supported_second_factors()
+            .iter()
+            .find(|f| strategy_name(&f.strategy) == strategy)
+    }
+
+    /// Verification of the first factor, contains example the passkey or
+    /// web3 nonce after the factor has been prepared
+    pub fn first_factor_verification(&self) -> Option<&ClientSignInFirstFactorVerification> {
+        self.sign_in.first_factor_verification.as_deref()
+    }
+
+    pub fn second_factor_verification(&self) -> Option<&ClientSignInSecondFactorVerification> {
+        self.sign_in.second_factor_verification.as_deref()
+    }
+
+    /// Prepares the first factor, example sends the email or SMS code
+    pub async fn prepare_first_factor(
+        &mut self,
+        factor: PrepareFirstFactor,
+    ) -> Result<SignInStatus, ClerkSignInError> {
+        let strategy = factor.strategy();
+        let supported = self
+            .first_factor(strategy)
+            .cloned()
+            .ok_or_else(|| ClerkSignInError::UnsupportedFactor(strategy.to_string()))?;
+        let redirect_url = match &factor {
+            PrepareFirstFactor::EmailLink { redirect_url } => Some(redirect_url.as_str()),
+            _ => None,
+        };
+
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_in_factor_one(
+                &self.sign_in.id,
+                strategy,
+                None, // origin
+                supported.email_address_id.as_deref(),
+                supported.phone_number_id.as_deref(),
+                supported.web3_wallet_id.as_deref(),
+                supported.passkey_id.as_deref(),
+                redirect_url,
+                None, // action_complete_redirect_url
+                None, // oidc_login_hint
+                None, // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to prepare sign in first factor: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    /// Attempts the first factor, on completion the created session is set
+    /// active
+    pub async fn attempt_first_factor(
+        &mut self,
+        factor: FirstFactor,
+    ) -> Result<SignInStatus, ClerkSignInError> {
+        let mut code = None;
+        let mut password = None;
+        let mut signature = None;
+        let mut ticket = None;
+        let mut public_key_credential = None;
+        match &factor {
+            FirstFactor::EmailCode(c)
+            | FirstFactor::PhoneCode(c)
+            | FirstFactor::ResetPasswordEmailCode(c)
+            | FirstFactor::ResetPasswordPhoneCode(c) => code = Some(c.as_str()),
+            FirstFactor::Password(p) => password = Some(p.as_str()),
+            FirstFactor::Ticket(t) => ticket = Some(t.as_str()),
+            FirstFactor::Passkey(c) => public_key_credential = Some(c.as_str()),
+            FirstFactor::Web3 { signature: s, .. } => signature = Some(s.as_str()),
+        }
+
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_in_factor_one(
+                &self.sign_in.id,
+                factor.strategy(),
+                None, // origin
+                code,
+                password,
+                signature,
+                None, // token
+                ticket,
+                public_key_credential,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to attempt sign in first factor: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    /// Prepares the second factor, example sends the SMS code
+    pub async fn prepare_second_factor(
+        &mut self,
+        factor: PrepareSecondFactor,
+    ) -> Result<SignInStatus, ClerkSignInError> {
+        let strategy = factor.strategy();
+        let supported = self
+            .second_factor(strategy)
+            .cloned()
+            .ok_or_else(|| ClerkSignInError::UnsupportedFactor(strategy.to_string()))?;
+
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_in_factor_two(
+                &self.sign_in.id,
+                Some(strategy),
+                supported.phone_number_id.as_deref(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to prepare sign in second factor: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    /// Attempts the second factor, on completion the created session is set
+    /// active
+    pub async fn attempt_second_factor(
+        &mut self,
+        factor: SecondFactor,
+    ) -> Result<SignInStatus, ClerkSignInError> {
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_in_factor_two(
+                &self.sign_in.id,
+                Some(factor.strategy()),
+                Some(factor.code()),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to attempt sign in second factor: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    /// Sets new password when the sign in needs one, example after
+    /// verifying a reset password code
+    pub async fn reset_password(
+        &mut self,
+        password: &str,
+        sign_out_of_other_sessions: bool,
+    ) -> Result<SignInStatus, ClerkSignInError> {
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .reset_password(&self.sign_in.id, password, Some(sign_out_of_other_sessions))
+            .await
+            .map_err(|e| {
+                error!("Failed to reset password: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    /// Fetches the latest state of the sign in
+    pub async fn reload(&mut self) -> Result<SignInStatus, ClerkSignInError> {
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .get_sign_in(&self.sign_in.id)
+            .await
+            .map_err(|e| {
+                error!("Failed to get sign in: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    /// Fetches the latest state of the sign in with the `rotating_token_nonce`
+    /// received on the OAuth redirect
+    pub async fn reload_with_nonce(
+        &mut self,
+        rotating_token_nonce: &str,
+    ) -> Result<SignInStatus, ClerkSignInError> {
+        let sign_in = self
+            .clerk
+            .get_fapi_client()
+            .get_sign_in_with_nonce(&self.sign_in.id, rotating_token_nonce)
+            .await
+            .map_err(|e| {
+                error!("Failed to get sign in: {e}");
+                ClerkSignInError::ClerkApiError(e.into())
+            })?;
+        self.update(sign_in).await
+    }
+
+    async fn update(&mut self, sign_in: ClientSignIn) -> Result<SignInStatus, ClerkSignInError> {
+        self.sign_in = sign_in;
+        if self.is_complete() {
+            let session_id = self.sign_in.created_session_id.clone();
+            if session_id.is_some() && session_id != self.activated_session_id {
+                self.clerk
+                    .set_active(session_id.clone(), None)
+                    .await
+                    .map_err(ClerkSignInError::ClerkSetActiveError)?;
+                self.activated_session_id = session_id;
+            }
+        }
+        Ok(self.sign_in.status)
+    }
+}
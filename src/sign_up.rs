@@ -0,0 +1,734 @@
+use crate::clerk::{Clerk, ClerkSetActiveError};
+use crate::error::ClerkApiError;
+use crate::models::client_auth_config;
+use crate::models::client_sign_up::Status;
+use crate::models::{
+    ClientAuthConfig, ClientEnvironment, ClientSignUp, ClientSignUpVerifications,
+    StubsSignUpVerification, UserSettingsAttribute,
+};
+use crate::sign_in::Web3Strategy;
+use log::error;
+use std::error::Error;
+use std::fmt;
+
+pub use crate::models::client_sign_up::Status as SignUpStatus;
+
+/// Field that can be collected during sign up
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SignUpField {
+    EmailAddress,
+    PhoneNumber,
+    Username,
+    FirstName,
+    LastName,
+    Password,
+    Web3Wallet,
+    LegalAccepted,
+    /// Fields this crate doesn't know about, example `oauth_google`
+    Other(String),
+}
+
+impl SignUpField {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SignUpField::EmailAddress => "email_address",
+            SignUpField::PhoneNumber => "phone_number",
+            SignUpField::Username => "username",
+            SignUpField::FirstName => "first_name",
+            SignUpField::LastName => "last_name",
+            SignUpField::Password => "password",
+            SignUpField::Web3Wallet => "web3_wallet",
+            SignUpField::LegalAccepted => "legal_accepted",
+            SignUpField::Other(field) => field,
+        }
+    }
+
+    /// Parses the field name used by the API
+    pub fn parse(field: &str) -> Self {
+        match field {
+            "email_address" => SignUpField::EmailAddress,
+            "phone_number" => SignUpField::PhoneNumber,
+            "username" => SignUpField::Username,
+            "first_name" => SignUpField::FirstName,
+            "last_name" => SignUpField::LastName,
+            "password" => SignUpField::Password,
+            "web3_wallet" => SignUpField::Web3Wallet,
+            "legal_accepted" => SignUpField::LegalAccepted,
+            other => SignUpField::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SignUpField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Fields the instance requires or accepts on sign up
+///
+/// Built from the environment before a sign up is created, so that the
+/// needed fields can be collected up front. Once the sign up exists
+/// `SignUpFlow::missing_fields` is the source of truth.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignUpRequirements {
+    pub required: Vec<SignUpField>,
+    pub optional: Vec<SignUpField>,
+}
+
+impl SignUpRequirements {
+    /// Reads the requirements from `UserSettingsAttributes`, falling back to
+    /// `ClientAuthConfig` on environments without user settings
+    pub fn from_environment(environment: &ClientEnvironment) -> Self {
+        let mut requirements = Self::default();
+        if let Some(user_settings) = &environment.user_settings {
+            let attributes = &user_settings.attributes;
+            let fields: [(SignUpField, &UserSettingsAttribute); 6] = [
+                (SignUpField::EmailAddress, &attributes.email_address),
+                (SignUpField::PhoneNumber, &attributes.phone_number),
+                (SignUpField::Username, &attributes.username),
+                (SignUpField::FirstName, &attributes.first_name),
+                (SignUpField::LastName, &attributes.last_name),
+                (SignUpField::Password, &attributes.password),
+            ];
+            for (field, attribute) in fields {
+                requirements.add(field, attribute.enabled, attribute.required);
+            }
+            if user_settings.sign_up.legal_consent_enabled {
+                requirements.add(SignUpField::LegalAccepted, true, true);
+            }
+        } else if let Some(auth_config) = &environment.auth_config {
+            requirements.add_from_auth_config(auth_config);
+        }
+        requirements
+    }
+
+    fn add_from_auth_config(&mut self, auth_config: &ClientAuthConfig) {
+        use client_auth_config::{
+            EmailAddress, FirstName, LastName, Password, PhoneNumber, Username,
+        };
+        let fields = [
+            (
+                SignUpField::EmailAddress,
+                auth_config.email_address != EmailAddress::Off,
+                auth_config.email_address == EmailAddress::Required,
+            ),
+            (
+                SignUpField::PhoneNumber,
+                auth_config.phone_number != PhoneNumber::Off,
+                auth_config.phone_number == PhoneNumber::Required,
+            ),
+            (
+                SignUpField::Username,
+                auth_config.username != Username::Off,
+                auth_config.username == Username::Required,
+            ),
+            (
+                SignUpField::FirstName,
+                auth_config.first_name != FirstName::Off,
+                auth_config.first_name == FirstName::Required,
+            ),
+            (
+                SignUpField::LastName,
+                auth_config.last_name != LastName::Off,
+                auth_config.last_name == LastName::Required,
+            ),
+            (
+                SignUpField::Password,
+                auth_config.password != Password::Off,
+                auth_config.password == Password::Required,
+            ),
+        ];
+        for (field, enabled, required) in fields {
+            self.add(field, enabled, required);
+        }
+    }
+
+    fn add(&mut self, field: SignUpField, enabled: bool, required: bool) {
+        match (enabled, required) {
+            (true, true) => self.required.push(field),
+            (true, false) => self.optional.push(field),
+            _ => {}
+        }
+    }
+
+    pub fn is_required(&self, field: &SignUpField) -> bool {
+        self.required.contains(field)
+    }
+
+    pub fn is_enabled(&self, field: &SignUpField) -> bool {
+        self.required.contains(field) || self.optional.contains(field)
+    }
+
+    /// Required fields the params don't provide
+    pub fn missing(&self, params: &SignUpParams) -> Vec<SignUpField> {
+        self.required
+            .iter()
+            .filter(|field| !params.has(field))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Values to create or update the sign up with
+///
+/// Only the fields that are set are sent to the API.
+#[derive(Clone, Default, PartialEq)]
+pub struct SignUpParams {
+    pub email_address: Option<String>,
+    pub phone_number: Option<String>,
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub password: Option<String>,
+    pub web3_wallet: Option<String>,
+    pub legal_accepted: Option<bool>,
+    /// Metadata stored on the user, needs to be a JSON object
+    pub unsafe_metadata: Option<serde_json::Value>,
+    /// Ticket from an invitation, the sign up uses `ticket` strategy when set
+    pub ticket: Option<String>,
+    pub captcha_token: Option<String>,
+    /// Continues a transferable sign in as a sign up, example when an OAuth
+    /// sign in finds no existing account
+    pub transfer: Option<bool>,
+}
+
+impl SignUpParams {
+    pub fn email_address(mut self, email_address: impl Into<String>) -> Self {
+        self.email_address = Some(email_address.into());
+        self
+    }
+
+    pub fn phone_number(mut self, phone_number: impl Into<String>) -> Self {
+        self.phone_number = Some(phone_number.into());
+        self
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn first_name(mut self, first_name: impl Into<String>) -> Self {
+        self.first_name = Some(first_name.into());
+        self
+    }
+
+    pub fn last_name(mut self, last_name: impl Into<String>) -> Self {
+        self.last_name = Some(last_name.into());
+        self
+    }
+
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    pub fn web3_wallet(mut self, web3_wallet: impl Into<String>) -> Self {
+        self.web3_wallet = Some(web3_wallet.into());
+        self
+    }
+
+    pub fn legal_accepted(mut self, legal_accepted: bool) -> Self {
+        self.legal_accepted = Some(legal_accepted);
+        self
+    }
+
+    pub fn unsafe_metadata(mut self, unsafe_metadata: serde_json::Value) -> Self {
+        self.unsafe_metadata = Some(unsafe_metadata);
+        self
+    }
+
+    pub fn ticket(mut self, ticket: impl Into<String>) -> Self {
+        self.ticket = Some(ticket.into());
+        self
+    }
+
+    pub fn captcha_token(mut self, captcha_token: impl Into<String>) -> Self {
+        self.captcha_token = Some(captcha_token.into());
+        self
+    }
+
+    pub fn transfer(mut self, transfer: bool) -> Self {
+        self.transfer = Some(transfer);
+        self
+    }
+
+    /// Whether the params provide a value for the field
+    pub fn has(&self, field: &SignUpField) -> bool {
+        match field {
+            SignUpField::EmailAddress => self.email_address.is_some(),
+            SignUpField::PhoneNumber => self.phone_number.is_some(),
+            SignUpField::Username => self.username.is_some(),
+            SignUpField::FirstName => self.first_name.is_some(),
+            SignUpField::LastName => self.last_name.is_some(),
+            SignUpField::Password => self.password.is_some(),
+            SignUpField::Web3Wallet => self.web3_wallet.is_some(),
+            SignUpField::LegalAccepted => self.legal_accepted == Some(true),
+            SignUpField::Other(_) => false,
+        }
+    }
+
+    fn strategy(&self) -> Option<&'static str> {
+        self.ticket.as_ref().map(|_| "ticket")
+    }
+
+    fn unsafe_metadata_json(&self) -> Result<Option<String>, ClerkSignUpError> {
+        match &self.unsafe_metadata {
+            None => Ok(None),
+            Some(value @ serde_json::Value::Object(_)) => Ok(Some(value.to_string())),
+            Some(_) => Err(ClerkSignUpError::InvalidMetadata),
+        }
+    }
+}
+
+// Password is kept out of logs
+impl fmt::Debug for SignUpParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SignUpParams")
+            .field("email_address", &self.email_address)
+            .field("phone_number", &self.phone_number)
+            .field("username", &self.username)
+            .field("first_name", &self.first_name)
+            .field("last_name", &self.last_name)
+            .field("password", &self.password.as_ref().map(|_| "[REDACTED]"))
+            .field("web3_wallet", &self.web3_wallet)
+            .field("legal_accepted", &self.legal_accepted)
+            .field("unsafe_metadata", &self.unsafe_metadata)
+            .field("ticket", &self.ticket.as_ref().map(|_| "[REDACTED]"))
+            .field("transfer", &self.transfer)
+            .finish()
+    }
+}
+
+/// Verification to prepare, example to send the code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrepareVerification {
+    EmailCode,
+    /// Email link that redirects to the given url after verification
+    EmailLink {
+        redirect_url: String,
+    },
+    PhoneCode,
+    /// Message to sign with the web3 wallet
+    Web3(Web3Strategy),
+}
+
+impl PrepareVerification {
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            PrepareVerification::EmailCode => "email_code",
+            PrepareVerification::EmailLink { .. } => "email_link",
+            PrepareVerification::PhoneCode => "phone_code",
+            PrepareVerification::Web3(web3) => web3.as_str(),
+        }
+    }
+
+    fn field(&self) -> SignUpField {
+        match self {
+            PrepareVerification::EmailCode | PrepareVerification::EmailLink { .. } => {
+                SignUpField::EmailAddress
+            }
+            PrepareVerification::PhoneCode => SignUpField::PhoneNumber,
+            PrepareVerification::Web3(_) => SignUpField::Web3Wallet,
+        }
+    }
+}
+
+/// Verification attempt with the code or signature to verify
+#[derive(Clone, PartialEq, Eq)]
+pub enum Verification {
+    EmailCode(String),
+    PhoneCode(String),
+    /// Signature of the prepared message, made with the web3 wallet
+    Web3 {
+        strategy: Web3Strategy,
+        signature: String,
+    },
+}
+
+impl Verification {
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            Verification::EmailCode(_) => "email_code",
+            Verification::PhoneCode(_) => "phone_code",
+            Verification::Web3 { strategy, .. } => strategy.as_str(),
+        }
+    }
+
+    fn code(&self) -> Option<&str> {
+        match self {
+            Verification::EmailCode(code) | Verification::PhoneCode(code) => Some(code),
+            Verification::Web3 { .. } => None,
+        }
+    }
+
+    fn signature(&self) -> Option<&str> {
+        match self {
+            Verification::Web3 { signature, .. } => Some(signature),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Verification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Verification({})", self.strategy())
+    }
+}
+
+#[derive(Debug)]
+pub enum ClerkSignUpError {
+    ClerkApiError(ClerkApiError),
+    /// `unsafe_metadata` was not a JSON object
+    InvalidMetadata,
+    UnsupportedStrategy(String),
+    ClerkSetActiveError(ClerkSetActiveError),
+}
+impl fmt::Display for ClerkSignUpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkSignUpError::ClerkApiError(e) => e.fmt(f),
+            ClerkSignUpError::InvalidMetadata => {
+                write!(f, "Unsafe metadata needs to be a JSON object")
+            }
+            ClerkSignUpError::UnsupportedStrategy(strategy) => {
+                write!(
+                    f,
+                    "Verification {strategy} is not supported for this sign up"
+                )
+            }
+            ClerkSignUpError::ClerkSetActiveError(e) => e.fmt(f),
+        }
+    }
+}
+impl Error for ClerkSignUpError {}
+
+impl ClerkSignUpError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkSignUpError::ClerkApiError(e) => Some(e),
+            ClerkSignUpError::ClerkSetActiveError(e) => e.api_error(),
+            _ => None,
+        }
+    }
+}
+
+/// Typed sign up flow on top of the sign up endpoints
+///
+/// The flow is started with `Clerk::sign_up`. Missing fields can be provided
+/// with `update` and the identifiers verified with `prepare_verification` and
+/// `attempt_verification` until the sign up is complete. When the sign up
+/// completes the created session is set active automatically.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::sign_up::{PrepareVerification, SignUpParams, Verification};
+///
+/// let params = SignUpParams::default()
+///     .email_address("user@example.com")
+///     .unsafe_metadata(serde_json::json!({ "plan": "pro" }));
+/// let missing = clerk.sign_up_requirements()?.missing(&params);
+/// assert!(missing.is_empty(), "missing fields: {missing:?}");
+///
+/// let mut sign_up = clerk.sign_up(params).await?;
+/// sign_up.prepare_verification(PrepareVerification::EmailCode).await?;
+/// sign_up
+///     .attempt_verification(Verification::EmailCode("424242".to_string()))
+///     .await?;
+/// assert!(sign_up.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SignUpFlow {
+    clerk: Clerk,
+    sign_up: ClientSignUp,
+    /// Session we've already set active, to avoid touching it again on reload
+    activated_session_id: Option<String>,
+}
+
+impl fmt::Debug for SignUpFlow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SignUpFlow")
+            .field("id", &self.sign_up.id)
+            .field("status", &self.sign_up.status)
+            .finish()
+    }
+}
+
+impl SignUpFlow {
+    /// Creates a new sign up with the given params
+    pub async fn create(clerk: &Clerk, params: SignUpParams) -> Result<Self, ClerkSignUpError> {
+        let unsafe_metadata = params.unsafe_metadata_json()?;
+        let sign_up = clerk
+            .get_fapi_client()
+            .create_sign_ups(
+                None, // origin
+                params.transfer,
+                params.password.as_deref(),
+                params.first_name.as_deref(),
+                params.last_name.as_deref(),
+                params.username.as_deref(),
+                params.email_address.as_deref(),
+                params.phone_number.as_deref(),
+                None, // email_address_or_phone_number
+                unsafe_metadata.as_deref(),
+                params.strategy(),
+                None, // action_complete_redirect_url
+                None, // redirect_url
+                params.ticket.as_deref(),
+                params.web3_wallet.as_deref(),
+                None, // token
+                None, // code
+                params.captcha_token.as_deref(),
+                None, // captcha_error
+                None, // captcha_widget_type
+                params.legal_accepted,
+                None, // oidc_login_hint
+                None, // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to create sign up: {e}");
+                ClerkSignUpError::ClerkApiError(e.into())
+            })?;
+        Self::from_sign_up(clerk, sign_up).await
+    }
+
+    /// Continues an existing sign up, example the one in `Client::sign_up`
+    pub async fn from_sign_up(
+        clerk: &Clerk,
+        sign_up: ClientSignUp,
+    ) -> Result<Self, ClerkSignUpError> {
+        let mut flow = Self {
+            clerk: clerk.clone(),
+            sign_up: sign_up.clone(),
+            activated_session_id: None,
+        };
+        flow.set_sign_up(sign_up).await?;
+        Ok(flow)
+    }
+
+    /// The current sign up as returned by the API
+    pub fn sign_up(&self) -> &ClientSignUp {
+        &self.sign_up
+    }
+
+    pub fn id(&self) -> &str {
+        &self.sign_up.id
+    }
+
+    pub fn status(&self) -> SignUpStatus {
+        self.sign_up.status
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.sign_up.status == Status::Complete
+    }
+
+    pub fn is_missing_requirements(&self) -> bool {
+        self.sign_up.status == Status::MissingRequirements
+    }
+
+    /// Session created by the sign up, set once the sign up is complete
+    pub fn created_session_id(&self) -> Option<&str> {
+        self.sign_up.created_session_id.as_deref()
+    }
+
+    pub fn required_fields(&self) -> Vec<SignUpField> {
+        parse_fields(&self.sign_up.required_fields)
+    }
+
+    pub fn optional_fields(&self) -> Vec<SignUpField> {
+        parse_fields(&self.sign_up.optional_fields)
+    }
+
+    /// Required fields that still need a value
+    pub fn missing_fields(&self) -> Vec<SignUpField> {
+        parse_fields(&self.sign_up.missing_fields)
+    }
+
+    /// Fields that have a value but still need to be verified
+    pub fn unverified_fields(&self) -> Vec<SignUpField> {
+        parse_fields(&self.sign_up.unverified_fields)
+    }
+
+    pub fn verifications(&self) -> &ClientSignUpVerifications {
+        &self.sign_up.verifications
+    }
+
+    /// Verification of the identifier, contains the supported strategies
+    /// and whether it needs to be prepared or attempted next
+    pub fn verification(&self, field: &SignUpField) -> Option<&StubsSignUpVerification> {
+        let verifications = &self.sign_up.verifications;
+        match field {
+            SignUpField::EmailAddress => verifications.email_address.as_deref(),
+            SignUpField::PhoneNumber => verifications.phone_number.as_deref(),
+            SignUpField::Web3Wallet => verifications.web3_wallet.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Provides missing fields for the sign up
+    pub async fn update(&mut self, params: SignUpParams) -> Result<SignUpStatus, ClerkSignUpError> {
+        let unsafe_metadata = params.unsafe_metadata_json()?;
+        let sign_up = self
+            .clerk
+            .get_fapi_client()
+            .update_sign_ups(
+                &self.sign_up.id,
+                None, // origin
+                params.password.as_deref(),
+                params.first_name.as_deref(),
+                params.last_name.as_deref(),
+                params.username.as_deref(),
+                params.email_address.as_deref(),
+                params.phone_number.as_deref(),
+                None, // email_address_or_phone_number
+                unsafe_metadata.as_deref(),
+                params.strategy(),
+                None, // redirect_url
+                None, // action_complete_redirect_url
+                params.ticket.as_deref(),
+                params.web3_wallet.as_deref(),
+                None, // token
+                None, // code
+                params.legal_accepted,
+                None, // oidc_login_hint
+                None, // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to update sign up: {e}");
+                ClerkSignUpError::ClerkApiError(e.into())
+            })?;
+        self.set_sign_up(sign_up).await
+    }
+
+    /// Prepares verification of the identifier, example sends the email or
+    /// SMS code
+    pub async fn prepare_verification(
+        &mut self,
+        verification: PrepareVerification,
+    ) -> Result<SignUpStatus, ClerkSignUpError> {
+        let strategy = verification.strategy();
+        self.check_supported(&verification.field(), strategy)?;
+        let redirect_url = match &verification {
+            PrepareVerification::EmailLink { redirect_url } => Some(redirect_url.as_str()),
+            _ => None,
+        };
+
+        let sign_up = self
+            .clerk
+            .get_fapi_client()
+            .prepare_sign_ups_verification(
+                &self.sign_up.id,
+                None, // origin
+                Some(strategy),
+                redirect_url,
+                None, // action_complete_redirect_url
+                None, // oidc_login_hint
+                None, // oidc_prompt
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to prepare sign up verification: {e}");
+                ClerkSignUpError::ClerkApiError(e.into())
+            })?;
+        self.set_sign_up(sign_up).await
+    }
+
+    /// Attempts verification of the identifier, on completion the created
+    /// session is set active
+    pub async fn attempt_verification(
+        &mut self,
+        verification: Verification,
+    ) -> Result<SignUpStatus, ClerkSignUpError> {
+        let sign_up = self
+            .clerk
+            .get_fapi_client()
+            .attempt_sign_ups_verification(
+                &self.sign_up.id,
+                None, // origin
+                Some(verification.strategy()),
+                verification.code(),
+                verification.signature(),
+                None, // token
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to attempt sign up verification: {e}");
+                ClerkSignUpError::ClerkApiError(e.into())
+            })?;
+        self.set_sign_up(sign_up).await
+    }
+
+    /// Fetches the latest state of the sign up, example after the email link
+    /// has been opened
+    pub async fn reload(&mut self) -> Result<SignUpStatus, ClerkSignUpError> {
+        let sign_up = self
+            .clerk
+            .get_fapi_client()
+            .get_sign_ups(&self.sign_up.id)
+            .await
+            .map_err(|e| {
+                error!("Failed to get sign up: {e}");
+                ClerkSignUpError::ClerkApiError(e.into())
+            })?;
+        self.set_sign_up(sign_up).await
+    }
+
+    /// Fetches the latest state of the sign up with the `rotating_token_nonce`
+    /// received on the OAuth redirect
+    pub async fn reload_with_nonce(
+        &mut self,
+        rotating_token_nonce: &str,
+    ) -> Result<SignUpStatus, ClerkSignUpError> {
+        let sign_up = self
+            .clerk
+            .get_fapi_client()
+            .get_sign_ups_with_nonce(&self.sign_up.id, rotating_token_nonce)
+            .await
+            .map_err(|e| {
+                error!("Failed to get sign up: {e}");
+                ClerkSignUpError::ClerkApiError(e.into())
+            })?;
+        self.set_sign_up(sign_up).await
+    }
+
+    /// Verifications the API doesn't list are rejected before calling it,
+    /// verifications not yet created for the sign up are left to the API
+    fn check_supported(&self, field: &SignUpField, strategy: &str) -> Result<(), ClerkSignUpError> {
+        match self.verification(field) {
+            Some(v) if !v.supported_strategies.iter().any(|s| s == strategy) => {
+                Err(ClerkSignUpError::UnsupportedStrategy(strategy.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn set_sign_up(
+        &mut self,
+        sign_up: ClientSignUp,
+    ) -> Result<SignUpStatus, ClerkSignUpError> {
+        self.sign_up = sign_up;
+        if self.is_complete() {
+            let session_id = self.sign_up.created_session_id.clone();
+            if session_id.is_some() && session_id != self.activated_session_id {
+                self.clerk
+                    .set_active(session_id.clone(), None)
+                    .await
+                    .map_err(ClerkSignUpError::ClerkSetActiveError)?;
+                self.activated_session_id = session_id;
+            }
+        }
+        Ok(self.sign_up.status)
+    }
+}
+
+fn parse_fields(fields: &[String]) -> Vec<SignUpField> {
+    fields.iter().map(|f| SignUpField::parse(f)).collect()
+}
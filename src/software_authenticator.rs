@@ -0,0 +1,370 @@
+use crate::passkey::{
+    AssertionCredential, AuthenticatorFuture, PasskeyAuthenticator,
+    PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions, RegistrationCredential,
+    ES256,
+};
+use anyhow::{anyhow, bail};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use parking_lot::Mutex;
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use url::Url;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+struct Credential {
+    id: Vec<u8>,
+    rp_id: String,
+    user_handle: Vec<u8>,
+    key: SigningKey,
+    sign_count: u32,
+}
+
+/// Passkey authenticator signing with ES256 keys kept in memory
+///
+/// Creates discoverable credentials with `none` attestation. The keys are
+/// lost when the authenticator is dropped, so it's meant for tests and
+/// tooling, not for protecting real accounts.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::passkey::SoftwareAuthenticator;
+///
+/// let authenticator = SoftwareAuthenticator::new("https://example.com");
+/// clerk.register_passkey(&authenticator).await?;
+/// clerk.sign_out(None).await?;
+/// let sign_in = clerk.sign_in_with_passkey(&authenticator).await?;
+/// assert!(sign_in.is_complete());
+/// # Ok(())
+/// # }
+/// ```
+pub struct SoftwareAuthenticator {
+    origin: String,
+    credentials: Mutex<Vec<Credential>>,
+}
+
+impl fmt::Debug for SoftwareAuthenticator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SoftwareAuthenticator")
+            .field("origin", &self.origin)
+            .field("credentials", &self.credentials.lock().len())
+            .finish()
+    }
+}
+
+impl SoftwareAuthenticator {
+    /// `origin` is put to the client data, example `https://example.com`,
+    /// and its domain is the relying party when the options don't have one
+    pub fn new(origin: impl Into<String>) -> Self {
+        Self {
+            origin: origin.into(),
+            credentials: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Ids of the created credentials
+    pub fn credential_ids(&self) -> Vec<Vec<u8>> {
+        self.credentials
+            .lock()
+            .iter()
+            .map(|c| c.id.clone())
+            .collect()
+    }
+
+    fn rp_id(&self, rp_id: Option<&str>) -> anyhow::Result<String> {
+        match rp_id {
+            Some(rp_id) => Ok(rp_id.to_string()),
+            None => Url::parse(&self.origin)?
+                .host_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("Origin {} has no domain", self.origin)),
+        }
+    }
+
+    fn client_data(&self, type_: &str, challenge: &[u8]) -> Vec<u8> {
+        serde_json::json!({
+            "type": type_,
+            "challenge": URL_SAFE_NO_PAD.encode(challenge),
+            "origin": self.origin,
+            "crossOrigin": false,
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    fn register(
+        &self,
+        options: &PublicKeyCredentialCreationOptions,
+    ) -> anyhow::Result<RegistrationCredential> {
+        if !options.pub_key_cred_params.iter().any(|p| p.alg == ES256) {
+            bail!("Relying party doesn't accept ES256 credentials");
+        }
+        let rp_id = self.rp_id(options.rp.id.as_deref())?;
+        let mut credentials = self.credentials.lock();
+        let excluded = credentials
+            .iter()
+            .any(|c| c.rp_id == rp_id && options.exclude_credentials.iter().any(|e| e.id == c.id));
+        if excluded {
+            bail!("Credential is already registered for {rp_id}");
+        }
+
+        let key = SigningKey::random(&mut OsRng);
+        let mut id = vec![0u8; 16];
+        OsRng.fill_bytes(&mut id);
+
+        let point = key.verifying_key().to_encoded_point(false);
+        let (Some(x), Some(y)) = (point.x(), point.y()) else {
+            bail!("Failed to encode the public key");
+        };
+        let mut public_key = Vec::new();
+        cbor_map(&mut public_key, 5);
+        cbor_int(&mut public_key, 1); // kty
+        cbor_int(&mut public_key, 2); // EC2
+        cbor_int(&mut public_key, 3); // alg
+        cbor_int(&mut public_key, ES256);
+        cbor_int(&mut public_key, -1); // crv
+        cbor_int(&mut public_key, 1); // P-256
+        cbor_int(&mut public_key, -2); // x
+        cbor_bytes(&mut public_key, x);
+        cbor_int(&mut public_key, -3); // y
+        cbor_bytes(&mut public_key, y);
+
+        let mut authenticator_data = authenticator_data(
+            &rp_id,
+            FLAG_USER_PRESENT | FLAG_USER_VERIFIED | FLAG_ATTESTED_CREDENTIAL_DATA,
+            0,
+        );
+        authenticator_data.extend([0u8; 16]); // AAGUID
+        authenticator_data.extend((id.len() as u16).to_be_bytes());
+        authenticator_data.extend(&id);
+        authenticator_data.extend(public_key);
+
+        let mut attestation_object = Vec::new();
+        cbor_map(&mut attestation_object, 3);
+        cbor_text(&mut attestation_object, "fmt");
+        cbor_text(&mut attestation_object, "none");
+        cbor_text(&mut attestation_object, "attStmt");
+        cbor_map(&mut attestation_object, 0);
+        cbor_text(&mut attestation_object, "authData");
+        cbor_bytes(&mut attestation_object, &authenticator_data);
+
+        credentials.push(Credential {
+            id: id.clone(),
+            rp_id,
+            user_handle: options.user.id.clone(),
+            key,
+            sign_count: 0,
+        });
+        Ok(RegistrationCredential {
+            raw_id: id,
+            authenticator_attachment: Some("platform".to_string()),
+            client_data_json: self.client_data("webauthn.create", &options.challenge),
+            attestation_object,
+            transports: vec!["internal".to_string()],
+        })
+    }
+
+    fn assert(
+        &self,
+        options: &PublicKeyCredentialRequestOptions,
+    ) -> anyhow::Result<AssertionCredential> {
+        let rp_id = self.rp_id(options.rp_id.as_deref())?;
+        let mut credentials = self.credentials.lock();
+        let credential = credentials
+            .iter_mut()
+            .find(|c| {
+                c.rp_id == rp_id
+                    && (options.allow_credentials.is_empty()
+                        || options.allow_credentials.iter().any(|a| a.id == c.id))
+            })
+            .ok_or_else(|| anyhow!("No credential for {rp_id}"))?;
+        credential.sign_count += 1;
+
+        let authenticator_data = authenticator_data(
+            &rp_id,
+            FLAG_USER_PRESENT | FLAG_USER_VERIFIED,
+            credential.sign_count,
+        );
+        let client_data_json = self.client_data("webauthn.get", &options.challenge);
+        let signed = [
+            authenticator_data.as_slice(),
+            Sha256::digest(&client_data_json).as_slice(),
+        ]
+        .concat();
+        let signature: Signature = credential.key.sign(&signed);
+
+        Ok(AssertionCredential {
+            raw_id: credential.id.clone(),
+            authenticator_attachment: Some("platform".to_string()),
+            client_data_json,
+            authenticator_data,
+            signature: signature.to_der().as_bytes().to_vec(),
+            user_handle: Some(credential.user_handle.clone()),
+        })
+    }
+}
+
+impl PasskeyAuthenticator for SoftwareAuthenticator {
+    fn create<'a>(
+        &'a self,
+        options: &'a PublicKeyCredentialCreationOptions,
+    ) -> AuthenticatorFuture<'a, anyhow::Result<RegistrationCredential>> {
+        Box::pin(async move { self.register(options) })
+    }
+
+    fn get<'a>(
+        &'a self,
+        options: &'a PublicKeyCredentialRequestOptions,
+    ) -> AuthenticatorFuture<'a, anyhow::Result<AssertionCredential>> {
+        Box::pin(async move { self.assert(options) })
+    }
+}
+
+fn authenticator_data(rp_id: &str, flags: u8, sign_count: u32) -> Vec<u8> {
+    let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+    data.push(flags);
+    data.extend(sign_count.to_be_bytes());
+    data
+}
+
+// Just enough CBOR for the COSE key and the attestation object
+
+fn cbor_header(out: &mut Vec<u8>, major: u8, len: u64) {
+    let major = major << 5;
+    match len {
+        0..=23 => out.push(major | len as u8),
+        24..=0xff => out.extend([major | 24, len as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend((len as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 26);
+            out.extend((len as u32).to_be_bytes());
+        }
+    }
+}
+
+fn cbor_int(out: &mut Vec<u8>, value: i64) {
+    if value >= 0 {
+        cbor_header(out, 0, value as u64);
+    } else {
+        cbor_header(out, 1, (-1 - value) as u64);
+    }
+}
+
+fn cbor_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    cbor_header(out, 2, bytes.len() as u64);
+    out.extend(bytes);
+}
+
+fn cbor_text(out: &mut Vec<u8>, text: &str) {
+    cbor_header(out, 3, text.len() as u64);
+    out.extend(text.as_bytes());
+}
+
+fn cbor_map(out: &mut Vec<u8>, len: u64) {
+    cbor_header(out, 5, len);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::passkey::{
+        PublicKeyCredentialDescriptor, PublicKeyCredentialParameters, PublicKeyCredentialRpEntity,
+        PublicKeyCredentialUserEntity,
+    };
+    use p256::ecdsa::signature::Verifier;
+
+    fn creation_options() -> PublicKeyCredentialCreationOptions {
+        PublicKeyCredentialCreationOptions {
+            rp: PublicKeyCredentialRpEntity {
+                id: None,
+                name: "Example".to_string(),
+            },
+            user: PublicKeyCredentialUserEntity {
+                id: b"user_1".to_vec(),
+                name: "john".to_string(),
+                display_name: "John".to_string(),
+            },
+            challenge: b"register".to_vec(),
+            pub_key_cred_params: vec![PublicKeyCredentialParameters {
+                type_: "public-key".to_string(),
+                alg: ES256,
+            }],
+            timeout: None,
+            exclude_credentials: vec![],
+            authenticator_selection: None,
+            attestation: None,
+        }
+    }
+
+    #[test]
+    fn test_register_and_sign() {
+        let authenticator = SoftwareAuthenticator::new("https://example.com");
+        let registration = authenticator.register(&creation_options()).unwrap();
+        assert_eq!(
+            authenticator.credential_ids(),
+            vec![registration.raw_id.clone()]
+        );
+        let client_data: serde_json::Value =
+            serde_json::from_slice(&registration.client_data_json).unwrap();
+        assert_eq!(client_data["type"], "webauthn.create");
+        assert_eq!(client_data["challenge"], "cmVnaXN0ZXI");
+
+        // Registering the same credential again is refused
+        let mut options = creation_options();
+        options.exclude_credentials = vec![PublicKeyCredentialDescriptor {
+            type_: "public-key".to_string(),
+            id: registration.raw_id.clone(),
+            transports: vec![],
+        }];
+        assert!(authenticator.register(&options).is_err());
+
+        let request = PublicKeyCredentialRequestOptions {
+            challenge: b"sign in".to_vec(),
+            timeout: None,
+            rp_id: Some("example.com".to_string()),
+            allow_credentials: vec![],
+            user_verification: None,
+        };
+        let assertion = authenticator.assert(&request).unwrap();
+        assert_eq!(assertion.raw_id, registration.raw_id);
+        assert_eq!(assertion.user_handle.as_deref(), Some(b"user_1".as_slice()));
+        let data = &assertion.authenticator_data;
+        assert_eq!(&data[..32], Sha256::digest(b"example.com").as_slice());
+        assert_eq!(data[32], FLAG_USER_PRESENT | FLAG_USER_VERIFIED);
+        assert_eq!(&data[33..37], &1u32.to_be_bytes());
+
+        let signed = [
+            data.as_slice(),
+            Sha256::digest(&assertion.client_data_json).as_slice(),
+        ]
+        .concat();
+        let signature = Signature::from_der(&assertion.signature).unwrap();
+        let credentials = authenticator.credentials.lock();
+        assert!(credentials[0]
+            .key
+            .verifying_key()
+            .verify(&signed, &signature)
+            .is_ok());
+        drop(credentials);
+
+        let other_rp = PublicKeyCredentialRequestOptions {
+            rp_id: Some("other.com".to_string()),
+            ..request
+        };
+        assert!(authenticator.assert(&other_rp).is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use futures::lock::Mutex as AsyncMutex;
+use futures_timer::Delay;
+use log::error;
+use parking_lot::{Mutex, RwLock};
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+pub use crate::configuration::{DefaultStore, Store};
+
+#[cfg(all(feature = "file-store", not(target_arch = "wasm32")))]
+pub use crate::file_store::JsonFileStore;
+
+#[cfg(all(feature = "encrypted-store", not(target_arch = "wasm32")))]
+pub use crate::file_store::EncryptedFileStore;
+
+/// Future returned by `AsyncStore`, not required to be `Send` on wasm32 so
+/// that browser storage APIs can be used
+#[cfg(not(target_arch = "wasm32"))]
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Store backed by async storage, example a database or a keychain
+///
+/// Clerk reads and writes its state synchronously, so an `AsyncStore` is used
+/// through `AsyncStoreAdapter`.
+pub trait AsyncStore: Send + Sync + fmt::Debug {
+    /// Returns all the stored key-value pairs
+    fn load_all(&self) -> StoreFuture<'_, anyhow::Result<HashMap<String, JsonValue>>>;
+
+    /// Inserts a key-value pair into the store
+    fn set<'a>(&'a self, key: &'a str, value: JsonValue) -> StoreFuture<'a, anyhow::Result<()>>;
+
+    /// Removes the key from the store
+    fn delete<'a>(&'a self, key: &'a str) -> StoreFuture<'a, anyhow::Result<()>>;
+}
+
+/// Adapts an `AsyncStore` to the synchronous `Store` Clerk uses
+///
+/// The stored values are loaded to memory when the adapter is created.
+/// Reads are served from memory and writes are kept in memory until `flush`
+/// writes them to the async store. Use `run_flush` to flush in the
+/// background.
+///
+/// # Examples
+/// ```
+/// # async fn example(my_store: impl clerk_fapi_rs::store::AsyncStore + 'static) -> anyhow::Result<()> {
+/// use clerk_fapi_rs::clerk::Clerk;
+/// use clerk_fapi_rs::configuration::{ClerkFapiConfiguration, ClientKind};
+/// use clerk_fapi_rs::store::AsyncStoreAdapter;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let store = Arc::new(AsyncStoreAdapter::new(my_store).await?);
+/// let config = ClerkFapiConfiguration::new_with_store(
+///     "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+///     None,
+///     None,
+///     Some(store.clone()),
+///     None,
+///     ClientKind::NonBrowser,
+/// )
+/// .map_err(anyhow::Error::msg)?;
+/// let clerk = Clerk::new(config);
+///
+/// tokio::spawn({
+///     let store = store.clone();
+///     async move { store.run_flush(Duration::from_secs(1)).await }
+/// });
+/// clerk.load().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct AsyncStoreAdapter<S: AsyncStore> {
+    store: S,
+    values: RwLock<HashMap<String, JsonValue>>,
+    dirty: Mutex<HashSet<String>>,
+    flush_lock: AsyncMutex<()>,
+}
+
+impl<S: AsyncStore> AsyncStoreAdapter<S> {
+    /// Creates the adapter and loads the stored values
+    pub async fn new(store: S) -> anyhow::Result<Self> {
+        let values = store.load_all().await?;
+        Ok(Self {
+            store,
+            values: RwLock::new(values),
+            dirty: Mutex::new(HashSet::new()),
+            flush_lock: AsyncMutex::new(()),
+        })
+    }
+
+    /// The wrapped async store
+    pub fn inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Whether there are writes not yet flushed to the async store
+    pub fn has_pending_writes(&self) -> bool {
+        !self.dirty.lock().is_empty()
+    }
+
+    /// Writes the pending changes to the async store
+    ///
+    /// Keys that fail to be written are kept pending and retried on the next
+    /// flush.
+    pub async fn flush(&self) -> anyhow::Result<()> {
+        let _guard = self.flush_lock.lock().await;
+        let keys: Vec<String> = self.dirty.lock().drain().collect();
+        let mut result = Ok(());
+        for key in keys {
+            let value = self.values.read().get(&key).cloned();
+            let written = match value {
+                Some(value) => self.store.set(&key, value).await,
+                None => self.store.delete(&key).await,
+            };
+            if let Err(e) = written {
+                error!("AsyncStoreAdapter: Failed to write {key}: {e}");
+                self.dirty.lock().insert(key);
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    /// Flushes the pending changes every `interval`
+    ///
+    /// The returned future runs until it's dropped, spawn it on the runtime
+    /// of your choice.
+    pub async fn run_flush(&self, interval: Duration) {
+        loop {
+            if self.has_pending_writes() {
+                // Errors are logged and retried on the next round
+                let _ = self.flush().await;
+            }
+            Delay::new(interval).await;
+        }
+    }
+}
+
+impl<S: AsyncStore> Store for AsyncStoreAdapter<S> {
+    fn set(&self, key: &str, value: JsonValue) {
+        self.values.write().insert(key.to_string(), value);
+        self.dirty.lock().insert(key.to_string());
+    }
+
+    fn get(&self, key: &str) -> Option<JsonValue> {
+        self.values.read().get(key).cloned()
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.values.read().contains_key(key)
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        let removed = self.values.write().remove(key).is_some();
+        if removed {
+            self.dirty.lock().insert(key.to_string());
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct MemoryAsyncStore {
+        values: Mutex<HashMap<String, JsonValue>>,
+    }
+
+    impl AsyncStore for MemoryAsyncStore {
+        fn load_all(&self) -> StoreFuture<'_, anyhow::Result<HashMap<String, JsonValue>>> {
+            Box::pin(async move { Ok(self.values.lock().clone()) })
+        }
+
+        fn set<'a>(
+            &'a self,
+            key: &'a str,
+            value: JsonValue,
+        ) -> StoreFuture<'a, anyhow::Result<()>> {
+            Box::pin(async move {
+                self.values.lock().insert(key.to_string(), value);
+                Ok(())
+            })
+        }
+
+        fn delete<'a>(&'a self, key: &'a str) -> StoreFuture<'a, anyhow::Result<()>> {
+            Box::pin(async move {
+                self.values.lock().remove(key);
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn test_async_store_adapter() {
+        futures::executor::block_on(async {
+            let inner = MemoryAsyncStore::default();
+            inner
+                .values
+                .lock()
+                .insert("stored".to_string(), JsonValue::from(1));
+
+            let adapter = AsyncStoreAdapter::new(inner).await.unwrap();
+            assert_eq!(adapter.get("stored"), Some(JsonValue::from(1)));
+
+            adapter.set("client", JsonValue::from("value"));
+            assert!(adapter.delete("stored"));
+            assert!(adapter.has_pending_writes());
+            assert!(adapter.inner().values.lock().contains_key("stored"));
+
+            adapter.flush().await.unwrap();
+            assert!(!adapter.has_pending_writes());
+            let values = adapter.inner().values.lock().clone();
+            assert_eq!(values.get("client"), Some(&JsonValue::from("value")));
+            assert!(!values.contains_key("stored"));
+        });
+    }
+}
@@ -0,0 +1,203 @@
+//! `tracing` instrumentation, enabled with the `tracing` feature
+//!
+//! Every `ClerkFapiClient` method opens a span named after the method, and
+//! every request it sends opens a `clerk_fapi.request` span inside it with
+//! the endpoint, method, status, latency, session id and, for error
+//! responses, the `x-clerk-trace-id` header Clerk returns. The response is
+//! passed on untouched, the `clerk_trace_id` of the error body is in the
+//! returned `ClerkApiError`. Only the path of the URL
+//! is recorded. Query parameters, bodies and headers are never recorded as
+//! they carry the dev browser token, verification codes, passwords and the
+//! `Authorization` header.
+use reqwest::{Request, Response};
+use tracing::field::Empty;
+use tracing::Span;
+use web_time::Instant;
+
+use crate::clerk_http_client::ClerkHttpError;
+
+/// Response header with the id to give to Clerk support
+const CLERK_TRACE_ID_HEADER: &str = "x-clerk-trace-id";
+
+/// Opens the span for a request, `session_id` is used when the request
+/// doesn't name the session itself
+pub(crate) fn request_span(request: &Request, session_id: Option<&str>) -> Span {
+    let url = request.url();
+    let query_session_id = url
+        .query_pairs()
+        .find(|(key, _)| key == "_clerk_session_id")
+        .map(|(_, value)| value.into_owned());
+    tracing::info_span!(
+        "clerk_fapi.request",
+        http.request.method = %request.method(),
+        endpoint = url.path(),
+        session_id = query_session_id.as_deref().or(session_id),
+        http.response.status_code = Empty,
+        latency_ms = Empty,
+        retries = Empty,
+        clerk_trace_id = Empty,
+    )
+}
+
+/// Records the outcome of the request to its span
+pub(crate) async fn record_result(
+    span: &Span,
+    started: Instant,
+    result: Result<Response, ClerkHttpError>,
+) -> Result<Response, ClerkHttpError> {
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            // reqwest errors include the URL and with it the query
+            let _guard = span.enter();
+            tracing::warn!(error = %redacted_error(&e), "Clerk request failed");
+            return Err(e);
+        }
+    };
+    span.record("http.response.status_code", response.status().as_u16());
+    if !response.status().is_success() {
+        if let Some(trace_id) = response
+            .headers()
+            .get(CLERK_TRACE_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            span.record("clerk_trace_id", trace_id);
+        }
+    }
+    Ok(response)
+}
+
+fn redacted_error(error: &ClerkHttpError) -> String {
+    match error {
+        ClerkHttpError::Reqwest(e) => {
+            let kind = if e.is_timeout() {
+                "timeout"
+            } else if e.is_request() {
+                "request"
+            } else if e.is_body() || e.is_decode() {
+                "body"
+            } else {
+                "other"
+            };
+            format!("reqwest {kind} error")
+        }
+        ClerkHttpError::Middleware(e) => format!("middleware: {e}"),
+    }
+}
+
+/// Ids of the active session, user and organization in `ClerkState`
+#[derive(Debug, Default)]
+pub(crate) struct ActiveIds {
+    pub session_id: Option<String>,
+    pub user_id: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+/// Records the changes of the active session, user and organization
+pub(crate) fn record_state_change(previous: &ActiveIds, current: &ActiveIds) {
+    let session_id = current.session_id.as_deref();
+    let user_id = current.user_id.as_deref();
+    let organization_id = current.organization_id.as_deref();
+    if previous.session_id != current.session_id || previous.user_id != current.user_id {
+        tracing::info!(
+            previous_session_id = previous.session_id.as_deref(),
+            session_id,
+            previous_user_id = previous.user_id.as_deref(),
+            user_id,
+            "Clerk session changed"
+        );
+    }
+    if previous.organization_id != current.organization_id {
+        tracing::info!(
+            session_id,
+            previous_organization_id = previous.organization_id.as_deref(),
+            organization_id,
+            "Clerk organization changed"
+        );
+    }
+    tracing::debug!(session_id, user_id, organization_id, "Clerk client updated");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    #[tokio::test]
+    async fn test_keeps_response() {
+        let body = r#"{"errors":[],"clerk_trace_id":"trace_123"}"#;
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/v1/client")
+            .with_status(422)
+            .with_header(CLERK_TRACE_ID_HEADER, "trace_123")
+            .with_body(body)
+            .create_async()
+            .await;
+        let url = format!("{}/v1/client", server.url());
+        let response = reqwest::get(&url).await.unwrap();
+
+        let span = Span::none();
+        let response = record_result(&span, Instant::now(), Ok(response))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 422);
+        assert_eq!(response.url().as_str(), url);
+        assert_eq!(response.text().await.unwrap(), body);
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_request_span_fields() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let writer = output.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || SharedWriter(writer.clone()))
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = reqwest::Client::new()
+                .post("https://clerk.example.com/v1/client/sign_ins?_clerk_session_id=sess_1&__clerk_db_jwt=secret")
+                .header("Authorization", "secret")
+                .body("password=secret")
+                .build()
+                .unwrap();
+            let span = request_span(&request, Some("sess_2"));
+            let response: Response = http::Response::builder()
+                .status(400)
+                .header(CLERK_TRACE_ID_HEADER, "trace_123")
+                .body(r#"{"errors":[],"clerk_trace_id":"trace_123"}"#)
+                .unwrap()
+                .into();
+            futures::executor::block_on(record_result(&span, Instant::now(), Ok(response)))
+                .unwrap();
+        });
+
+        let output = String::from_utf8(output.lock().clone()).unwrap();
+        assert!(output.contains("http.request.method=POST"));
+        assert!(output.contains("endpoint=\"/v1/client/sign_ins\""));
+        assert!(output.contains("session_id=\"sess_1\""));
+        assert!(output.contains("http.response.status_code=400"));
+        assert!(output.contains("clerk_trace_id=\"trace_123\""));
+        assert!(!output.contains("secret"));
+    }
+
+    #[derive(Clone)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,2182 @@
+use crate::clerk::Clerk;
+use crate::configuration::ClerkFapiConfiguration;
+use crate::models::ClientEnvironment;
+use anyhow::{anyhow, bail};
+use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use log::{debug, error};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use parking_lot::Mutex;
+use rand_core::OsRng;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Code accepted for every email code verification, the same as Clerk test
+/// mode accepts
+pub const TEST_CODE: &str = "424242";
+
+/// User that exists in the fake Frontend API
+#[derive(Clone)]
+pub struct FakeUser {
+    email_address: String,
+    password: Option<String>,
+    username: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+}
+
+// The password is kept out of logs
+impl fmt::Debug for FakeUser {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FakeUser")
+            .field("email_address", &self.email_address)
+            .field("username", &self.username)
+            .finish()
+    }
+}
+
+impl FakeUser {
+    /// User signing in with the email address and `TEST_CODE`
+    pub fn new(email_address: impl Into<String>) -> Self {
+        Self {
+            email_address: email_address.into(),
+            password: None,
+            username: None,
+            first_name: None,
+            last_name: None,
+        }
+    }
+
+    /// Lets the user sign in with the password too
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    pub fn first_name(mut self, first_name: impl Into<String>) -> Self {
+        self.first_name = Some(first_name.into());
+        self
+    }
+
+    pub fn last_name(mut self, last_name: impl Into<String>) -> Self {
+        self.last_name = Some(last_name.into());
+        self
+    }
+}
+
+/// Organization that exists in the fake Frontend API
+#[derive(Debug, Clone)]
+pub struct FakeOrganization {
+    name: String,
+    slug: Option<String>,
+    /// Email addresses of the members with their roles
+    members: Vec<(String, String)>,
+}
+
+impl FakeOrganization {
+    /// Organization with the slug derived from the name
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            slug: None,
+            members: Vec::new(),
+        }
+    }
+
+    pub fn slug(mut self, slug: impl Into<String>) -> Self {
+        self.slug = Some(slug.into());
+        self
+    }
+
+    /// Adds the user with the email address as a member, example with the
+    /// `org:admin` or `org:member` role
+    pub fn member(mut self, email_address: impl Into<String>, role: impl Into<String>) -> Self {
+        self.members.push((email_address.into(), role.into()));
+        self
+    }
+}
+
+/// Builder of `FakeFapi`, see `FakeFapi::builder`
+#[derive(Debug, Clone)]
+pub struct FakeFapiBuilder {
+    environment: Value,
+    users: Vec<FakeUser>,
+    organizations: Vec<FakeOrganization>,
+    token_lifetime: Duration,
+}
+
+impl Default for FakeFapiBuilder {
+    fn default() -> Self {
+        Self {
+            environment: default_environment(),
+            users: Vec::new(),
+            organizations: Vec::new(),
+            token_lifetime: Duration::from_secs(60),
+        }
+    }
+}
+
+impl FakeFapiBuilder {
+    /// Environment served from `/v1/environment`, it also decides the
+    /// required sign up fields and single session mode
+    pub fn environment(mut self, environment: ClientEnvironment) -> Self {
+        match serde_json::to_value(environment) {
+            Ok(environment) => self.environment = environment,
+            Err(e) => error!("FakeFapi: Failed to serialize environment: {e}"),
+        }
+        self
+    }
+
+    pub fn user(mut self, user: FakeUser) -> Self {
+        self.users.push(user);
+        self
+    }
+
+    /// Adds the organization, its members need to be added with `user`
+    pub fn organization(mut self, organization: FakeOrganization) -> Self {
+        self.organizations.push(organization);
+        self
+    }
+
+    /// How long the session tokens are valid, 60 seconds by default like
+    /// in Clerk
+    pub fn token_lifetime(mut self, token_lifetime: Duration) -> Self {
+        self.token_lifetime = token_lifetime;
+        self
+    }
+
+    /// Starts the server on a random local port
+    pub fn start(self) -> anyhow::Result<FakeFapi> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let url = format!("http://{addr}");
+
+        // Tokens are issued by the Frontend API of the publishable key
+        let issuer = format!("https://{addr}");
+        let mut state = FakeState::new(issuer, self.environment, self.token_lifetime);
+        for user in self.users {
+            state.add_user(user)?;
+        }
+        for organization in self.organizations {
+            state.add_organization(organization)?;
+        }
+
+        let fake = FakeFapi {
+            url,
+            addr,
+            state: Arc::new(Mutex::new(state)),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        };
+        let state = fake.state.clone();
+        let shutdown = fake.shutdown.clone();
+        thread::Builder::new()
+            .name("fake-fapi".to_string())
+            .spawn(move || accept(listener, state, shutdown))?;
+        Ok(fake)
+    }
+}
+
+/// Stateful fake of the Clerk Frontend API for testing apps offline
+///
+/// Runs an HTTP server on a local port in a background thread, so it works
+/// with any async runtime. It keeps the users, organizations, clients and
+/// sessions in memory and implements the environment, client, sign in, sign
+/// up, session, token, `/v1/me` and organization endpoints. Email codes are
+/// accepted when they are `TEST_CODE`. Session tokens are ES256 signed with
+/// a key served from `/.well-known/jwks.json`, so `Clerk::verify_token`
+/// works against the fake. Like the real API every response carries a new
+/// `Authorization` header identifying the client. The server stops when the
+/// `FakeFapi` is dropped.
+///
+/// # Examples
+/// ```
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use clerk_fapi_rs::sign_in::{FirstFactor, PrepareFirstFactor};
+/// use clerk_fapi_rs::testing::{FakeFapi, FakeUser, TEST_CODE};
+///
+/// let fake = FakeFapi::builder()
+///     .user(FakeUser::new("jane@example.com"))
+///     .start()?;
+/// let clerk = fake.clerk();
+/// clerk.load().await?;
+///
+/// let mut sign_in = clerk.sign_in("jane@example.com").await?;
+/// sign_in.prepare_first_factor(PrepareFirstFactor::EmailCode).await?;
+/// sign_in
+///     .attempt_first_factor(FirstFactor::EmailCode(TEST_CODE.to_string()))
+///     .await?;
+/// assert!(clerk.user()?.is_some());
+/// # Ok(())
+/// # }
+/// ```
+pub struct FakeFapi {
+    url: String,
+    addr: SocketAddr,
+    state: Arc<Mutex<FakeState>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl fmt::Debug for FakeFapi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FakeFapi").field("url", &self.url).finish()
+    }
+}
+
+impl FakeFapi {
+    pub fn builder() -> FakeFapiBuilder {
+        FakeFapiBuilder::default()
+    }
+
+    /// Starts the fake with the default environment and no users
+    pub fn start() -> anyhow::Result<Self> {
+        Self::builder().start()
+    }
+
+    /// The environment served unless `FakeFapiBuilder::environment` is
+    /// given, email code and password sign in in single session mode with
+    /// organizations enabled
+    pub fn default_environment() -> ClientEnvironment {
+        serde_json::from_value(default_environment())
+            .expect("Default environment matches ClientEnvironment")
+    }
+
+    /// Url of the server, used as the proxy url
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Development publishable key pointing to the server
+    pub fn publishable_key(&self) -> String {
+        format!(
+            "pk_test_{}",
+            STANDARD_NO_PAD.encode(format!("{}$", self.addr))
+        )
+    }
+
+    /// Native configuration using the server as the proxy
+    pub fn configuration(&self) -> ClerkFapiConfiguration {
+        ClerkFapiConfiguration::new(self.publishable_key(), Some(self.url.clone()), None)
+            .expect("Publishable key of the fake is valid")
+    }
+
+    /// New `Clerk` with `configuration`, not loaded yet
+    pub fn clerk(&self) -> Clerk {
+        Clerk::new(self.configuration())
+    }
+
+    /// Adds a user, returns the user id
+    pub fn add_user(&self, user: FakeUser) -> anyhow::Result<String> {
+        self.state.lock().add_user(user)
+    }
+
+    /// Adds an organization, returns the organization id
+    pub fn add_organization(&self, organization: FakeOrganization) -> anyhow::Result<String> {
+        self.state.lock().add_organization(organization)
+    }
+
+    /// Id of the user with the email address
+    pub fn user_id(&self, email_address: &str) -> Option<String> {
+        self.state
+            .lock()
+            .user_by_identifier(email_address)
+            .map(|u| u.id.clone())
+    }
+
+    /// Number of active sessions across all the clients
+    pub fn active_session_count(&self) -> usize {
+        self.state
+            .lock()
+            .sessions
+            .values()
+            .filter(|s| s.status == "active")
+            .count()
+    }
+}
+
+impl Drop for FakeFapi {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wakes up the accept loop so that it sees the shutdown
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn accept(listener: TcpListener, state: Arc<Mutex<FakeState>>, shutdown: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        match stream {
+            Ok(stream) => {
+                let state = state.clone();
+                let shutdown = shutdown.clone();
+                thread::spawn(move || {
+                    if let Err(e) = serve(stream, state, shutdown) {
+                        debug!("FakeFapi: Connection closed: {e}");
+                    }
+                });
+            }
+            Err(e) => error!("FakeFapi: Failed to accept connection: {e}"),
+        }
+    }
+}
+
+/// Serves the requests of a keep-alive connection until it's closed
+fn serve(
+    stream: TcpStream,
+    state: Arc<Mutex<FakeState>>,
+    shutdown: Arc<AtomicBool>,
+) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    while let Some(request) = read_request(&mut reader)? {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        debug!("FakeFapi: {} {}", request.method, request.path);
+        let response = state.lock().handle(&request);
+        write_response(&mut writer, &response)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    form: HashMap<String, String>,
+    authorization: Option<String>,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.form
+            .get(name)
+            .or_else(|| self.query.get(name))
+            .map(String::as_str)
+            .filter(|v| !v.is_empty())
+    }
+}
+
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    body: Value,
+    authorization: Option<String>,
+}
+
+/// Reads one HTTP/1.1 request, None once the connection is closed
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid request line",
+        ));
+    };
+    let url = Url::parse(&format!("http://localhost{target}"))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut request = Request {
+        method: method.to_string(),
+        path: url.path().to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        ..Default::default()
+    };
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .parse()
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                request.authorization = Some(value.to_string());
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    request.form = url::form_urlencoded::parse(&body).into_owned().collect();
+    Ok(Some(request))
+}
+
+fn write_response(writer: &mut impl Write, response: &Response) -> io::Result<()> {
+    let body = response.body.to_string();
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Unprocessable Entity",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {reason}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n",
+        response.status,
+        body.len()
+    );
+    if let Some(authorization) = &response.authorization {
+        head.push_str(&format!("authorization: {authorization}\r\n"));
+    }
+    head.push_str("\r\n");
+    writer.write_all(head.as_bytes())?;
+    writer.write_all(body.as_bytes())?;
+    writer.flush()
+}
+
+/// Error responded in the Clerk `errors` format
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    code: &'static str,
+    message: String,
+    param_name: Option<&'static str>,
+}
+
+impl ApiError {
+    fn new(status: u16, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            param_name: None,
+        }
+    }
+
+    fn param(mut self, param_name: &'static str) -> Self {
+        self.param_name = Some(param_name);
+        self
+    }
+
+    fn not_found() -> Self {
+        Self::new(404, "resource_not_found", "Resource not found")
+    }
+
+    fn signed_out() -> Self {
+        Self::new(401, "signed_out", "You are signed out")
+    }
+
+    fn body(&self) -> Value {
+        json!({
+            "errors": [{
+                "message": self.message,
+                "long_message": self.message,
+                "code": self.code,
+                "meta": self.param_name.map(|p| json!({ "param_name": p })),
+            }],
+            "clerk_trace_id": "fake_fapi"
+        })
+    }
+}
+
+type Handled = Result<Value, ApiError>;
+
+#[derive(Debug)]
+struct UserRecord {
+    id: String,
+    email_address_id: String,
+    email_address: String,
+    password: Option<String>,
+    username: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    unsafe_metadata: Value,
+    legal_accepted_at: Option<i64>,
+    last_sign_in_at: Option<i64>,
+    created_at: i64,
+}
+
+#[derive(Debug)]
+struct OrganizationRecord {
+    id: String,
+    name: String,
+    slug: String,
+    created_at: i64,
+}
+
+#[derive(Debug)]
+struct MembershipRecord {
+    id: String,
+    organization_id: String,
+    user_id: String,
+    role: String,
+    created_at: i64,
+}
+
+#[derive(Debug)]
+struct ClientRecord {
+    id: String,
+    sign_in: Option<SignInRecord>,
+    sign_up: Option<SignUpRecord>,
+    last_active_session_id: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+#[derive(Debug)]
+struct SessionRecord {
+    id: String,
+    client_id: String,
+    user_id: String,
+    status: &'static str,
+    last_active_organization_id: Option<String>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+#[derive(Debug)]
+struct SignInRecord {
+    id: String,
+    user_id: String,
+    identifier: String,
+    status: &'static str,
+    first_factor_verification: Option<Value>,
+    created_session_id: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EmailVerification {
+    NeedsPrepare,
+    NeedsAttempt,
+    Verified,
+}
+
+#[derive(Debug)]
+struct SignUpRecord {
+    id: String,
+    email_address: Option<String>,
+    password: Option<String>,
+    username: Option<String>,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    unsafe_metadata: Value,
+    legal_accepted_at: Option<i64>,
+    email_verification: EmailVerification,
+    created_session_id: Option<String>,
+    created_user_id: Option<String>,
+    created_at: i64,
+}
+
+struct FakeState {
+    issuer: String,
+    environment: Value,
+    key: SigningKey,
+    kid: String,
+    token_lifetime: Duration,
+    users: Vec<UserRecord>,
+    organizations: Vec<OrganizationRecord>,
+    memberships: Vec<MembershipRecord>,
+    clients: HashMap<String, ClientRecord>,
+    /// Every issued `Authorization` header with the client it identifies
+    authorizations: HashMap<String, String>,
+    sessions: HashMap<String, SessionRecord>,
+}
+
+impl FakeState {
+    fn new(issuer: String, environment: Value, token_lifetime: Duration) -> Self {
+        Self {
+            issuer,
+            environment,
+            key: SigningKey::random(&mut OsRng),
+            kid: new_id("ins"),
+            token_lifetime,
+            users: Vec::new(),
+            organizations: Vec::new(),
+            memberships: Vec::new(),
+            clients: HashMap::new(),
+            authorizations: HashMap::new(),
+            sessions: HashMap::new(),
+        }
+    }
+
+    fn add_user(&mut self, user: FakeUser) -> anyhow::Result<String> {
+        if self.user_by_identifier(&user.email_address).is_some() {
+            bail!("User {} already exists", user.email_address);
+        }
+        let id = new_id("user");
+        self.users.push(UserRecord {
+            id: id.clone(),
+            email_address_id: new_id("idn"),
+            email_address: user.email_address,
+            password: user.password,
+            username: user.username,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            unsafe_metadata: json!({}),
+            legal_accepted_at: None,
+            last_sign_in_at: None,
+            created_at: now_ms(),
+        });
+        Ok(id)
+    }
+
+    fn add_organization(&mut self, organization: FakeOrganization) -> anyhow::Result<String> {
+        let slug = organization
+            .slug
+            .unwrap_or_else(|| slugify(&organization.name));
+        if self.organizations.iter().any(|o| o.slug == slug) {
+            bail!("Organization {slug} already exists");
+        }
+        let members = organization
+            .members
+            .into_iter()
+            .map(|(email_address, role)| {
+                self.user_by_identifier(&email_address)
+                    .map(|u| (u.id.clone(), role))
+                    .ok_or_else(|| anyhow!("No user {email_address} to add to {slug}"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let id = self.create_organization(organization.name, slug);
+        for (user_id, role) in members {
+            self.add_membership(&id, user_id, role);
+        }
+        Ok(id)
+    }
+
+    fn create_organization(&mut self, name: String, slug: String) -> String {
+        let id = new_id("org");
+        self.organizations.push(OrganizationRecord {
+            id: id.clone(),
+            name,
+            slug,
+            created_at: now_ms(),
+        });
+        id
+    }
+
+    fn add_membership(&mut self, organization_id: &str, user_id: String, role: String) {
+        self.memberships.push(MembershipRecord {
+            id: new_id("orgmem"),
+            organization_id: organization_id.to_string(),
+            user_id,
+            role,
+            created_at: now_ms(),
+        });
+    }
+
+    fn user_by_identifier(&self, identifier: &str) -> Option<&UserRecord> {
+        self.users.iter().find(|u| {
+            u.email_address.eq_ignore_ascii_case(identifier)
+                || u.username.as_deref() == Some(identifier)
+        })
+    }
+
+    fn user(&self, user_id: &str) -> &UserRecord {
+        self.users
+            .iter()
+            .find(|u| u.id == user_id)
+            .expect("Sessions point to existing users")
+    }
+
+    fn membership(&self, user_id: &str, organization_id: &str) -> Option<&MembershipRecord> {
+        self.memberships
+            .iter()
+            .find(|m| m.user_id == user_id && m.organization_id == organization_id)
+    }
+
+    fn setting(&self, pointer: &str) -> bool {
+        self.environment
+            .pointer(pointer)
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    //
+    // Requests
+    //
+
+    fn handle(&mut self, request: &Request) -> Response {
+        let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+        // Like the real API only the client endpoints identify the client
+        let client_id = match segments.as_slice() {
+            ["v1", "environment"] | [".well-known", ..] => None,
+            ["v1", ..] => Some(self.client_id(request.authorization.as_deref())),
+            _ => None,
+        };
+        let result = match &client_id {
+            Some(client_id) => self.route_client(client_id, request, &segments),
+            None => self.route(request, &segments),
+        };
+        let authorization = client_id.map(|client_id| {
+            if let Some(client) = self.clients.get_mut(&client_id) {
+                client.updated_at = now_ms();
+            }
+            let authorization = new_id("fake_client");
+            self.authorizations.insert(authorization.clone(), client_id);
+            authorization
+        });
+        let (status, body) = match result {
+            Ok(body) => (200, body),
+            Err(e) => (e.status, e.body()),
+        };
+        Response {
+            status,
+            body,
+            authorization,
+        }
+    }
+
+    /// Client of the `Authorization` header, a new client when the header
+    /// is missing or unknown
+    fn client_id(&mut self, authorization: Option<&str>) -> String {
+        if let Some(client_id) = authorization.and_then(|a| self.authorizations.get(a)) {
+            return client_id.clone();
+        }
+        let id = new_id("client");
+        let now = now_ms();
+        self.clients.insert(
+            id.clone(),
+            ClientRecord {
+                id: id.clone(),
+                sign_in: None,
+                sign_up: None,
+                last_active_session_id: None,
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        id
+    }
+
+    fn route(&self, request: &Request, segments: &[&str]) -> Handled {
+        match (request.method.as_str(), segments) {
+            ("GET", ["v1", "environment"]) => Ok(self.environment.clone()),
+            ("GET", [".well-known", "jwks.json"]) => Ok(self.jwks()),
+            _ => Err(ApiError::not_found()),
+        }
+    }
+
+    fn route_client(&mut self, client_id: &str, request: &Request, segments: &[&str]) -> Handled {
+        match (request.method.as_str(), segments) {
+            ("GET" | "POST" | "PUT", ["v1", "client"]) => Ok(json!({
+                "response": self.client_json(client_id),
+                "client": null
+            })),
+            ("DELETE", ["v1", "client", "sessions"]) => {
+                self.end_client_sessions(client_id);
+                Ok(json!({
+                    "response": self.client_json(client_id),
+                    "client": null
+                }))
+            }
+            ("POST", ["v1", "client", "sign_ins"]) => self.create_sign_in(client_id, request),
+            ("GET", ["v1", "client", "sign_ins", id]) => {
+                self.sign_in(client_id, id)?;
+                Ok(self.wrap_sign_in(client_id))
+            }
+            ("POST", ["v1", "client", "sign_ins", id, "prepare_first_factor"]) => {
+                self.prepare_first_factor(client_id, id, request)
+            }
+            ("POST", ["v1", "client", "sign_ins", id, "attempt_first_factor"]) => {
+                self.attempt_first_factor(client_id, id, request)
+            }
+            ("POST", ["v1", "client", "sign_ups"]) => self.create_sign_up(client_id, request),
+            ("GET", ["v1", "client", "sign_ups", id]) => {
+                self.sign_up(client_id, id)?;
+                Ok(self.wrap_sign_up(client_id))
+            }
+            ("PATCH", ["v1", "client", "sign_ups", id]) => {
+                self.update_sign_up(client_id, id, request)
+            }
+            ("POST", ["v1", "client", "sign_ups", id, "prepare_verification"]) => {
+                self.prepare_verification(client_id, id, request)
+            }
+            ("POST", ["v1", "client", "sign_ups", id, "attempt_verification"]) => {
+                self.attempt_verification(client_id, id, request)
+            }
+            ("GET", ["v1", "client", "sessions", id]) => {
+                self.session(client_id, id)?;
+                Ok(self.wrap_session(client_id, id))
+            }
+            ("POST", ["v1", "client", "sessions", id, "touch"]) => {
+                self.touch_session(client_id, id, request)
+            }
+            ("POST", ["v1", "client", "sessions", id, action @ ("end" | "remove")]) => {
+                self.session(client_id, id)?;
+                self.end_session(id, if *action == "end" { "ended" } else { "removed" });
+                Ok(self.wrap_session(client_id, id))
+            }
+            ("POST", ["v1", "client", "sessions", id, "tokens"]) => {
+                let session = self.session(client_id, id)?;
+                let organization_id = request
+                    .param("organization_id")
+                    .map(str::to_string)
+                    .or_else(|| session.last_active_organization_id.clone());
+                if let Some(organization_id) = &organization_id {
+                    self.membership(&session.user_id, organization_id)
+                        .ok_or_else(ApiError::not_found)?;
+                }
+                let jwt = self.session_token(session, organization_id.as_deref());
+                Ok(json!({ "object": "token", "jwt": jwt }))
+            }
+            ("POST", ["v1", "client", "sessions", id, "tokens", template]) => {
+                let session = self.session(client_id, id)?;
+                let now = now_secs();
+                let jwt = self.sign(&json!({
+                    "sub": session.user_id,
+                    "iss": self.issuer,
+                    "iat": now,
+                    "nbf": now,
+                    "exp": now + self.token_lifetime.as_secs() as i64,
+                    "template": template,
+                }));
+                Ok(json!({ "object": "token", "jwt": jwt }))
+            }
+            ("GET", ["v1", "me"]) => {
+                let session = self.active_session(client_id)?;
+                Ok(json!({
+                    "response": self.user_json(&session.user_id),
+                    "client": self.client_json(client_id)
+                }))
+            }
+            ("GET", ["v1", "me", "organization_memberships"]) => {
+                let user_id = self.active_session(client_id)?.user_id.clone();
+                let memberships: Vec<&MembershipRecord> = self
+                    .memberships
+                    .iter()
+                    .filter(|m| m.user_id == user_id)
+                    .collect();
+                Ok(json!({
+                    "response": self.membership_page(request, &memberships, false),
+                    "client": self.client_json(client_id)
+                }))
+            }
+            ("DELETE", ["v1", "me", "organization_memberships", id]) => {
+                self.leave_organization(client_id, id)
+            }
+            ("POST", ["v1", "organizations"]) => self.create_organization_for(client_id, request),
+            ("GET", ["v1", "organizations", id]) => {
+                self.organization_of_user(client_id, id)?;
+                Ok(json!({
+                    "response": self.organization_json(id),
+                    "client": self.client_json(client_id)
+                }))
+            }
+            ("GET", ["v1", "organizations", id, "memberships"]) => {
+                self.organization_of_user(client_id, id)?;
+                let role = request.param("role");
+                let memberships: Vec<&MembershipRecord> = self
+                    .memberships
+                    .iter()
+                    .filter(|m| m.organization_id == *id)
+                    .filter(|m| role.is_none_or(|role| m.role == role))
+                    .collect();
+                Ok(json!({
+                    "response": self.membership_page(request, &memberships, true),
+                    "client": self.client_json(client_id)
+                }))
+            }
+            _ => Err(ApiError::not_found()),
+        }
+    }
+
+    //
+    // Sign in
+    //
+
+    fn sign_in(&self, client_id: &str, sign_in_id: &str) -> Result<&SignInRecord, ApiError> {
+        self.clients[client_id]
+            .sign_in
+            .as_ref()
+            .filter(|s| s.id == sign_in_id)
+            .ok_or_else(ApiError::not_found)
+    }
+
+    fn sign_in_mut(
+        &mut self,
+        client_id: &str,
+        sign_in_id: &str,
+    ) -> Result<&mut SignInRecord, ApiError> {
+        self.clients
+            .get_mut(client_id)
+            .and_then(|c| c.sign_in.as_mut())
+            .filter(|s| s.id == sign_in_id)
+            .ok_or_else(ApiError::not_found)
+    }
+
+    fn ensure_signed_out(&self, client_id: &str) -> Result<(), ApiError> {
+        if self.setting("/auth_config/single_session_mode")
+            && self.clients[client_id].last_active_session_id.is_some()
+        {
+            return Err(ApiError::new(
+                400,
+                "session_exists",
+                "You're already signed in",
+            ));
+        }
+        Ok(())
+    }
+
+    fn create_sign_in(&mut self, client_id: &str, request: &Request) -> Handled {
+        self.ensure_signed_out(client_id)?;
+        let identifier = request.param("identifier").ok_or_else(|| {
+            ApiError::new(422, "form_param_missing", "Enter an identifier").param("identifier")
+        })?;
+        let user = self.user_by_identifier(identifier).ok_or_else(|| {
+            ApiError::new(
+                422,
+                "form_identifier_not_found",
+                "Couldn't find your account",
+            )
+            .param("identifier")
+        })?;
+        let sign_in_id = new_id("sia");
+        let sign_in = SignInRecord {
+            id: sign_in_id.clone(),
+            user_id: user.id.clone(),
+            identifier: identifier.to_string(),
+            status: "needs_first_factor",
+            first_factor_verification: None,
+            created_session_id: None,
+            created_at: now_ms(),
+        };
+        if let Some(client) = self.clients.get_mut(client_id) {
+            client.sign_in = Some(sign_in);
+        }
+        // The password can be given right away instead of as the first
+        // factor attempt
+        if let Some(password) = request.param("password") {
+            self.verify_password(client_id, &sign_in_id, password)?;
+        }
+        Ok(self.wrap_sign_in(client_id))
+    }
+
+    fn prepare_first_factor(
+        &mut self,
+        client_id: &str,
+        sign_in_id: &str,
+        request: &Request,
+    ) -> Handled {
+        let strategy = request.param("strategy").unwrap_or_default();
+        let sign_in = self.sign_in_mut(client_id, sign_in_id)?;
+        match strategy {
+            "email_code" => {
+                sign_in.first_factor_verification = Some(json!({
+                    "object": "verification_otp",
+                    "status": "unverified",
+                    "strategy": "email_code",
+                    "attempts": 0,
+                    "expire_at": now_ms() + 600_000
+                }));
+            }
+            "password" => {}
+            _ => return Err(unsupported_strategy(strategy)),
+        }
+        Ok(self.wrap_sign_in(client_id))
+    }
+
+    fn attempt_first_factor(
+        &mut self,
+        client_id: &str,
+        sign_in_id: &str,
+        request: &Request,
+    ) -> Handled {
+        let strategy = request.param("strategy").unwrap_or_default();
+        match strategy {
+            "email_code" => {
+                let sign_in = self.sign_in_mut(client_id, sign_in_id)?;
+                let prepared = sign_in
+                    .first_factor_verification
+                    .as_ref()
+                    .is_some_and(|v| v["strategy"] == "email_code");
+                if !prepared {
+                    return Err(ApiError::new(
+                        400,
+                        "verification_missing",
+                        "Prepare the email code first",
+                    ));
+                }
+                if request.param("code") != Some(TEST_CODE) {
+                    return Err(
+                        ApiError::new(422, "form_code_incorrect", "Incorrect code").param("code")
+                    );
+                }
+                sign_in.first_factor_verification = Some(json!({
+                    "object": "verification_otp",
+                    "status": "verified",
+                    "strategy": "email_code",
+                    "attempts": 1,
+                    "expire_at": now_ms() + 600_000
+                }));
+                self.complete_sign_in(client_id);
+            }
+            "password" => {
+                let password = request.param("password").unwrap_or_default().to_string();
+                self.verify_password(client_id, sign_in_id, &password)?;
+            }
+            _ => return Err(unsupported_strategy(strategy)),
+        }
+        Ok(self.wrap_sign_in(client_id))
+    }
+
+    fn verify_password(
+        &mut self,
+        client_id: &str,
+        sign_in_id: &str,
+        password: &str,
+    ) -> Result<(), ApiError> {
+        let user_id = self.sign_in(client_id, sign_in_id)?.user_id.clone();
+        if self.user(&user_id).password.as_deref() != Some(password) {
+            return Err(
+                ApiError::new(422, "form_password_incorrect", "Password is incorrect")
+                    .param("password"),
+            );
+        }
+        self.sign_in_mut(client_id, sign_in_id)?
+            .first_factor_verification = Some(json!({
+            "object": "verification_password",
+            "status": "verified",
+            "strategy": "password",
+            "attempts": 1,
+            "expire_at": null
+        }));
+        self.complete_sign_in(client_id);
+        Ok(())
+    }
+
+    fn complete_sign_in(&mut self, client_id: &str) {
+        let Some(user_id) = self.clients[client_id]
+            .sign_in
+            .as_ref()
+            .map(|s| s.user_id.clone())
+        else {
+            return;
+        };
+        let session_id = self.create_session(client_id, &user_id);
+        if let Some(sign_in) = self
+            .clients
+            .get_mut(client_id)
+            .and_then(|c| c.sign_in.as_mut())
+        {
+            sign_in.status = "complete";
+            sign_in.created_session_id = Some(session_id);
+        }
+    }
+
+    //
+    // Sign up
+    //
+
+    fn sign_up(&self, client_id: &str, sign_up_id: &str) -> Result<&SignUpRecord, ApiError> {
+        self.clients[client_id]
+            .sign_up
+            .as_ref()
+            .filter(|s| s.id == sign_up_id)
+            .ok_or_else(ApiError::not_found)
+    }
+
+    fn sign_up_mut(
+        &mut self,
+        client_id: &str,
+        sign_up_id: &str,
+    ) -> Result<&mut SignUpRecord, ApiError> {
+        self.clients
+            .get_mut(client_id)
+            .and_then(|c| c.sign_up.as_mut())
+            .filter(|s| s.id == sign_up_id)
+            .ok_or_else(ApiError::not_found)
+    }
+
+    fn create_sign_up(&mut self, client_id: &str, request: &Request) -> Handled {
+        self.ensure_signed_out(client_id)?;
+        let mut sign_up = SignUpRecord {
+            id: new_id("sua"),
+            email_address: None,
+            password: None,
+            username: None,
+            first_name: None,
+            last_name: None,
+            unsafe_metadata: json!({}),
+            legal_accepted_at: None,
+            email_verification: EmailVerification::NeedsPrepare,
+            created_session_id: None,
+            created_user_id: None,
+            created_at: now_ms(),
+        };
+        self.apply_sign_up_params(&mut sign_up, request)?;
+        if let Some(client) = self.clients.get_mut(client_id) {
+            client.sign_up = Some(sign_up);
+        }
+        self.complete_sign_up(client_id);
+        Ok(self.wrap_sign_up(client_id))
+    }
+
+    fn update_sign_up(&mut self, client_id: &str, sign_up_id: &str, request: &Request) -> Handled {
+        self.sign_up(client_id, sign_up_id)?;
+        let mut sign_up = self
+            .clients
+            .get_mut(client_id)
+            .and_then(|c| c.sign_up.take());
+        let result = match sign_up.as_mut() {
+            Some(sign_up) => self.apply_sign_up_params(sign_up, request),
+            None => Ok(()),
+        };
+        if let Some(client) = self.clients.get_mut(client_id) {
+            client.sign_up = sign_up;
+        }
+        result?;
+        self.complete_sign_up(client_id);
+        Ok(self.wrap_sign_up(client_id))
+    }
+
+    fn apply_sign_up_params(
+        &self,
+        sign_up: &mut SignUpRecord,
+        request: &Request,
+    ) -> Result<(), ApiError> {
+        if let Some(email_address) = request.param("email_address") {
+            if self.user_by_identifier(email_address).is_some() {
+                return Err(ApiError::new(
+                    422,
+                    "form_identifier_exists",
+                    "That email address is taken. Please try another.",
+                )
+                .param("email_address"));
+            }
+            if sign_up.email_address.as_deref() != Some(email_address) {
+                sign_up.email_address = Some(email_address.to_string());
+                sign_up.email_verification = EmailVerification::NeedsPrepare;
+            }
+        }
+        if let Some(username) = request.param("username") {
+            if self.user_by_identifier(username).is_some() {
+                return Err(ApiError::new(
+                    422,
+                    "form_identifier_exists",
+                    "That username is taken. Please try another.",
+                )
+                .param("username"));
+            }
+            sign_up.username = Some(username.to_string());
+        }
+        if let Some(password) = request.param("password") {
+            sign_up.password = Some(password.to_string());
+        }
+        if let Some(first_name) = request.param("first_name") {
+            sign_up.first_name = Some(first_name.to_string());
+        }
+        if let Some(last_name) = request.param("last_name") {
+            sign_up.last_name = Some(last_name.to_string());
+        }
+        if let Some(unsafe_metadata) = request.param("unsafe_metadata") {
+            sign_up.unsafe_metadata = serde_json::from_str(unsafe_metadata).map_err(|_| {
+                ApiError::new(422, "form_param_format_invalid", "Invalid metadata")
+                    .param("unsafe_metadata")
+            })?;
+        }
+        if request.param("legal_accepted") == Some("true") {
+            sign_up.legal_accepted_at = Some(now_ms());
+        }
+        Ok(())
+    }
+
+    fn prepare_verification(
+        &mut self,
+        client_id: &str,
+        sign_up_id: &str,
+        request: &Request,
+    ) -> Handled {
+        let strategy = request.param("strategy").unwrap_or_default();
+        if strategy != "email_code" {
+            return Err(unsupported_strategy(strategy));
+        }
+        let sign_up = self.sign_up_mut(client_id, sign_up_id)?;
+        if sign_up.email_address.is_none() {
+            return Err(
+                ApiError::new(422, "form_param_missing", "Enter an email address")
+                    .param("email_address"),
+            );
+        }
+        if sign_up.email_verification != EmailVerification::Verified {
+            sign_up.email_verification = EmailVerification::NeedsAttempt;
+        }
+        Ok(self.wrap_sign_up(client_id))
+    }
+
+    fn attempt_verification(
+        &mut self,
+        client_id: &str,
+        sign_up_id: &str,
+        request: &Request,
+    ) -> Handled {
+        let strategy = request.param("strategy").unwrap_or_default();
+        if strategy != "email_code" {
+            return Err(unsupported_strategy(strategy));
+        }
+        let code = request.param("code");
+        let sign_up = self.sign_up_mut(client_id, sign_up_id)?;
+        if sign_up.email_verification == EmailVerification::NeedsPrepare {
+            return Err(ApiError::new(
+                400,
+                "verification_missing",
+                "Prepare the email code first",
+            ));
+        }
+        if code != Some(TEST_CODE) {
+            return Err(ApiError::new(422, "form_code_incorrect", "Incorrect code").param("code"));
+        }
+        sign_up.email_verification = EmailVerification::Verified;
+        self.complete_sign_up(client_id);
+        Ok(self.wrap_sign_up(client_id))
+    }
+
+    /// Fields of the environment that are enabled and required for sign up
+    fn required_fields(&self) -> Vec<&'static str> {
+        let mut fields: Vec<&'static str> = [
+            "email_address",
+            "password",
+            "username",
+            "first_name",
+            "last_name",
+        ]
+        .into_iter()
+        .filter(|f| {
+            self.setting(&format!("/user_settings/attributes/{f}/enabled"))
+                && self.setting(&format!("/user_settings/attributes/{f}/required"))
+        })
+        .collect();
+        if self.setting("/user_settings/sign_up/legal_consent_enabled") {
+            fields.push("legal_accepted");
+        }
+        fields
+    }
+
+    fn missing_fields(&self, sign_up: &SignUpRecord) -> Vec<&'static str> {
+        self.required_fields()
+            .into_iter()
+            .filter(|field| match *field {
+                "email_address" => sign_up.email_address.is_none(),
+                "password" => sign_up.password.is_none(),
+                "username" => sign_up.username.is_none(),
+                "first_name" => sign_up.first_name.is_none(),
+                "last_name" => sign_up.last_name.is_none(),
+                "legal_accepted" => sign_up.legal_accepted_at.is_none(),
+                _ => false,
+            })
+            .collect()
+    }
+
+    fn unverified_fields(&self, sign_up: &SignUpRecord) -> Vec<&'static str> {
+        if sign_up.email_address.is_some()
+            && sign_up.email_verification != EmailVerification::Verified
+            && self.setting("/user_settings/attributes/email_address/verify_at_sign_up")
+        {
+            vec!["email_address"]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Creates the user and the session once nothing is missing
+    fn complete_sign_up(&mut self, client_id: &str) {
+        let Some(sign_up) = self.clients[client_id].sign_up.as_ref() else {
+            return;
+        };
+        if sign_up.created_user_id.is_some()
+            || !self.missing_fields(sign_up).is_empty()
+            || !self.unverified_fields(sign_up).is_empty()
+        {
+            return;
+        }
+        let Some(email_address) = sign_up.email_address.clone() else {
+            return;
+        };
+        let user = FakeUser {
+            email_address,
+            password: sign_up.password.clone(),
+            username: sign_up.username.clone(),
+            first_name: sign_up.first_name.clone(),
+            last_name: sign_up.last_name.clone(),
+        };
+        let unsafe_metadata = sign_up.unsafe_metadata.clone();
+        let legal_accepted_at = sign_up.legal_accepted_at;
+        let Ok(user_id) = self.add_user(user) else {
+            return;
+        };
+        if let Some(user) = self.users.iter_mut().find(|u| u.id == user_id) {
+            user.unsafe_metadata = unsafe_metadata;
+            user.legal_accepted_at = legal_accepted_at;
+        }
+        let session_id = self.create_session(client_id, &user_id);
+        if let Some(sign_up) = self
+            .clients
+            .get_mut(client_id)
+            .and_then(|c| c.sign_up.as_mut())
+        {
+            sign_up.created_user_id = Some(user_id);
+            sign_up.created_session_id = Some(session_id);
+        }
+    }
+
+    //
+    // Sessions
+    //
+
+    fn create_session(&mut self, client_id: &str, user_id: &str) -> String {
+        let id = new_id("sess");
+        let now = now_ms();
+        self.sessions.insert(
+            id.clone(),
+            SessionRecord {
+                id: id.clone(),
+                client_id: client_id.to_string(),
+                user_id: user_id.to_string(),
+                status: "active",
+                last_active_organization_id: None,
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        if let Some(user) = self.users.iter_mut().find(|u| u.id == user_id) {
+            user.last_sign_in_at = Some(now);
+        }
+        if let Some(client) = self.clients.get_mut(client_id) {
+            client.last_active_session_id = Some(id.clone());
+        }
+        id
+    }
+
+    fn session(&self, client_id: &str, session_id: &str) -> Result<&SessionRecord, ApiError> {
+        self.sessions
+            .get(session_id)
+            .filter(|s| s.client_id == client_id && s.status == "active")
+            .ok_or_else(ApiError::not_found)
+    }
+
+    fn active_session(&self, client_id: &str) -> Result<&SessionRecord, ApiError> {
+        self.clients[client_id]
+            .last_active_session_id
+            .as_deref()
+            .and_then(|id| self.session(client_id, id).ok())
+            .ok_or_else(ApiError::signed_out)
+    }
+
+    fn touch_session(&mut self, client_id: &str, session_id: &str, request: &Request) -> Handled {
+        let user_id = self.session(client_id, session_id)?.user_id.clone();
+        // An empty organization id switches to the personal account
+        let organization_id = match request.form.get("active_organization_id") {
+            Some(id) if id.is_empty() => Some(None),
+            Some(id) => {
+                self.membership(&user_id, id)
+                    .ok_or_else(ApiError::not_found)?;
+                Some(Some(id.clone()))
+            }
+            None => None,
+        };
+        if let Some(session) = self.sessions.get_mut(session_id) {
+            if let Some(organization_id) = organization_id {
+                session.last_active_organization_id = organization_id;
+            }
+            session.updated_at = now_ms();
+        }
+        if let Some(client) = self.clients.get_mut(client_id) {
+            client.last_active_session_id = Some(session_id.to_string());
+        }
+        Ok(self.wrap_session(client_id, session_id))
+    }
+
+    fn end_session(&mut self, session_id: &str, status: &'static str) {
+        let Some(session) = self.sessions.get_mut(session_id) else {
+            return;
+        };
+        session.status = status;
+        session.updated_at = now_ms();
+        let client_id = session.client_id.clone();
+        let next = self
+            .sessions
+            .values()
+            .filter(|s| s.client_id == client_id && s.status == "active")
+            .max_by_key(|s| s.updated_at)
+            .map(|s| s.id.clone());
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            if client.last_active_session_id.as_deref() == Some(session_id) {
+                client.last_active_session_id = next;
+            }
+        }
+    }
+
+    fn end_client_sessions(&mut self, client_id: &str) {
+        let session_ids: Vec<String> = self
+            .sessions
+            .values()
+            .filter(|s| s.client_id == client_id && s.status == "active")
+            .map(|s| s.id.clone())
+            .collect();
+        for session_id in session_ids {
+            self.end_session(&session_id, "removed");
+        }
+        if let Some(client) = self.clients.get_mut(client_id) {
+            client.sign_in = None;
+            client.sign_up = None;
+        }
+    }
+
+    //
+    // Organizations
+    //
+
+    fn organization_of_user(&self, client_id: &str, organization_id: &str) -> Result<(), ApiError> {
+        let user_id = &self.active_session(client_id)?.user_id;
+        self.membership(user_id, organization_id)
+            .map(|_| ())
+            .ok_or_else(ApiError::not_found)
+    }
+
+    fn leave_organization(&mut self, client_id: &str, organization_id: &str) -> Handled {
+        let user_id = self.active_session(client_id)?.user_id.clone();
+        let membership_id = self
+            .membership(&user_id, organization_id)
+            .ok_or_else(ApiError::not_found)?
+            .id
+            .clone();
+        self.memberships.retain(|m| m.id != membership_id);
+        for session in self.sessions.values_mut().filter(|s| {
+            s.user_id == user_id
+                && s.last_active_organization_id.as_deref() == Some(organization_id)
+        }) {
+            session.last_active_organization_id = None;
+        }
+        Ok(json!({
+            "response": {
+                "object": "organization_membership",
+                "id": membership_id,
+                "deleted": true
+            },
+            "client": self.client_json(client_id)
+        }))
+    }
+
+    fn create_organization_for(&mut self, client_id: &str, request: &Request) -> Handled {
+        let user_id = self.active_session(client_id)?.user_id.clone();
+        let name = request.param("name").ok_or_else(|| {
+            ApiError::new(422, "form_param_missing", "Enter a name").param("name")
+        })?;
+        let slug = request
+            .param("slug")
+            .map(str::to_string)
+            .unwrap_or_else(|| slugify(name));
+        if self.organizations.iter().any(|o| o.slug == slug) {
+            return Err(
+                ApiError::new(422, "form_identifier_exists", "That slug is taken").param("slug"),
+            );
+        }
+        let role = self
+            .environment
+            .pointer("/organization_settings/creator_role")
+            .and_then(Value::as_str)
+            .unwrap_or("org:admin")
+            .to_string();
+        let id = self.create_organization(name.to_string(), slug);
+        self.add_membership(&id, user_id, role);
+        Ok(json!({
+            "response": self.organization_json(&id),
+            "client": self.client_json(client_id)
+        }))
+    }
+
+    //
+    // Tokens
+    //
+
+    fn sign(&self, claims: &Value) -> String {
+        let header = json!({ "alg": "ES256", "kid": self.kid, "typ": "JWT" });
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature: Signature = self.key.sign(signing_input.as_bytes());
+        format!(
+            "{signing_input}.{}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    fn session_token(&self, session: &SessionRecord, organization_id: Option<&str>) -> String {
+        let now = now_secs();
+        let mut claims = json!({
+            "sid": session.id,
+            "sub": session.user_id,
+            "iss": self.issuer,
+            "iat": now,
+            "nbf": now,
+            "exp": now + self.token_lifetime.as_secs() as i64,
+            "fva": [0, -1],
+        });
+        let membership = organization_id.and_then(|id| self.membership(&session.user_id, id));
+        let organization = membership.and_then(|m| {
+            self.organizations
+                .iter()
+                .find(|o| o.id == m.organization_id)
+        });
+        if let (Some(membership), Some(organization)) = (membership, organization) {
+            claims["org_id"] = json!(organization.id);
+            claims["org_slug"] = json!(organization.slug);
+            claims["org_role"] = json!(membership.role);
+            claims["org_permissions"] = json!(role_permissions(&membership.role));
+        }
+        self.sign(&claims)
+    }
+
+    fn jwks(&self) -> Value {
+        let point = self.key.verifying_key().to_encoded_point(false);
+        json!({
+            "keys": [{
+                "kid": self.kid,
+                "alg": "ES256",
+                "use": "sig",
+                "kty": "EC",
+                "crv": "P-256",
+                "x": point.x().map(|x| URL_SAFE_NO_PAD.encode(x)),
+                "y": point.y().map(|y| URL_SAFE_NO_PAD.encode(y)),
+            }]
+        })
+    }
+
+    //
+    // Responses
+    //
+
+    fn wrap_sign_in(&self, client_id: &str) -> Value {
+        json!({
+            "response": self.clients[client_id].sign_in.as_ref().map(|s| self.sign_in_json(s)),
+            "client": self.client_json(client_id)
+        })
+    }
+
+    fn wrap_sign_up(&self, client_id: &str) -> Value {
+        json!({
+            "response": self.clients[client_id].sign_up.as_ref().map(|s| self.sign_up_json(s)),
+            "client": self.client_json(client_id)
+        })
+    }
+
+    fn wrap_session(&self, client_id: &str, session_id: &str) -> Value {
+        json!({
+            "response": self.session_json(&self.sessions[session_id]),
+            "client": self.client_json(client_id)
+        })
+    }
+
+    fn client_json(&self, client_id: &str) -> Value {
+        let client = &self.clients[client_id];
+        let mut sessions: Vec<&SessionRecord> = self
+            .sessions
+            .values()
+            .filter(|s| s.client_id == client.id && s.status == "active")
+            .collect();
+        sessions.sort_by_key(|s| s.created_at);
+        json!({
+            "object": "client",
+            "id": client.id,
+            "sign_in": client.sign_in.as_ref().map(|s| self.sign_in_json(s)),
+            "sign_up": client.sign_up.as_ref().map(|s| self.sign_up_json(s)),
+            "sessions": sessions.into_iter().map(|s| self.session_json(s)).collect::<Vec<_>>(),
+            "last_active_session_id": client.last_active_session_id,
+            "last_authentication_strategy": null,
+            "cookie_expires_at": null,
+            "captcha_bypass": false,
+            "created_at": client.created_at,
+            "updated_at": client.updated_at
+        })
+    }
+
+    fn session_json(&self, session: &SessionRecord) -> Value {
+        let user = self.user(&session.user_id);
+        json!({
+            "object": "session",
+            "id": session.id,
+            "status": session.status,
+            "expire_at": session.updated_at + 7 * 24 * 3_600_000,
+            "abandon_at": session.created_at + 30 * 24 * 3_600_000,
+            "last_active_at": session.updated_at,
+            "last_active_organization_id": session.last_active_organization_id,
+            "actor": null,
+            "user": self.user_json(&session.user_id),
+            "public_user_data": public_user_data(user),
+            "factor_verification_age": [0, -1],
+            "created_at": session.created_at,
+            "updated_at": session.updated_at,
+            "last_active_token": {
+                "object": "token",
+                "jwt": self.session_token(session, session.last_active_organization_id.as_deref())
+            }
+        })
+    }
+
+    fn user_json(&self, user_id: &str) -> Value {
+        let user = self.user(user_id);
+        let memberships: Vec<Value> = self
+            .memberships
+            .iter()
+            .filter(|m| m.user_id == user_id)
+            .map(|m| self.membership_json(m, false))
+            .collect();
+        json!({
+            "id": user.id,
+            "object": "user",
+            "username": user.username,
+            "first_name": user.first_name,
+            "last_name": user.last_name,
+            "image_url": "",
+            "has_image": false,
+            "primary_email_address_id": user.email_address_id,
+            "primary_phone_number_id": null,
+            "primary_web3_wallet_id": null,
+            "password_enabled": user.password.is_some(),
+            "two_factor_enabled": false,
+            "totp_enabled": false,
+            "backup_code_enabled": false,
+            "email_addresses": [{
+                "id": user.email_address_id,
+                "object": "email_address",
+                "email_address": user.email_address,
+                "reserved": false,
+                "verification": {
+                    "object": "verification_otp",
+                    "status": "verified",
+                    "strategy": "email_code",
+                    "attempts": 1,
+                    "expire_at": user.created_at
+                },
+                "linked_to": [],
+                "created_at": user.created_at,
+                "updated_at": user.created_at
+            }],
+            "phone_numbers": [],
+            "web3_wallets": [],
+            "passkeys": [],
+            "organization_memberships": memberships,
+            "external_accounts": [],
+            "saml_accounts": [],
+            "public_metadata": {},
+            "unsafe_metadata": user.unsafe_metadata,
+            "external_id": null,
+            "last_sign_in_at": user.last_sign_in_at,
+            "banned": false,
+            "locked": false,
+            "lockout_expires_in_seconds": null,
+            "verification_attempts_remaining": 100,
+            "created_at": user.created_at,
+            "updated_at": user.created_at,
+            "delete_self_enabled": self.setting("/user_settings/actions/delete_self"),
+            "create_organization_enabled": self.setting("/organization_settings/enabled"),
+            "last_active_at": user.last_sign_in_at,
+            "mfa_enabled_at": null,
+            "mfa_disabled_at": null,
+            "legal_accepted_at": user.legal_accepted_at,
+            "profile_image_url": ""
+        })
+    }
+
+    fn organization_json(&self, organization_id: &str) -> Value {
+        let Some(organization) = self.organizations.iter().find(|o| o.id == organization_id) else {
+            return Value::Null;
+        };
+        let members_count = self
+            .memberships
+            .iter()
+            .filter(|m| m.organization_id == organization_id)
+            .count();
+        json!({
+            "object": "organization",
+            "id": organization.id,
+            "name": organization.name,
+            "slug": organization.slug,
+            "image_url": "",
+            "has_image": false,
+            "members_count": members_count,
+            "pending_invitations_count": 0,
+            "max_allowed_memberships": self
+                .environment
+                .pointer("/organization_settings/max_allowed_memberships")
+                .and_then(Value::as_i64)
+                .unwrap_or(5),
+            "admin_delete_enabled": self.setting("/organization_settings/actions/admin_delete"),
+            "public_metadata": {},
+            "created_at": organization.created_at,
+            "updated_at": organization.created_at,
+            "logo_url": null
+        })
+    }
+
+    fn membership_json(&self, membership: &MembershipRecord, with_user: bool) -> Value {
+        let mut json = json!({
+            "object": "organization_membership",
+            "id": membership.id,
+            "public_metadata": {},
+            "role": membership.role,
+            "role_name": role_name(&membership.role),
+            "permissions": role_permissions(&membership.role),
+            "created_at": membership.created_at,
+            "updated_at": membership.created_at,
+            "organization": self.organization_json(&membership.organization_id)
+        });
+        if with_user {
+            json["public_user_data"] = public_user_data(self.user(&membership.user_id));
+        }
+        json
+    }
+
+    /// Page of the memberships with the `limit` and `offset` of the
+    /// request, a plain list unless `paginated` is set
+    fn membership_page(
+        &self,
+        request: &Request,
+        memberships: &[&MembershipRecord],
+        with_user: bool,
+    ) -> Value {
+        let offset = request
+            .param("offset")
+            .and_then(|o| o.parse().ok())
+            .unwrap_or(0);
+        let limit = request
+            .param("limit")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(10);
+        let data: Vec<Value> = memberships
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|m| self.membership_json(m, with_user))
+            .collect();
+        if request.param("paginated") == Some("true") {
+            json!({ "data": data, "total_count": memberships.len() })
+        } else {
+            json!(data)
+        }
+    }
+
+    fn sign_in_json(&self, sign_in: &SignInRecord) -> Value {
+        let user = self.user(&sign_in.user_id);
+        let mut first_factors = vec![json!({
+            "strategy": "email_code",
+            "safe_identifier": user.email_address,
+            "email_address_id": user.email_address_id,
+            "primary": true
+        })];
+        if user.password.is_some() {
+            first_factors.push(json!({ "strategy": "password" }));
+        }
+        json!({
+            "object": "sign_in_attempt",
+            "id": sign_in.id,
+            "status": sign_in.status,
+            "supported_identifiers": ["email_address", "username"],
+            "supported_first_factors": first_factors,
+            "supported_second_factors": null,
+            "first_factor_verification": sign_in.first_factor_verification,
+            "second_factor_verification": null,
+            "identifier": sign_in.identifier,
+            "user_data": {
+                "first_name": user.first_name,
+                "last_name": user.last_name,
+                "image_url": "",
+                "has_image": false
+            },
+            "created_session_id": sign_in.created_session_id,
+            "abandon_at": sign_in.created_at + 24 * 3_600_000
+        })
+    }
+
+    fn sign_up_json(&self, sign_up: &SignUpRecord) -> Value {
+        let missing_fields = self.missing_fields(sign_up);
+        let unverified_fields = self.unverified_fields(sign_up);
+        let complete = sign_up.created_session_id.is_some();
+        let required_fields = self.required_fields();
+        let optional_fields: Vec<&str> = [
+            "email_address",
+            "password",
+            "username",
+            "first_name",
+            "last_name",
+        ]
+        .into_iter()
+        .filter(|f| {
+            self.setting(&format!("/user_settings/attributes/{f}/enabled"))
+                && !required_fields.contains(f)
+        })
+        .collect();
+        let email_verification = sign_up.email_address.as_ref().map(|_| {
+            let (status, next_action) = match sign_up.email_verification {
+                EmailVerification::NeedsPrepare => ("unverified", "needs_prepare"),
+                EmailVerification::NeedsAttempt => ("unverified", "needs_attempt"),
+                EmailVerification::Verified => ("verified", ""),
+            };
+            json!({
+                "status": status,
+                "strategy": "email_code",
+                "next_action": next_action,
+                "supported_strategies": ["email_code"]
+            })
+        });
+        json!({
+            "object": "sign_up_attempt",
+            "id": sign_up.id,
+            "status": if complete {
+                "complete"
+            } else {
+                "missing_requirements"
+            },
+            "required_fields": required_fields,
+            "optional_fields": optional_fields,
+            "missing_fields": missing_fields,
+            "unverified_fields": unverified_fields,
+            "verifications": {
+                "email_address": email_verification,
+                "phone_number": null,
+                "web3_wallet": null,
+                "external_account": null
+            },
+            "username": sign_up.username,
+            "email_address": sign_up.email_address,
+            "phone_number": null,
+            "web3_wallet": null,
+            "password_enabled": sign_up.password.is_some(),
+            "first_name": sign_up.first_name,
+            "last_name": sign_up.last_name,
+            "unsafe_metadata": sign_up.unsafe_metadata,
+            "custom_action": false,
+            "external_id": null,
+            "created_session_id": sign_up.created_session_id,
+            "created_user_id": sign_up.created_user_id,
+            "abandon_at": sign_up.created_at + 24 * 3_600_000,
+            "legal_accepted_at": sign_up.legal_accepted_at
+        })
+    }
+}
+
+fn unsupported_strategy(strategy: &str) -> ApiError {
+    ApiError::new(
+        422,
+        "strategy_for_user_invalid",
+        format!("The fake doesn't support the {strategy} strategy"),
+    )
+    .param("strategy")
+}
+
+fn public_user_data(user: &UserRecord) -> Value {
+    json!({
+        "first_name": user.first_name,
+        "last_name": user.last_name,
+        "image_url": "",
+        "has_image": false,
+        "identifier": user.email_address,
+        "user_id": user.id
+    })
+}
+
+fn role_name(role: &str) -> String {
+    match role {
+        "org:admin" => "Admin".to_string(),
+        "org:member" => "Member".to_string(),
+        role => role.trim_start_matches("org:").to_string(),
+    }
+}
+
+/// System permissions of the default roles
+fn role_permissions(role: &str) -> Vec<&'static str> {
+    match role {
+        "org:admin" => vec![
+            "org:sys_profile:manage",
+            "org:sys_profile:delete",
+            "org:sys_memberships:read",
+            "org:sys_memberships:manage",
+            "org:sys_domains:read",
+            "org:sys_domains:manage",
+        ],
+        "org:member" => vec!["org:sys_memberships:read"],
+        _ => vec![],
+    }
+}
+
+/// Lowercase slug of the name with the words separated by dashes
+fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn new_id(prefix: &str) -> String {
+    format!("{prefix}_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+fn now_secs() -> i64 {
+    now_ms() / 1000
+}
+
+fn attribute(
+    enabled: bool,
+    required: bool,
+    first_factors: &[&str],
+    verifications: &[&str],
+) -> Value {
+    json!({
+        "enabled": enabled,
+        "required": required,
+        "used_for_first_factor": !first_factors.is_empty(),
+        "first_factors": first_factors,
+        "used_for_second_factor": false,
+        "second_factors": [],
+        "verifications": verifications,
+        "verify_at_sign_up": !verifications.is_empty()
+    })
+}
+
+fn default_environment() -> Value {
+    json!({
+        "auth_config": {
+            "object": "auth_config",
+            "id": "aac_fake",
+            "first_name": "on",
+            "last_name": "on",
+            "email_address": "on",
+            "phone_number": "off",
+            "username": "on",
+            "password": "required",
+            "identification_requirements": [["email_address"], ["username"]],
+            "identification_strategies": ["email_address", "username"],
+            "first_factors": ["email_code", "password"],
+            "second_factors": [],
+            "email_address_verification_strategies": ["email_code"],
+            "single_session_mode": true,
+            "enhanced_email_deliverability": false,
+            "test_mode": true,
+            "cookieless_dev": false,
+            "url_based_session_syncing": false,
+            "claimed_at": 0,
+            "reverification": false
+        },
+        "display_config": {
+            "object": "display_config",
+            "id": "display_config_fake",
+            "instance_environment_type": "development",
+            "application_name": "Fake",
+            "theme": {},
+            "preferred_sign_in_strategy": "otp",
+            "logo_image_url": "",
+            "favicon_image_url": "",
+            "home_url": "",
+            "sign_in_url": "",
+            "sign_up_url": "",
+            "user_profile_url": "",
+            "waitlist_url": "",
+            "after_sign_in_url": "",
+            "after_sign_up_url": "",
+            "after_sign_out_one_url": "",
+            "after_sign_out_all_url": "",
+            "after_switch_session_url": "",
+            "after_join_waitlist_url": "",
+            "organization_profile_url": "",
+            "create_organization_url": "",
+            "after_leave_organization_url": "",
+            "after_create_organization_url": "",
+            "logo_link_url": "",
+            "support_email": "",
+            "branded": false,
+            "experimental_force_oauth_first": false,
+            "clerk_js_version": "5",
+            "show_devmode_warning": false,
+            "google_one_tap_client_id": "",
+            "help_url": null,
+            "privacy_policy_url": "",
+            "terms_url": "",
+            "logo_url": "",
+            "favicon_url": "",
+            "logo_image": null,
+            "favicon_image": null,
+            "captcha_public_key": null,
+            "captcha_widget_type": null,
+            "captcha_public_key_invisible": null,
+            "captcha_provider": null,
+            "captcha_oauth_bypass": []
+        },
+        "user_settings": {
+            "attributes": {
+                "email_address": attribute(true, true, &["email_code"], &["email_code"]),
+                "phone_number": attribute(false, false, &[], &[]),
+                "username": attribute(true, false, &[], &[]),
+                "web3_wallet": attribute(false, false, &[], &[]),
+                "first_name": attribute(true, false, &[], &[]),
+                "last_name": attribute(true, false, &[], &[]),
+                "password": attribute(true, true, &[], &[]),
+                "authenticator_app": attribute(false, false, &[], &[]),
+                "ticket": attribute(false, false, &[], &[]),
+                "backup_code": attribute(false, false, &[], &[]),
+                "passkey": attribute(false, false, &[], &[])
+            },
+            "sign_in": {
+                "second_factor": { "required": false }
+            },
+            "sign_up": {
+                "captcha_enabled": false,
+                "captcha_widget_type": "invisible",
+                "custom_action_required": false,
+                "progressive": true,
+                "mode": "public",
+                "legal_consent_enabled": false
+            },
+            "restrictions": {
+                "allowlist": { "enabled": false },
+                "blocklist": { "enabled": false },
+                "allowlist_blocklist_disabled_on_sign_in": { "enabled": false },
+                "block_email_subaddresses": { "enabled": false },
+                "block_disposable_email_domains": { "enabled": false }
+            },
+            "username_settings": {
+                "min_length": 4,
+                "max_length": 64,
+                "allow_extended_special_characters": false
+            },
+            "actions": {
+                "delete_self": true,
+                "create_organization": true,
+                "create_organizations_limit": null
+            },
+            "attack_protection": {
+                "user_lockout": {
+                    "enabled": false,
+                    "max_attempts": 100,
+                    "duration_in_minutes": 60
+                },
+                "pii": { "enabled": true },
+                "email_link": { "require_same_client": false },
+                "enumeration_protection": { "enabled": false }
+            },
+            "passkey_settings": {
+                "allow_autofill": false,
+                "show_sign_in_button": false
+            },
+            "social": {},
+            "password_settings": {
+                "disable_hibp": true,
+                "min_length": 8,
+                "max_length": 72,
+                "require_special_char": false,
+                "require_numbers": false,
+                "require_uppercase": false,
+                "require_lowercase": false,
+                "show_zxcvbn": false,
+                "min_zxcvbn_strength": 0,
+                "enforce_hibp_on_sign_in": false,
+                "allowed_special_characters": "!\"#$%&'()*+,-./:;<=>?@[]^_`{|}~"
+            },
+            "saml": { "enabled": false },
+            "enterprise_sso": { "enabled": false }
+        },
+        "organization_settings": {
+            "enabled": true,
+            "max_allowed_memberships": 5,
+            "actions": { "admin_delete": true },
+            "domains": {
+                "enabled": false,
+                "enrollment_modes": [],
+                "default_role": "org:member"
+            },
+            "slug": { "disabled": false },
+            "creator_role": "org:admin"
+        },
+        "fraud_settings": {
+            "object": "fraud_settings",
+            "native": { "device_attestation_mode": "disabled" }
+        },
+        "commerce_settings": {
+            "billing": {
+                "enabled": false,
+                "has_paid_user_plans": false,
+                "has_paid_org_plans": false,
+                "user": { "enabled": false, "has_paid_plans": false },
+                "organization": { "enabled": false, "has_paid_plans": false }
+            }
+        },
+        "api_keys_settings": { "enabled": false },
+        "maintenance_mode": false,
+        "client_debug_mode": false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /v1/client/sign_ins?_is_native=1 HTTP/1.1\r\n\
+                   Authorization: fake_client_abc\r\n\
+                   Content-Type: application/x-www-form-urlencoded\r\n\
+                   Content-Length: 40\r\n\r\n\
+                   identifier=jane%40example.com&password=x\
+                   GET /v1/environment HTTP/1.1\r\n\r\n";
+        let mut reader = BufReader::new(raw.as_bytes());
+
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/client/sign_ins");
+        assert_eq!(request.authorization.as_deref(), Some("fake_client_abc"));
+        assert_eq!(request.param("identifier"), Some("jane@example.com"));
+        assert_eq!(request.param("password"), Some("x"));
+        assert_eq!(request.param("_is_native"), Some("1"));
+
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(request.path, "/v1/environment");
+        assert!(read_request(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Example Corp"), "example-corp");
+        assert_eq!(slugify("  ACME, Inc. "), "acme-inc");
+    }
+
+    #[test]
+    fn test_default_environment() {
+        let environment = FakeFapi::default_environment();
+        assert!(environment.auth_config.unwrap().single_session_mode);
+    }
+
+    #[test]
+    fn test_organization_members_must_exist() {
+        let result = FakeFapi::builder()
+            .organization(FakeOrganization::new("Acme").member("nobody@example.com", "org:admin"))
+            .start();
+        assert!(result.is_err());
+    }
+}
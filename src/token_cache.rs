@@ -0,0 +1,124 @@
+use crate::jwt::{now_seconds, unverified_expiry};
+use futures::lock::Mutex as AsyncMutex;
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Tokens are reused until they are this many seconds from expiring
+pub(crate) const TOKEN_EXPIRY_LEEWAY: i64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct TokenCacheKey {
+    pub session_id: String,
+    pub organization_id: Option<String>,
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    jwt: String,
+    expires_at: i64,
+}
+
+/// Session tokens cached per session, organization and template
+///
+/// Each key has its own async lock, callers asking for the same token hold
+/// the lock while fetching so that concurrent calls end up doing a single
+/// request and the rest pick the token from the cache.
+#[derive(Debug, Default)]
+pub(crate) struct TokenCache {
+    tokens: RwLock<HashMap<TokenCacheKey, CachedToken>>,
+    locks: Mutex<HashMap<TokenCacheKey, Arc<AsyncMutex<()>>>>,
+}
+
+impl TokenCache {
+    /// Returns the cached token if it's valid for at least `min_validity`
+    /// seconds
+    pub fn get(&self, key: &TokenCacheKey, min_validity: i64) -> Option<String> {
+        let tokens = self.tokens.read();
+        tokens
+            .get(key)
+            .filter(|t| t.expires_at - min_validity > now_seconds())
+            .map(|t| t.jwt.clone())
+    }
+
+    /// Caches the token, tokens without `exp` claim are not cached
+    pub fn set(&self, key: TokenCacheKey, jwt: String) {
+        if let Some(expires_at) = unverified_expiry(&jwt) {
+            self.tokens
+                .write()
+                .insert(key, CachedToken { jwt, expires_at });
+        }
+    }
+
+    pub fn lock(&self, key: &TokenCacheKey) -> Arc<AsyncMutex<()>> {
+        self.locks.lock().entry(key.clone()).or_default().clone()
+    }
+
+    pub fn remove_session(&self, session_id: &str) {
+        self.tokens
+            .write()
+            .retain(|k, _| k.session_id != session_id);
+        // Locks held by a fetch in flight are kept so callers waiting on
+        // them still share the fetch
+        self.locks
+            .lock()
+            .retain(|k, l| k.session_id != session_id || Arc::strong_count(l) > 1);
+    }
+
+    pub fn clear(&self) {
+        self.tokens.write().clear();
+        self.locks.lock().retain(|_, l| Arc::strong_count(l) > 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+    fn jwt(exp: i64) -> String {
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::json!({ "exp": exp }).to_string());
+        format!("eyJhbGciOiJSUzI1NiJ9.{payload}.signature")
+    }
+
+    fn key(session_id: &str) -> TokenCacheKey {
+        TokenCacheKey {
+            session_id: session_id.to_string(),
+            organization_id: None,
+            template: None,
+        }
+    }
+
+    #[test]
+    fn test_token_cache_expiry() {
+        let cache = TokenCache::default();
+        let fresh = jwt(now_seconds() + 60);
+        cache.set(key("sess_1"), fresh.clone());
+        cache.set(key("sess_2"), jwt(now_seconds() + 5));
+        cache.set(key("sess_3"), "not.a.jwt".to_string());
+
+        assert_eq!(cache.get(&key("sess_1"), TOKEN_EXPIRY_LEEWAY), Some(fresh));
+        assert_eq!(cache.get(&key("sess_1"), 120), None);
+        assert_eq!(cache.get(&key("sess_2"), TOKEN_EXPIRY_LEEWAY), None);
+        assert_eq!(cache.get(&key("sess_3"), TOKEN_EXPIRY_LEEWAY), None);
+
+        cache.remove_session("sess_1");
+        assert_eq!(cache.get(&key("sess_1"), TOKEN_EXPIRY_LEEWAY), None);
+    }
+
+    #[test]
+    fn test_token_cache_prunes_locks() {
+        let cache = TokenCache::default();
+        drop(cache.lock(&key("sess_1")));
+        drop(cache.lock(&key("sess_2")));
+        let held = cache.lock(&key("sess_3"));
+
+        cache.remove_session("sess_1");
+        assert_eq!(cache.locks.lock().len(), 2);
+
+        cache.clear();
+        assert_eq!(cache.locks.lock().len(), 1);
+        assert!(Arc::ptr_eq(&held, &cache.lock(&key("sess_3"))));
+    }
+}
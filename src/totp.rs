@@ -0,0 +1,425 @@
+use crate::clerk::Clerk;
+use crate::error::ClerkApiError;
+use crate::models::Totp;
+use log::error;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use std::error::Error;
+use std::fmt;
+use url::Url;
+
+#[cfg(feature = "totp-codes")]
+use hmac::digest::{core_api::BlockSizeUser, Digest};
+#[cfg(feature = "totp-codes")]
+use hmac::{Mac, SimpleHmac};
+#[cfg(feature = "totp-codes")]
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// HMAC algorithm the TOTP codes are generated with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TotpAlgorithm::Sha1 => "SHA1",
+            TotpAlgorithm::Sha256 => "SHA256",
+            TotpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    fn parse(algorithm: &str) -> Option<Self> {
+        match algorithm.to_ascii_uppercase().as_str() {
+            "SHA1" => Some(TotpAlgorithm::Sha1),
+            "SHA256" => Some(TotpAlgorithm::Sha256),
+            "SHA512" => Some(TotpAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// The `otpauth://totp/` URI authenticator apps are enrolled with
+///
+/// Parsed from `Totp::uri`, and rendered back with `to_string` example for
+/// a QR code.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TotpUri {
+    pub issuer: Option<String>,
+    pub account_name: String,
+    /// Base32 encoded shared secret
+    pub secret: String,
+    pub algorithm: TotpAlgorithm,
+    pub digits: u32,
+    /// Seconds each code is valid for
+    pub period: u64,
+}
+
+// Secret is kept out of logs
+impl fmt::Debug for TotpUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TotpUri")
+            .field("issuer", &self.issuer)
+            .field("account_name", &self.account_name)
+            .field("secret", &"[REDACTED]")
+            .field("algorithm", &self.algorithm)
+            .field("digits", &self.digits)
+            .field("period", &self.period)
+            .finish()
+    }
+}
+
+impl fmt::Display for TotpUri {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let account_name = utf8_percent_encode(&self.account_name, NON_ALPHANUMERIC);
+        write!(f, "otpauth://totp/")?;
+        if let Some(issuer) = &self.issuer {
+            write!(f, "{}:", utf8_percent_encode(issuer, NON_ALPHANUMERIC))?;
+        }
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("secret", &self.secret);
+        if let Some(issuer) = &self.issuer {
+            query.append_pair("issuer", issuer);
+        }
+        query
+            .append_pair("algorithm", self.algorithm.as_str())
+            .append_pair("digits", &self.digits.to_string())
+            .append_pair("period", &self.period.to_string());
+        write!(f, "{account_name}?{}", query.finish())
+    }
+}
+
+impl TotpUri {
+    /// Parses the URI, the issuer is read from the `issuer` parameter or
+    /// the label prefix
+    pub fn parse(uri: &str) -> Result<Self, ClerkTotpError> {
+        let invalid = |reason: &str| ClerkTotpError::InvalidUri(reason.to_string());
+        let url = Url::parse(uri).map_err(|_| invalid("not a URL"))?;
+        if url.scheme() != "otpauth" || url.host_str() != Some("totp") {
+            return Err(invalid("not an otpauth://totp/ URI"));
+        }
+        let label = percent_decode_str(url.path().trim_start_matches('/'))
+            .decode_utf8()
+            .map_err(|_| invalid("label is not UTF-8"))?;
+        let (mut issuer, account_name) = match label.split_once(':') {
+            Some((issuer, account_name)) => (Some(issuer.trim().to_string()), account_name),
+            None => (None, label.as_ref()),
+        };
+
+        let mut totp = Self::from_secret("");
+        totp.account_name = account_name.trim().to_string();
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "secret" => totp.secret = value.into_owned(),
+                "issuer" => issuer = Some(value.into_owned()),
+                "algorithm" => {
+                    totp.algorithm =
+                        TotpAlgorithm::parse(&value).ok_or_else(|| invalid("unknown algorithm"))?
+                }
+                "digits" => {
+                    totp.digits = value
+                        .parse()
+                        .ok()
+                        .filter(|d| (6..=8).contains(d))
+                        .ok_or_else(|| invalid("digits must be 6 to 8"))?
+                }
+                "period" => {
+                    totp.period = value
+                        .parse()
+                        .ok()
+                        .filter(|p| *p > 0)
+                        .ok_or_else(|| invalid("invalid period"))?
+                }
+                _ => {}
+            }
+        }
+        if totp.secret.is_empty() {
+            return Err(invalid("missing secret"));
+        }
+        totp.issuer = issuer.filter(|i| !i.is_empty());
+        Ok(totp)
+    }
+
+    /// URI with the default parameters for the base32 secret, 6 digit SHA1
+    /// codes valid for 30 seconds
+    pub fn from_secret(secret: impl Into<String>) -> Self {
+        Self {
+            issuer: None,
+            account_name: String::new(),
+            secret: secret.into(),
+            algorithm: TotpAlgorithm::Sha1,
+            digits: 6,
+            period: 30,
+        }
+    }
+
+    /// QR code of the URI as an SVG document
+    #[cfg(feature = "totp-qr")]
+    pub fn qr_svg(&self) -> Result<String, ClerkTotpError> {
+        use qrcode::render::svg;
+
+        let code = qrcode::QrCode::new(self.to_string()).map_err(ClerkTotpError::QrCode)?;
+        Ok(code.render::<svg::Color>().min_dimensions(200, 200).build())
+    }
+
+    /// QR code of the URI drawn with unicode blocks, for printing to a
+    /// terminal with a dark background
+    #[cfg(feature = "totp-qr")]
+    pub fn qr_terminal(&self) -> Result<String, ClerkTotpError> {
+        use qrcode::render::unicode::Dense1x2;
+
+        let code = qrcode::QrCode::new(self.to_string()).map_err(ClerkTotpError::QrCode)?;
+        Ok(code
+            .render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .build())
+    }
+
+    /// RFC 6238 code for the unix time in seconds
+    #[cfg(feature = "totp-codes")]
+    pub fn code_at(&self, unix_time: u64) -> Result<String, ClerkTotpError> {
+        let key = decode_base32(&self.secret).ok_or(ClerkTotpError::InvalidSecret)?;
+        let counter = (unix_time / self.period).to_be_bytes();
+        let hash = match self.algorithm {
+            TotpAlgorithm::Sha1 => hmac_digest::<sha1::Sha1>(&key, &counter),
+            TotpAlgorithm::Sha256 => hmac_digest::<sha2::Sha256>(&key, &counter),
+            TotpAlgorithm::Sha512 => hmac_digest::<sha2::Sha512>(&key, &counter),
+        };
+        // Dynamic truncation, RFC 4226 section 5.3
+        let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            hash[offset],
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]) & 0x7fff_ffff;
+        let code = binary % 10u32.pow(self.digits);
+        Ok(format!("{code:0width$}", width = self.digits as usize))
+    }
+
+    /// Code for the current time
+    #[cfg(feature = "totp-codes")]
+    pub fn code_now(&self) -> Result<String, ClerkTotpError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.code_at(now)
+    }
+}
+
+#[cfg(feature = "totp-codes")]
+fn hmac_digest<D: Digest + BlockSizeUser>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac =
+        <SimpleHmac<D> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// RFC 4648 base32, case insensitive and padding optional as authenticator
+/// apps accept it
+#[cfg(feature = "totp-codes")]
+fn decode_base32(input: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| *c != '=' && !c.is_whitespace()) {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u32 - 'A' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    (!bytes.is_empty()).then_some(bytes)
+}
+
+#[derive(Debug)]
+pub enum ClerkTotpError {
+    ClerkApiError(ClerkApiError),
+    /// The API didn't return the secret to enroll with
+    MissingSecret,
+    InvalidUri(String),
+    InvalidSecret,
+    #[cfg(feature = "totp-qr")]
+    QrCode(qrcode::types::QrError),
+}
+impl fmt::Display for ClerkTotpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkTotpError::ClerkApiError(e) => e.fmt(f),
+            ClerkTotpError::MissingSecret => write!(f, "Clerk didn't return a TOTP secret"),
+            ClerkTotpError::InvalidUri(reason) => write!(f, "Invalid TOTP URI: {reason}"),
+            ClerkTotpError::InvalidSecret => write!(f, "TOTP secret is not valid base32"),
+            #[cfg(feature = "totp-qr")]
+            ClerkTotpError::QrCode(e) => write!(f, "Failed to create QR code: {e}"),
+        }
+    }
+}
+impl Error for ClerkTotpError {}
+
+impl ClerkTotpError {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkTotpError::ClerkApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// TOTP being added to the signed in user
+///
+/// Started with `Clerk::enroll_totp`. Show the URI to the user, example as a
+/// QR code, and verify the enrollment with a code from their authenticator
+/// app. The backup codes are returned once the enrollment is verified.
+///
+/// # Examples
+/// ```
+/// # async fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// let enrollment = clerk.enroll_totp().await?;
+/// println!("{}", enrollment.uri());
+/// let backup_codes = enrollment.verify("123456").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TotpEnrollment {
+    clerk: Clerk,
+    totp: Totp,
+    uri: TotpUri,
+}
+
+impl fmt::Debug for TotpEnrollment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TotpEnrollment")
+            .field("id", &self.totp.id)
+            .field("uri", &self.uri)
+            .finish()
+    }
+}
+
+impl TotpEnrollment {
+    /// Creates a new TOTP secret for the signed in user
+    pub async fn create(clerk: &Clerk) -> Result<Self, ClerkTotpError> {
+        let totp = clerk.get_fapi_client().post_totp().await.map_err(|e| {
+            error!("Failed to create TOTP: {e}");
+            ClerkTotpError::ClerkApiError(e.into())
+        })?;
+        let uri = match (&totp.uri, &totp.secret) {
+            (Some(uri), _) => TotpUri::parse(uri)?,
+            (None, Some(secret)) => TotpUri::from_secret(secret),
+            (None, None) => return Err(ClerkTotpError::MissingSecret),
+        };
+        Ok(Self {
+            clerk: clerk.clone(),
+            totp,
+            uri,
+        })
+    }
+
+    /// The TOTP as returned by the API
+    pub fn totp(&self) -> &Totp {
+        &self.totp
+    }
+
+    pub fn uri(&self) -> &TotpUri {
+        &self.uri
+    }
+
+    /// Verifies the enrollment with a code from the authenticator app and
+    /// returns the backup codes
+    pub async fn verify(&self, code: &str) -> Result<Vec<String>, ClerkTotpError> {
+        let totp = self
+            .clerk
+            .get_fapi_client()
+            .verify_totp(Some(code))
+            .await
+            .map_err(|e| {
+                error!("Failed to verify TOTP: {e}");
+                ClerkTotpError::ClerkApiError(e.into())
+            })?;
+        Ok(totp
+            .backup_codes
+            .flatten()
+            .or_else(|| self.totp.backup_codes.clone().flatten())
+            .unwrap_or_default())
+    }
+
+    /// Verifies the enrollment with a code generated from the secret
+    #[cfg(feature = "totp-codes")]
+    pub async fn verify_with_generated_code(&self) -> Result<Vec<String>, ClerkTotpError> {
+        self.verify(&self.uri.code_now()?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uri() {
+        let uri = TotpUri::parse(
+            "otpauth://totp/Example%20App:john%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example%20App&algorithm=SHA256&digits=8&period=60",
+        )
+        .unwrap();
+        assert_eq!(uri.issuer.as_deref(), Some("Example App"));
+        assert_eq!(uri.account_name, "john@example.com");
+        assert_eq!(uri.secret, "JBSWY3DPEHPK3PXP");
+        assert_eq!(uri.algorithm, TotpAlgorithm::Sha256);
+        assert_eq!(uri.digits, 8);
+        assert_eq!(uri.period, 60);
+        assert!(!format!("{uri:?}").contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(TotpUri::parse(&uri.to_string()).unwrap(), uri);
+
+        let uri = TotpUri::parse("otpauth://totp/john?secret=JBSWY3DPEHPK3PXP").unwrap();
+        assert_eq!(uri.issuer, None);
+        assert_eq!(uri.account_name, "john");
+        assert_eq!(uri.algorithm, TotpAlgorithm::Sha1);
+        assert_eq!(uri.digits, 6);
+        assert_eq!(uri.period, 30);
+
+        assert!(TotpUri::parse("otpauth://hotp/john?secret=JBSWY3DPEHPK3PXP").is_err());
+        assert!(TotpUri::parse("otpauth://totp/john").is_err());
+        assert!(TotpUri::parse("otpauth://totp/john?secret=A&digits=12").is_err());
+    }
+
+    #[cfg(feature = "totp-codes")]
+    #[test]
+    fn test_rfc6238_codes() {
+        let mut uri = TotpUri::from_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+        uri.digits = 8;
+        assert_eq!(uri.code_at(59).unwrap(), "94287082");
+        assert_eq!(uri.code_at(1111111109).unwrap(), "07081804");
+        assert_eq!(uri.code_at(20000000000).unwrap(), "65353130");
+
+        uri.secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA====".to_string();
+        uri.algorithm = TotpAlgorithm::Sha256;
+        assert_eq!(uri.code_at(59).unwrap(), "46119246");
+
+        uri.secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNA=".to_string();
+        uri.algorithm = TotpAlgorithm::Sha512;
+        assert_eq!(uri.code_at(59).unwrap(), "90693936");
+
+        uri.secret = "not base32!".to_string();
+        assert!(uri.code_at(59).is_err());
+    }
+
+    #[cfg(feature = "totp-qr")]
+    #[test]
+    fn test_qr_code() {
+        let uri = TotpUri::from_secret("JBSWY3DPEHPK3PXP");
+        assert!(uri.qr_svg().unwrap().contains("<svg"));
+        assert!(!uri.qr_terminal().unwrap().is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use log::error;
 
 use crate::{
     clerk_fapi::ClerkFapiClient,
+    error::ClerkApiError,
     models::{
         ClientClient, ClientClientWrappedOrganizationMembershipsResponse, ClientOrganization,
         ClientOrganizationMembership, ClientSession,
@@ -31,14 +32,14 @@ pub fn find_organization_id_from_memberships(
 pub enum ClerkOrgFindingError {
     NoUserFound,
     NoMatchFound,
-    ClerkApiError,
+    ClerkApiError(ClerkApiError),
 }
 impl fmt::Display for ClerkOrgFindingError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             ClerkOrgFindingError::NoUserFound => write!(f, "No user found"),
             ClerkOrgFindingError::NoMatchFound => write!(f, "No match found"),
-            ClerkOrgFindingError::ClerkApiError => write!(f, "Clerk API error"),
+            ClerkOrgFindingError::ClerkApiError(e) => e.fmt(f),
         }
     }
 }
@@ -68,7 +69,7 @@ pub async fn find_target_organization(
     // Let's start by refreshing user
     let user = fapi.get_user().await.map_err(|e| {
         error!("Failed to get user: {}", e);
-        ClerkOrgFindingError::ClerkApiError
+        ClerkOrgFindingError::ClerkApiError(e.into())
     })?;
 
     if let Some(user_org_memberships) = user.organization_memberships {
@@ -91,7 +92,7 @@ pub async fn find_target_organization(
         .await
         .map_err(|e| {
             error!("Failed to get org memberships: {}", e);
-            ClerkOrgFindingError::ClerkApiError
+            ClerkOrgFindingError::ClerkApiError(e.into())
         })?;
 
     if let Some(org) =
@@ -0,0 +1,470 @@
+use crate::models::{ClientEnvironment, UserSettingsPasswordSettings};
+use std::error::Error;
+use std::fmt;
+
+/// Longest password the API accepts, bcrypt uses only the first 72 bytes
+pub const MAX_PASSWORD_BYTES: usize = 72;
+
+/// Username length limits used when the instance doesn't set them
+pub const DEFAULT_USERNAME_MIN_LENGTH: i64 = 4;
+pub const DEFAULT_USERNAME_MAX_LENGTH: i64 = 64;
+
+/// Input that the API would reject, with the error code and param name the
+/// API would respond with, example `form_password_length_too_short`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub code: &'static str,
+    /// The form param, example `password`
+    pub param: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(code: &'static str, param: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            param,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+impl Error for ValidationError {}
+
+/// Validates form input offline against the rules of the instance
+///
+/// Built from the environment, so forms can give feedback before a
+/// request is made. The rules the environment doesn't have, example on
+/// environments without user settings, are not checked and the API has
+/// the final word.
+///
+/// The environment only tells whether the allowlist and the blocklist are
+/// enabled, the identifiers on them are given with `with_allowlist` and
+/// `with_blocklist`. Entries are identifiers or whole email domains as
+/// `*@example.com`.
+///
+/// # Examples
+/// ```
+/// # fn example(clerk: clerk_fapi_rs::clerk::Clerk) -> Result<(), Box<dyn std::error::Error>> {
+/// let validator = clerk.validator()?.with_blocklist(["*@spam.example"]);
+/// if let Err(errors) = validator.validate_password("hunter2") {
+///     for error in errors {
+///         println!("{}: {}", error.code, error.message);
+///     }
+/// }
+/// validator.validate_email_address("jane@example.com")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Validator {
+    password_settings: Option<UserSettingsPasswordSettings>,
+    username_min_length: i64,
+    username_max_length: i64,
+    username_extended_characters: bool,
+    allowlist_enabled: bool,
+    blocklist_enabled: bool,
+    allowlist: Vec<String>,
+    blocklist: Vec<String>,
+}
+
+impl Validator {
+    pub fn from_environment(environment: &ClientEnvironment) -> Self {
+        let Some(user_settings) = &environment.user_settings else {
+            return Self {
+                username_min_length: DEFAULT_USERNAME_MIN_LENGTH,
+                username_max_length: DEFAULT_USERNAME_MAX_LENGTH,
+                ..Default::default()
+            };
+        };
+        let username = &user_settings.username_settings;
+        let restrictions = &user_settings.restrictions;
+        Self {
+            password_settings: Some(*user_settings.password_settings.clone()),
+            username_min_length: username.min_length.unwrap_or(DEFAULT_USERNAME_MIN_LENGTH),
+            username_max_length: username.max_length.unwrap_or(DEFAULT_USERNAME_MAX_LENGTH),
+            username_extended_characters: username.allow_extended_special_characters,
+            allowlist_enabled: restrictions.allowlist.enabled,
+            blocklist_enabled: restrictions.blocklist.enabled,
+            allowlist: Vec::new(),
+            blocklist: Vec::new(),
+        }
+    }
+
+    /// Identifiers allowed to sign up when the allowlist is enabled
+    pub fn with_allowlist(mut self, entries: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allowlist = entries.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Identifiers not allowed to sign up when the blocklist is enabled
+    pub fn with_blocklist(mut self, entries: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.blocklist = entries.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Checks the password against the password settings, returning all
+    /// the rules it breaks
+    ///
+    /// With the `password-strength` feature passwords weaker than
+    /// `min_zxcvbn_strength` are rejected too, when the instance checks
+    /// the strength.
+    pub fn validate_password(&self, password: &str) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if password.len() > MAX_PASSWORD_BYTES {
+            errors.push(ValidationError::new(
+                "form_password_size_in_bytes_exceeded",
+                "password",
+                format!("Passwords must be at most {MAX_PASSWORD_BYTES} bytes"),
+            ));
+        }
+        if let Some(settings) = &self.password_settings {
+            let length = password.chars().count() as i64;
+            if length < settings.min_length {
+                errors.push(ValidationError::new(
+                    "form_password_length_too_short",
+                    "password",
+                    format!(
+                        "Passwords must be {} characters or more",
+                        settings.min_length
+                    ),
+                ));
+            }
+            if settings.max_length > 0 && length > settings.max_length {
+                errors.push(ValidationError::new(
+                    "form_password_length_too_long",
+                    "password",
+                    format!(
+                        "Passwords must be {} characters or less",
+                        settings.max_length
+                    ),
+                ));
+            }
+            let has = |matches: &dyn Fn(char) -> bool| password.chars().any(matches);
+            let classes = [
+                (
+                    settings.require_lowercase && !has(&char::is_lowercase),
+                    "form_password_no_lowercase",
+                    "a lowercase letter",
+                ),
+                (
+                    settings.require_uppercase && !has(&char::is_uppercase),
+                    "form_password_no_uppercase",
+                    "an uppercase letter",
+                ),
+                (
+                    settings.require_numbers && !has(&|c| c.is_ascii_digit()),
+                    "form_password_no_number",
+                    "a number",
+                ),
+                (
+                    settings.require_special_char && !has(&|c| self.is_special(c)),
+                    "form_password_no_special_char",
+                    "a special character",
+                ),
+            ];
+            for (missing, code, what) in classes {
+                if missing {
+                    errors.push(ValidationError::new(
+                        code,
+                        "password",
+                        format!("Passwords must contain {what}"),
+                    ));
+                }
+            }
+            #[cfg(feature = "password-strength")]
+            if settings.show_zxcvbn
+                && i64::from(u8::from(zxcvbn::zxcvbn(password, &[]).score()))
+                    < settings.min_zxcvbn_strength
+            {
+                errors.push(ValidationError::new(
+                    "form_password_not_strong_enough",
+                    "password",
+                    "Given password is not strong enough",
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn is_special(&self, c: char) -> bool {
+        match &self.password_settings {
+            Some(settings) if !settings.allowed_special_characters.is_empty() => {
+                settings.allowed_special_characters.contains(c)
+            }
+            _ => c.is_ascii_punctuation(),
+        }
+    }
+
+    /// Checks the length and the characters of the username
+    ///
+    /// Usernames have letters, numbers, `_` and `-`, and any other ASCII
+    /// punctuation when the instance allows extended special characters.
+    pub fn validate_username(&self, username: &str) -> Result<(), ValidationError> {
+        let length = username.chars().count() as i64;
+        if length < self.username_min_length || length > self.username_max_length {
+            return Err(ValidationError::new(
+                "form_username_invalid_length",
+                "username",
+                format!(
+                    "Usernames must be between {} and {} characters long",
+                    self.username_min_length, self.username_max_length
+                ),
+            ));
+        }
+        let allowed = |c: char| {
+            c.is_alphanumeric()
+                || c == '_'
+                || c == '-'
+                || (self.username_extended_characters && c.is_ascii_punctuation())
+        };
+        if !username.chars().all(allowed) {
+            return Err(ValidationError::new(
+                "form_username_invalid_character",
+                "username",
+                "Usernames can only contain letters, numbers and '_' or '-'",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the format of the email address and that it passes the
+    /// allowlist and the blocklist
+    pub fn validate_email_address(&self, email_address: &str) -> Result<(), ValidationError> {
+        if !is_email_address(email_address) {
+            return Err(ValidationError::new(
+                "form_param_format_invalid",
+                "email_address",
+                "email_address must be a valid email address",
+            ));
+        }
+        self.check_access(email_address, "email_address")
+    }
+
+    /// Checks that the phone number is in the E.164 format, example
+    /// `+15555550100`, and that it passes the allowlist and the blocklist
+    ///
+    /// Spaces, dashes, dots and parentheses are ignored.
+    pub fn validate_phone_number(&self, phone_number: &str) -> Result<(), ValidationError> {
+        let normalized = normalize_phone_number(phone_number);
+        let digits = normalized.strip_prefix('+').unwrap_or_default();
+        if !normalized.starts_with('+')
+            || !(7..=15).contains(&digits.len())
+            || !digits.chars().all(|c| c.is_ascii_digit())
+            || digits.starts_with('0')
+        {
+            return Err(ValidationError::new(
+                "form_param_format_invalid",
+                "phone_number",
+                "phone_number must be a valid phone number according to E.164 international standard",
+            ));
+        }
+        self.check_access(phone_number, "phone_number")
+    }
+
+    /// Checks the identifier, example a web3 wallet, against the allowlist
+    /// and the blocklist given to the validator
+    ///
+    /// The lists are checked only when they are enabled on the instance, an
+    /// enabled allowlist with no entries given is not checked.
+    pub fn validate_identifier_access(&self, identifier: &str) -> Result<(), ValidationError> {
+        self.check_access(identifier, "identifier")
+    }
+
+    fn check_access(&self, identifier: &str, param: &'static str) -> Result<(), ValidationError> {
+        let listed = |list: &[String]| list.iter().any(|entry| list_matches(entry, identifier));
+        let allowed =
+            !self.allowlist_enabled || self.allowlist.is_empty() || listed(&self.allowlist);
+        let blocked = self.blocklist_enabled && listed(&self.blocklist);
+        if !allowed || blocked {
+            return Err(ValidationError::new(
+                "not_allowed_access",
+                param,
+                format!("{identifier} is not allowed to access this application"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Whether the address looks like `local@domain.tld`, the API does the
+/// full check
+fn is_email_address(email_address: &str) -> bool {
+    let Some((local, domain)) = email_address.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !email_address.chars().any(char::is_whitespace)
+        && !domain.starts_with('-')
+        && domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+fn normalize_phone_number(phone_number: &str) -> String {
+    phone_number
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect()
+}
+
+/// Whether the allowlist or blocklist entry covers the identifier
+fn list_matches(entry: &str, identifier: &str) -> bool {
+    let entry = entry.trim().to_lowercase();
+    let identifier = identifier.trim().to_lowercase();
+    if let Some(domain) = entry.strip_prefix("*@") {
+        return identifier
+            .rsplit_once('@')
+            .is_some_and(|(_, d)| d == domain);
+    }
+    if entry.starts_with('+') {
+        return normalize_phone_number(&entry) == normalize_phone_number(&identifier);
+    }
+    entry == identifier
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        ClientUserSettings, UserSettingsRestrictionsEnabled, UserSettingsUsernameSettings,
+    };
+
+    fn validator() -> Validator {
+        let mut user_settings = ClientUserSettings::default();
+        *user_settings.password_settings = UserSettingsPasswordSettings {
+            min_length: 8,
+            max_length: 72,
+            require_special_char: true,
+            require_numbers: true,
+            require_uppercase: true,
+            require_lowercase: true,
+            allowed_special_characters: "!#$%&_".to_string(),
+            ..Default::default()
+        };
+        *user_settings.username_settings = UserSettingsUsernameSettings {
+            min_length: Some(3),
+            max_length: Some(10),
+            allow_extended_special_characters: false,
+        };
+        *user_settings.restrictions.allowlist = UserSettingsRestrictionsEnabled { enabled: true };
+        *user_settings.restrictions.blocklist = UserSettingsRestrictionsEnabled { enabled: true };
+        let environment = ClientEnvironment {
+            user_settings: Some(Box::new(user_settings)),
+            ..Default::default()
+        };
+        Validator::from_environment(&environment)
+    }
+
+    fn codes(result: Result<(), Vec<ValidationError>>) -> Vec<&'static str> {
+        result.unwrap_err().into_iter().map(|e| e.code).collect()
+    }
+
+    #[test]
+    fn test_validate_password() {
+        let validator = validator();
+        assert!(validator.validate_password("Tr0ub4dor&3").is_ok());
+        assert_eq!(
+            codes(validator.validate_password("abc")),
+            vec![
+                "form_password_length_too_short",
+                "form_password_no_uppercase",
+                "form_password_no_number",
+                "form_password_no_special_char"
+            ]
+        );
+        // Only the allowed special characters count
+        assert_eq!(
+            codes(validator.validate_password("Tr0ub4dor*3")),
+            vec!["form_password_no_special_char"]
+        );
+        assert_eq!(
+            codes(validator.validate_password(&format!("Aa1!{}", "ä".repeat(40)))),
+            vec!["form_password_size_in_bytes_exceeded"]
+        );
+        // Without user settings only the byte limit is checked
+        let validator = Validator::from_environment(&ClientEnvironment::default());
+        assert!(validator.validate_password("abc").is_ok());
+    }
+
+    #[cfg(feature = "password-strength")]
+    #[test]
+    fn test_validate_password_strength() {
+        let mut validator = validator();
+        let settings = validator.password_settings.as_mut().unwrap();
+        settings.show_zxcvbn = true;
+        settings.min_zxcvbn_strength = 3;
+        assert_eq!(
+            codes(validator.validate_password("Password1!")),
+            vec!["form_password_not_strong_enough"]
+        );
+        assert!(validator.validate_password("Gx7#mQ2_vR9%").is_ok());
+    }
+
+    #[test]
+    fn test_validate_username() {
+        let validator = validator();
+        assert!(validator.validate_username("jane_doe-1").is_ok());
+        assert_eq!(
+            validator.validate_username("jd").unwrap_err().code,
+            "form_username_invalid_length"
+        );
+        assert_eq!(
+            validator.validate_username("jane.doe").unwrap_err().code,
+            "form_username_invalid_character"
+        );
+        let mut extended = validator.clone();
+        extended.username_extended_characters = true;
+        assert!(extended.validate_username("jane.doe").is_ok());
+    }
+
+    #[test]
+    fn test_validate_email_address_and_phone_number() {
+        let validator = Validator::from_environment(&ClientEnvironment::default());
+        for valid in ["jane@example.com", "jane+tag@sub.example.co"] {
+            assert!(validator.validate_email_address(valid).is_ok(), "{valid}");
+        }
+        for invalid in ["jane", "@example.com", "jane@example", "ja ne@example.com"] {
+            let error = validator.validate_email_address(invalid).unwrap_err();
+            assert_eq!(error.code, "form_param_format_invalid");
+            assert_eq!(error.param, "email_address");
+        }
+        assert!(validator.validate_phone_number("+1 (555) 555-0100").is_ok());
+        for invalid in ["5555550100", "+1555", "+1555abc0100", "+01555550100"] {
+            let error = validator.validate_phone_number(invalid).unwrap_err();
+            assert_eq!(error.code, "form_param_format_invalid");
+            assert_eq!(error.param, "phone_number");
+        }
+    }
+
+    #[test]
+    fn test_allowlist_and_blocklist() {
+        let validator = validator()
+            .with_allowlist(["*@example.com", "+15555550100"])
+            .with_blocklist(["spam@example.com"]);
+        assert!(validator.validate_email_address("Jane@Example.com").is_ok());
+        assert!(validator.validate_phone_number("+1 555 555 0100").is_ok());
+        for denied in ["jane@other.com", "spam@example.com"] {
+            assert_eq!(
+                validator.validate_email_address(denied).unwrap_err().code,
+                "not_allowed_access"
+            );
+        }
+        assert!(validator.validate_identifier_access("0x1234").is_err());
+
+        // Lists are ignored when the restrictions are disabled
+        let validator = Validator::from_environment(&ClientEnvironment::default())
+            .with_blocklist(["spam@example.com"]);
+        assert!(validator.validate_email_address("spam@example.com").is_ok());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::clerk::Clerk;
+use crate::error::ClerkApiError;
+use crate::models::{
+    ClientSignInFirstFactorVerification, ClientWeb3Wallet, ClientWeb3WalletVerification,
+};
+use crate::sign_in::{ClerkSignInError, FirstFactor, PrepareFirstFactor, SignInFlow, SignInStatus};
+use crate::sign_up::{
+    ClerkSignUpError, PrepareVerification, SignUpField, SignUpFlow, SignUpParams, SignUpStatus,
+    Verification,
+};
+use log::error;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+pub use crate::sign_in::Web3Strategy;
+
+#[cfg(all(feature = "local-web3-signer", not(target_arch = "wasm32")))]
+pub use crate::local_web3_signer::LocalWeb3Signer;
+
+/// Future returned by `Web3Signer`, on wasm32 it doesn't need to be `Send`
+/// so that browser wallets can be called
+#[cfg(not(target_arch = "wasm32"))]
+pub type SignerFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+pub type SignerFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Web3 wallet signing the messages Clerk asks to verify the wallet
+///
+/// `LocalWeb3Signer`, behind the `local-web3-signer` feature, signs with a
+/// secp256k1 key held in memory.
+pub trait Web3Signer: Send + Sync {
+    /// Address of the wallet, example
+    /// `0x2c7536E3605D9C16a7a3D7b1898e529396a65c23`
+    fn address(&self) -> String;
+
+    /// Strategy matching the wallet, MetaMask by default
+    fn strategy(&self) -> Web3Strategy {
+        Web3Strategy::Metamask
+    }
+
+    /// Signs the message as EIP-191 `personal_sign` does, returning the
+    /// 0x prefixed hex signature
+    fn sign_message<'a>(&'a self, message: &'a str) -> SignerFuture<'a, anyhow::Result<String>>;
+}
+
+#[derive(Debug)]
+pub enum ClerkWeb3Error {
+    ClerkApiError(ClerkApiError),
+    ClerkSignInError(ClerkSignInError),
+    ClerkSignUpError(ClerkSignUpError),
+    /// The API didn't return the message to sign
+    MissingMessage,
+    Signer(anyhow::Error),
+}
+impl fmt::Display for ClerkWeb3Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClerkWeb3Error::ClerkApiError(e) => e.fmt(f),
+            ClerkWeb3Error::ClerkSignInError(e) => e.fmt(f),
+            ClerkWeb3Error::ClerkSignUpError(e) => e.fmt(f),
+            ClerkWeb3Error::MissingMessage => write!(f, "Clerk didn't return a message to sign"),
+            ClerkWeb3Error::Signer(e) => write!(f, "Web3 signer failed: {e}"),
+        }
+    }
+}
+impl Error for ClerkWeb3Error {}
+
+impl ClerkWeb3Error {
+    /// The Clerk API error behind this error, if any
+    pub fn api_error(&self) -> Option<&ClerkApiError> {
+        match self {
+            ClerkWeb3Error::ClerkApiError(e) => Some(e),
+            ClerkWeb3Error::ClerkSignInError(e) => e.api_error(),
+            ClerkWeb3Error::ClerkSignUpError(e) => e.api_error(),
+            _ => None,
+        }
+    }
+}
+
+async fn sign(
+    signer: &(impl Web3Signer + ?Sized),
+    message: &str,
+) -> Result<String, ClerkWeb3Error> {
+    signer
+        .sign_message(message)
+        .await
+        .map_err(ClerkWeb3Error::Signer)
+}
+
+/// Signs in with the wallet of the signer
+pub async fn sign_in_with_web3(
+    clerk: &Clerk,
+    signer: &(impl Web3Signer + ?Sized),
+) -> Result<SignInFlow, ClerkWeb3Error> {
+    let mut flow = SignInFlow::create(clerk, &signer.address())
+        .await
+        .map_err(ClerkWeb3Error::ClerkSignInError)?;
+    flow.attempt_web3(signer).await?;
+    Ok(flow)
+}
+
+/// Signs up with the wallet of the signer, the params can provide the other
+/// fields the instance requires
+pub async fn sign_up_with_web3(
+    clerk: &Clerk,
+    signer: &(impl Web3Signer + ?Sized),
+    params: SignUpParams,
+) -> Result<SignUpFlow, ClerkWeb3Error> {
+    let mut flow = SignUpFlow::create(clerk, params.web3_wallet(signer.address()))
+        .await
+        .map_err(ClerkWeb3Error::ClerkSignUpError)?;
+    flow.attempt_web3(signer).await?;
+    Ok(flow)
+}
+
+/// Adds the wallet of the signer to the signed in user and verifies it
+pub async fn link_web3_wallet(
+    clerk: &Clerk,
+    signer: &(impl Web3Signer + ?Sized),
+) -> Result<ClientWeb3Wallet, ClerkWeb3Error> {
+    let fapi = clerk.get_fapi_client();
+    let wallet = fapi
+        .post_web3_wallets(&signer.address(), None)
+        .await
+        .map_err(|e| {
+            error!("Failed to create web3 wallet: {e}");
+            ClerkWeb3Error::ClerkApiError(e.into())
+        })?;
+    let wallet = fapi
+        .prepare_web3_wallet_verification(
+            &wallet.id,
+            signer.strategy().as_str(),
+            None, // origin
+            None, // redirect_url
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to prepare web3 wallet verification: {e}");
+            ClerkWeb3Error::ClerkApiError(e.into())
+        })?;
+    let message = match wallet.verification.as_deref() {
+        Some(ClientWeb3WalletVerification::StubsVerificationWeb3Signature(v)) => {
+            v.message.clone().flatten()
+        }
+        _ => None,
+    }
+    .ok_or(ClerkWeb3Error::MissingMessage)?;
+    let signature = sign(signer, &message).await?;
+
+    fapi.attempt_web3_wallet_verification(&wallet.id, &signature, None)
+        .await
+        .map_err(|e| {
+            error!("Failed to verify web3 wallet: {e}");
+            ClerkWeb3Error::ClerkApiError(e.into())
+        })
+}
+
+impl SignInFlow {
+    /// Prepares and attempts the first factor with a signature from the
+    /// wallet
+    pub async fn attempt_web3(
+        &mut self,
+        signer: &(impl Web3Signer + ?Sized),
+    ) -> Result<SignInStatus, ClerkWeb3Error> {
+        let strategy = signer.strategy();
+        self.prepare_first_factor(PrepareFirstFactor::Web3(strategy))
+            .await
+            .map_err(ClerkWeb3Error::ClerkSignInError)?;
+        let message = match self.first_factor_verification() {
+            Some(ClientSignInFirstFactorVerification::StubsVerificationWeb3Signature(v)) => {
+                v.message.clone().flatten()
+            }
+            _ => None,
+        }
+        .ok_or(ClerkWeb3Error::MissingMessage)?;
+        let signature = sign(signer, &message).await?;
+        self.attempt_first_factor(FirstFactor::Web3 {
+            strategy,
+            signature,
+        })
+        .await
+        .map_err(ClerkWeb3Error::ClerkSignInError)
+    }
+}
+
+impl SignUpFlow {
+    /// Prepares and attempts verification of the web3 wallet with a
+    /// signature from the wallet
+    pub async fn attempt_web3(
+        &mut self,
+        signer: &(impl Web3Signer + ?Sized),
+    ) -> Result<SignUpStatus, ClerkWeb3Error> {
+        let strategy = signer.strategy();
+        self.prepare_verification(PrepareVerification::Web3(strategy))
+            .await
+            .map_err(ClerkWeb3Error::ClerkSignUpError)?;
+        let message = self
+            .verification(&SignUpField::Web3Wallet)
+            .and_then(|v| v.message.clone())
+            .ok_or(ClerkWeb3Error::MissingMessage)?;
+        let signature = sign(signer, &message).await?;
+        self.attempt_verification(Verification::Web3 {
+            strategy,
+            signature,
+        })
+        .await
+        .map_err(ClerkWeb3Error::ClerkSignUpError)
+    }
+}
@@ -676,6 +676,8 @@ async fn test_init() {
 
 #[tokio::test]
 async fn test_init_environment_failure() {
+    use clerk_fapi_rs::retry::RetryPolicy;
+
     let mut server = Server::new_async().await;
 
     let env_mock = server
@@ -690,7 +692,8 @@ async fn test_init_environment_failure() {
         Some(server.url()),
         None,
     )
-    .unwrap();
+    .unwrap()
+    .with_retry_policy(RetryPolicy::none());
 
     let client = Clerk::new(config);
 
@@ -747,6 +750,218 @@ async fn test_init_uses_update_client() {
     assert!(client.user().unwrap().is_some());
 }
 
+#[tokio::test]
+async fn test_load_strategies() {
+    use clerk_fapi_rs::clerk::{ClerkLoadError, LoadSource, LoadStrategy};
+    use std::time::Duration;
+
+    let mut server = Server::new_async().await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_status(200)
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .expect(3)
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(2)
+        .create_async()
+        .await;
+
+    // Clones of the config share the store
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+
+    let result = Clerk::new(config.clone())
+        .load_with(LoadStrategy::CacheOnly)
+        .await;
+    assert!(matches!(result, Err(ClerkLoadError::FailedToLoadEnv(None))));
+
+    let hour = LoadStrategy::CacheFirst {
+        max_age: Duration::from_secs(3600),
+    };
+    let source = Clerk::new(config.clone())
+        .load_with(hour)
+        .await
+        .unwrap()
+        .source;
+    assert_eq!(source, LoadSource::Network);
+
+    let clerk = Clerk::new(config.clone());
+    let loaded = clerk.load_with(hour).await.unwrap();
+    assert_eq!(loaded.source, LoadSource::Cache);
+    assert!(loaded.revalidation.is_none());
+    assert!(clerk.client().is_ok());
+    assert_eq!(
+        Clerk::new(config.clone())
+            .load_with(LoadStrategy::CacheOnly)
+            .await
+            .unwrap()
+            .source,
+        LoadSource::Cache
+    );
+
+    // Loading from the cache doesn't make the stored values fresh again
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let short = LoadStrategy::CacheFirst {
+        max_age: Duration::from_millis(10),
+    };
+    let source = Clerk::new(config.clone())
+        .load_with(short)
+        .await
+        .unwrap()
+        .source;
+    assert_eq!(source, LoadSource::Network);
+
+    client_mock.assert_async().await;
+
+    // The revalidation fetches the signed in client and calls the listeners
+    client_mock.remove_async().await;
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let clerk = Clerk::new(config.clone());
+    let signed_in = Arc::new(AtomicBool::new(false));
+    let signed_in_clone = signed_in.clone();
+    clerk.add_listener(move |_, session, _, _| {
+        signed_in_clone.store(session.is_some(), Ordering::SeqCst);
+    });
+
+    let loaded = clerk
+        .load_with(LoadStrategy::CacheThenRevalidate)
+        .await
+        .unwrap();
+    assert_eq!(loaded.source, LoadSource::Cache);
+    assert!(!signed_in.load(Ordering::SeqCst));
+
+    tokio::spawn(loaded.revalidation.unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(signed_in.load(Ordering::SeqCst));
+    assert!(clerk.user().unwrap().is_some());
+
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_load_from_cache_restores_dev_browser() {
+    use clerk_fapi_rs::clerk::{LoadSource, LoadStrategy};
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let dev_browser_mock = server
+        .mock("POST", "/v1/dev_browser")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "id": "dvb_123",
+                "instance_id": "ins_123",
+                "token": "dvb_token",
+                "client_id": null,
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:00:00Z",
+                "home_origin": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment")
+        .match_query(Matcher::UrlEncoded(
+            "__clerk_db_jwt".to_string(),
+            "dvb_123".to_string(),
+        ))
+        .with_status(200)
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .expect(2)
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client")
+        .match_query(Matcher::UrlEncoded(
+            "__clerk_db_jwt".to_string(),
+            "dvb_123".to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(2)
+        .create_async()
+        .await;
+
+    // Clones of the config share the store
+    let config = ClerkFapiConfiguration::new_browser(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+
+    let source = Clerk::new(config.clone())
+        .load_with(LoadStrategy::NetworkFirst)
+        .await
+        .unwrap()
+        .source;
+    assert_eq!(source, LoadSource::Network);
+
+    // The stored client is revalidated with the dev browser it belongs to
+    let clerk = Clerk::new(config.clone());
+    let loaded = clerk
+        .load_with(LoadStrategy::CacheThenRevalidate)
+        .await
+        .unwrap();
+    assert_eq!(loaded.source, LoadSource::Cache);
+    loaded.revalidation.unwrap().await.unwrap();
+
+    dev_browser_mock.assert_async().await;
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+}
+
 #[tokio::test]
 async fn test_get_token() {
     let mut server = Server::new_async().await;
@@ -971,3 +1186,2807 @@ async fn test_parse_client() {
     // this is just smoke test to be able to parse real client data
     let _client: clerk_fapi_rs::models::ClientClient = serde_json::from_value(value1).unwrap();
 }
+
+#[tokio::test]
+async fn test_verify_token() {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+
+    let mut server = Server::new_async().await;
+
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]);
+    let jwks_mock = server
+        .mock("GET", "/.well-known/jwks.json?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "keys": [{
+                    "kid": "ins_test",
+                    "alg": "EdDSA",
+                    "use": "sig",
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": URL_SAFE_NO_PAD.encode(signing_key.verifying_key().as_bytes())
+                }]
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        // Once for the first token and once more for the unknown kid
+        .expect(2)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some("ins_test".to_string());
+    let sign = |iss: &str| {
+        jsonwebtoken::encode(
+            &header,
+            &serde_json::json!({
+                "sid": "sess_abc123xyz456def789",
+                "sub": "user_abc123",
+                "iss": iss,
+                "exp": now + 60,
+                "iat": now,
+                "nbf": now
+            }),
+            &EncodingKey::from_ed_der(signing_key.to_pkcs8_der().unwrap().as_bytes()),
+        )
+        .unwrap()
+    };
+
+    // Requests go through the proxy but the tokens are issued by the
+    // Frontend API of the instance
+    let token = sign("https://clerk.example.com");
+    let claims = clerk.verify_token(&token).await.unwrap();
+    assert_eq!(claims.sid.as_deref(), Some("sess_abc123xyz456def789"));
+    assert_eq!(claims.sub, "user_abc123");
+
+    // Second verification uses the cached JWKS
+    assert!(clerk.verify_token(&token).await.is_ok());
+
+    assert!(matches!(
+        clerk.verify_token(&sign(&server.url())).await,
+        Err(clerk_fapi_rs::jwt::JwtVerificationError::InvalidIssuer(_))
+    ));
+
+    // An unknown kid fetches the JWKS again, but only once per interval
+    let mut forged_header = header.clone();
+    forged_header.kid = Some("ins_forged".to_string());
+    let forged = jsonwebtoken::encode(
+        &forged_header,
+        &serde_json::json!({ "sub": "user_abc123", "exp": now + 60 }),
+        &EncodingKey::from_ed_der(signing_key.to_pkcs8_der().unwrap().as_bytes()),
+    )
+    .unwrap();
+    for _ in 0..3 {
+        assert!(matches!(
+            clerk.verify_token(&forged).await,
+            Err(clerk_fapi_rs::jwt::JwtVerificationError::UnknownKeyId(_))
+        ));
+    }
+
+    jwks_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_get_token_is_cached_and_coalesced() {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 60;
+    let jwt = format!(
+        "eyJhbGciOiJSUzI1NiJ9.{}.signature",
+        URL_SAFE_NO_PAD.encode(serde_json::json!({ "exp": exp }).to_string())
+    );
+
+    let token_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/tokens?_is_native=1",
+        )
+        .with_status(200)
+        .with_body(serde_json::json!({ "jwt": jwt }).to_string())
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+
+    let client = Clerk::new(config);
+    client.load().await.unwrap();
+
+    let (first, second) = tokio::join!(client.get_token(None, None), client.get_token(None, None));
+    assert_eq!(first.unwrap(), Some(jwt.clone()));
+    assert_eq!(second.unwrap(), Some(jwt.clone()));
+
+    let third = client.get_token(None, None).await.unwrap();
+    assert_eq!(third, Some(jwt));
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    token_mock.assert_async().await;
+}
+
+fn sign_in_attempt(status: &str, created_session_id: Option<&str>) -> Value {
+    serde_json::json!({
+        "object": "sign_in_attempt",
+        "id": "sia_abc123",
+        "status": status,
+        "supported_identifiers": ["email_address"],
+        "supported_first_factors": [
+            {
+                "strategy": "email_code",
+                "safe_identifier": "j***@example.com",
+                "email_address_id": "idn_abc123",
+                "primary": true
+            },
+            {
+                "strategy": "password"
+            }
+        ],
+        "supported_second_factors": null,
+        "first_factor_verification": null,
+        "second_factor_verification": null,
+        "identifier": "john.doe@example.com",
+        "user_data": null,
+        "created_session_id": created_session_id,
+        "abandon_at": 1731414303443i64
+    })
+}
+
+#[tokio::test]
+async fn test_sign_in_flow() {
+    use clerk_fapi_rs::sign_in::{FirstFactor, PrepareFirstFactor, SignInStatus};
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .match_body(Matcher::UrlEncoded(
+            "identifier".to_string(),
+            "john.doe@example.com".to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": sign_in_attempt("needs_first_factor", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let prepare_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ins/sia_abc123/prepare_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "email_code".to_string()),
+            Matcher::UrlEncoded("email_address_id".to_string(), "idn_abc123".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": sign_in_attempt("needs_first_factor", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let attempt_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ins/sia_abc123/attempt_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "email_code".to_string()),
+            Matcher::UrlEncoded("code".to_string(), "424242".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": sign_in_attempt("complete", Some("sess_abc123xyz456def789")),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let touch_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/touch?_is_native=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client()["sessions"][0],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let mut sign_in = clerk.sign_in("john.doe@example.com").await.unwrap();
+    assert!(sign_in.needs_first_factor());
+    assert!(sign_in.first_factor("password").is_some());
+    assert!(sign_in.first_factor("phone_code").is_none());
+
+    sign_in
+        .prepare_first_factor(PrepareFirstFactor::EmailCode)
+        .await
+        .unwrap();
+    assert!(sign_in
+        .prepare_first_factor(PrepareFirstFactor::PhoneCode)
+        .await
+        .is_err());
+
+    let status = sign_in
+        .attempt_first_factor(FirstFactor::EmailCode("424242".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(status, SignInStatus::Complete);
+    assert_eq!(
+        sign_in.created_session_id(),
+        Some("sess_abc123xyz456def789")
+    );
+    assert!(clerk.session().unwrap().is_some());
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_mock.assert_async().await;
+    prepare_mock.assert_async().await;
+    attempt_mock.assert_async().await;
+    touch_mock.assert_async().await;
+}
+
+fn sign_up_attempt(status: &str, created_session_id: Option<&str>) -> Value {
+    let complete = status == "complete";
+    serde_json::json!({
+        "object": "sign_up_attempt",
+        "id": "sua_abc123",
+        "status": status,
+        "required_fields": ["email_address", "password", "legal_accepted"],
+        "optional_fields": ["first_name", "last_name", "username"],
+        "missing_fields": [],
+        "unverified_fields": if complete { vec![] } else { vec!["email_address"] },
+        "verifications": {
+            "email_address": {
+                "next_action": if complete { "" } else { "needs_prepare" },
+                "supported_strategies": ["email_code", "email_link"]
+            },
+            "phone_number": null,
+            "web3_wallet": null,
+            "external_account": null
+        },
+        "username": null,
+        "email_address": "john.doe@example.com",
+        "phone_number": null,
+        "web3_wallet": null,
+        "password_enabled": true,
+        "first_name": null,
+        "last_name": null,
+        "unsafe_metadata": { "plan": "pro" },
+        "custom_action": false,
+        "external_id": null,
+        "created_session_id": created_session_id,
+        "created_user_id": null,
+        "abandon_at": 1731414303443i64,
+        "legal_accepted_at": 1731414303443i64
+    })
+}
+
+#[tokio::test]
+async fn test_sign_up_flow() {
+    use clerk_fapi_rs::sign_up::{
+        PrepareVerification, SignUpField, SignUpParams, SignUpStatus, Verification,
+    };
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_mock = server
+        .mock("POST", "/v1/client/sign_ups?_is_native=1")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded(
+                "email_address".to_string(),
+                "john.doe@example.com".to_string(),
+            ),
+            Matcher::UrlEncoded("password".to_string(), "correct horse".to_string()),
+            Matcher::UrlEncoded("legal_accepted".to_string(), "true".to_string()),
+            Matcher::UrlEncoded(
+                "unsafe_metadata".to_string(),
+                r#"{"plan":"pro"}"#.to_string(),
+            ),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": sign_up_attempt("missing_requirements", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let prepare_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ups/sua_abc123/prepare_verification?_is_native=1",
+        )
+        .match_body(Matcher::UrlEncoded(
+            "strategy".to_string(),
+            "email_code".to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": sign_up_attempt("missing_requirements", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let attempt_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ups/sua_abc123/attempt_verification?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "email_code".to_string()),
+            Matcher::UrlEncoded("code".to_string(), "424242".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": sign_up_attempt("complete", Some("sess_abc123xyz456def789")),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let touch_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/touch?_is_native=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client()["sessions"][0],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let requirements = clerk.sign_up_requirements().unwrap();
+    assert_eq!(
+        requirements.required,
+        vec![
+            SignUpField::EmailAddress,
+            SignUpField::Password,
+            SignUpField::LegalAccepted
+        ]
+    );
+    assert!(!requirements.is_enabled(&SignUpField::PhoneNumber));
+
+    let params = SignUpParams::default().email_address("john.doe@example.com");
+    assert_eq!(
+        requirements.missing(&params),
+        vec![SignUpField::Password, SignUpField::LegalAccepted]
+    );
+    let params = params
+        .password("correct horse")
+        .legal_accepted(true)
+        .unsafe_metadata(serde_json::json!({ "plan": "pro" }));
+    assert!(requirements.missing(&params).is_empty());
+    assert!(!format!("{params:?}").contains("correct horse"));
+
+    assert!(clerk
+        .sign_up(SignUpParams::default().unsafe_metadata(serde_json::json!("pro")))
+        .await
+        .is_err());
+
+    let mut sign_up = clerk.sign_up(params).await.unwrap();
+    assert!(sign_up.is_missing_requirements());
+    assert!(sign_up.missing_fields().is_empty());
+    assert_eq!(sign_up.unverified_fields(), vec![SignUpField::EmailAddress]);
+    assert!(sign_up.verification(&SignUpField::EmailAddress).is_some());
+
+    sign_up
+        .prepare_verification(PrepareVerification::EmailCode)
+        .await
+        .unwrap();
+
+    let status = sign_up
+        .attempt_verification(Verification::EmailCode("424242".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(status, SignUpStatus::Complete);
+    assert_eq!(
+        sign_up.created_session_id(),
+        Some("sess_abc123xyz456def789")
+    );
+    assert!(clerk.session().unwrap().is_some());
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_mock.assert_async().await;
+    prepare_mock.assert_async().await;
+    attempt_mock.assert_async().await;
+    touch_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_api_errors_are_structured() {
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .with_status(422)
+        .with_body(
+            serde_json::json!({
+                "errors": [{
+                    "message": "Couldn't find your account.",
+                    "long_message": "Couldn't find your account.",
+                    "code": "form_identifier_not_found",
+                    "meta": { "param_name": "identifier" }
+                }],
+                "clerk_trace_id": "trace_abc123"
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let err = clerk.sign_in("nobody@example.com").await.unwrap_err();
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.status(), Some(422));
+    assert!(api_error.is_code("form_identifier_not_found"));
+    assert_eq!(api_error.clerk_trace_id(), Some("trace_abc123"));
+    assert_eq!(api_error.message(), Some("Couldn't find your account."));
+    assert!(!api_error.is_rate_limited());
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_load_error_carries_api_error() {
+    let mut server = Server::new_async().await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_status(401)
+        .with_body(
+            serde_json::json!({
+                "errors": [{
+                    "message": "Invalid host",
+                    "long_message": "The publishable key doesn't match the host.",
+                    "code": "host_invalid"
+                }],
+                "clerk_trace_id": "trace_env123"
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(401)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+
+    // Nothing in the store to fall back to
+    let err = clerk.load().await.unwrap_err();
+    assert!(matches!(
+        err,
+        clerk_fapi_rs::clerk::ClerkLoadError::FailedToLoadEnv(Some(_))
+    ));
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.status(), Some(401));
+    assert!(api_error.is_code("host_invalid"));
+    assert_eq!(api_error.clerk_trace_id(), Some("trace_env123"));
+    assert!(!clerk.loaded());
+
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_retries_transient_failures() {
+    use clerk_fapi_rs::retry::RetryPolicy;
+    use std::time::Duration;
+
+    let mut server = Server::new_async().await;
+
+    let env_unavailable_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_status(503)
+        .with_header("retry-after", "0")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    // Non idempotent requests are not retried on server errors
+    let sign_in_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .with_status(500)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap()
+    .with_retry_policy(RetryPolicy {
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    });
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+    assert!(clerk.environment().is_ok());
+
+    let err = clerk.sign_in("john.doe@example.com").await.unwrap_err();
+    assert_eq!(err.api_error().unwrap().status(), Some(500));
+
+    env_unavailable_mock.assert_async().await;
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+    sign_in_mock.assert_async().await;
+}
+
+#[derive(Debug, Default, Clone)]
+struct RecordingMiddleware {
+    statuses: Arc<parking_lot::Mutex<Vec<u16>>>,
+}
+
+impl clerk_fapi_rs::middleware::Middleware for RecordingMiddleware {
+    fn after_response(&self, response: &reqwest::Response) -> anyhow::Result<()> {
+        self.statuses.lock().push(response.status().as_u16());
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct DenySignOutMiddleware;
+
+impl clerk_fapi_rs::middleware::Middleware for DenySignOutMiddleware {
+    fn before_request(&self, request: &mut reqwest::Request) -> anyhow::Result<()> {
+        if request.method() == reqwest::Method::DELETE {
+            anyhow::bail!("sign out disabled");
+        }
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_middlewares() {
+    use clerk_fapi_rs::middleware::{HeadersMiddleware, LoggingMiddleware, RequestIdMiddleware};
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .match_header("x-app-version", "1.2.3")
+        .match_header("x-request-id", Matcher::Any)
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .match_header("x-app-version", "1.2.3")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let recording = RecordingMiddleware::default();
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap()
+    .with_middleware(LoggingMiddleware::default())
+    .with_middleware(RequestIdMiddleware::default())
+    .with_middleware(
+        HeadersMiddleware::default()
+            .header("x-app-version", "1.2.3")
+            .unwrap(),
+    )
+    .with_middleware(recording.clone())
+    .with_middleware(DenySignOutMiddleware);
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    assert_eq!(*recording.statuses.lock(), vec![200, 200]);
+
+    let err = clerk.sign_out(None).await.unwrap_err();
+    assert!(err.is_transport_error());
+    assert!(err.to_string().contains("sign out disabled"));
+
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_subscribe() {
+    use clerk_fapi_rs::events::ClerkEvent;
+    use futures::{FutureExt, StreamExt};
+
+    let mut server = Server::new_async().await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let token_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/tokens?_is_native=1",
+        )
+        .with_status(200)
+        .with_body(serde_json::json!({ "jwt": "test.jwt.token" }).to_string())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let sign_out_mock = server
+        .mock("DELETE", "/v1/client/sessions?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    let mut events = clerk.subscribe();
+    let unsubscribed = clerk.subscribe();
+
+    clerk.load().await.unwrap();
+    match events.next().await.unwrap() {
+        ClerkEvent::SignedIn { session, user } => {
+            assert_eq!(session.id, "sess_abc123xyz456def789");
+            assert_eq!(user.id, "user_123abc456def789");
+        }
+        event => panic!("unexpected event {event:?}"),
+    }
+
+    unsubscribed.unsubscribe();
+
+    clerk.get_token(None, None).await.unwrap();
+    assert_eq!(
+        events.next().await.unwrap(),
+        ClerkEvent::TokenRefreshed {
+            session_id: "sess_abc123xyz456def789".to_string(),
+            organization_id: Some("org_987zyx654wvu321".to_string()),
+            template: None,
+        }
+    );
+
+    clerk.sign_out(None).await.unwrap();
+    assert_eq!(
+        events.next().await.unwrap(),
+        ClerkEvent::SignedOut {
+            session_id: "sess_abc123xyz456def789".to_string()
+        }
+    );
+    assert!(events.next().now_or_never().is_none());
+
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+    token_mock.assert_async().await;
+    sign_out_mock.assert_async().await;
+}
+
+fn session_reverification(status: &str) -> Value {
+    serde_json::json!({
+        "object": "session_reverification",
+        "level": "first_factor",
+        "status": status,
+        "supported_first_factors": [{ "strategy": "password" }],
+        "supported_second_factors": null,
+        "first_factor_verification": null,
+        "second_factor_verification": null,
+        "session": {
+            "id": "sess_abc123xyz456def789",
+            "object": "session",
+            "status": "active",
+            "expire_at": 1_700_000_000_000i64,
+            "abandon_at": 1_700_000_000_000i64,
+            "last_active_at": 1_700_000_000_000i64
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_with_reverification() {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+    use clerk_fapi_rs::error::ClerkApiError;
+    use clerk_fapi_rs::reverification::{ReverificationLevel, ReverificationPolicy};
+    use clerk_fapi_rs::sign_in::FirstFactor;
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let rejected_mock = server
+        .mock("DELETE", "/v1/me?_is_native=1")
+        .with_status(403)
+        .with_body(
+            serde_json::json!({
+                "errors": [{
+                    "message": "Reverification required",
+                    "long_message": "You need to provide additional verification to perform this operation",
+                    "code": "session_reverification_required",
+                    "meta": { "reverification": { "level": "first_factor", "after_minutes": 10 } }
+                }]
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let delete_mock = server
+        .mock("DELETE", "/v1/me?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": { "object": "user", "id": "user_123abc456def789", "deleted": true },
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let start_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/verify?_is_native=1",
+        )
+        .match_body(Matcher::UrlEncoded(
+            "level".to_string(),
+            "first_factor".to_string(),
+        ))
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": session_reverification("needs_first_factor"),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let attempt_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/verify/attempt_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "password".to_string()),
+            Matcher::UrlEncoded("password".to_string(), "hunter2".to_string()),
+        ]))
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": session_reverification("complete"),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 60;
+    let jwt = format!(
+        "eyJhbGciOiJSUzI1NiJ9.{}.signature",
+        URL_SAFE_NO_PAD.encode(serde_json::json!({ "exp": exp }).to_string())
+    );
+
+    // Fetched again after the reverification
+    let token_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/tokens?_is_native=1",
+        )
+        .with_status(200)
+        .with_body(serde_json::json!({ "jwt": jwt }).to_string())
+        .with_header("content-type", "application/json")
+        .expect(2)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+    clerk.get_token(None, None).await.unwrap();
+    clerk.get_token(None, None).await.unwrap();
+
+    let policy = ReverificationPolicy::STRICT;
+    let needs = clerk
+        .needs_reverification(policy.level, policy.max_age)
+        .unwrap();
+    let session = clerk.session().unwrap().unwrap();
+    assert_eq!(
+        needs,
+        !policy.is_satisfied(&session.factor_verification_age)
+    );
+
+    let fapi = clerk.get_fapi_client();
+    let deleted = clerk
+        .with_reverification(
+            || async { fapi.delete_user().await.map_err(ClerkApiError::from) },
+            |mut reverification| async move {
+                assert_eq!(
+                    reverification.level(),
+                    Some(ReverificationLevel::FirstFactor)
+                );
+                assert!(reverification.first_factor("password").is_some());
+                reverification
+                    .attempt_first_factor(FirstFactor::Password("hunter2".to_string()))
+                    .await?;
+                Ok(reverification)
+            },
+        )
+        .await
+        .unwrap();
+    assert!(deleted.deleted);
+    clerk.get_token(None, None).await.unwrap();
+
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+    token_mock.assert_async().await;
+    rejected_mock.assert_async().await;
+    delete_mock.assert_async().await;
+    start_mock.assert_async().await;
+    attempt_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_has() {
+    use clerk_fapi_rs::authorization::AuthCheck;
+
+    let mut server = Server::new_async().await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    assert!(!clerk.has(&AuthCheck::new()));
+
+    clerk.load().await.unwrap();
+    assert!(clerk.has(&AuthCheck::new()));
+    // No active organization
+    assert!(!clerk.has(&AuthCheck::new().role("admin")));
+    assert!(!clerk.has(&AuthCheck::new().permission("org:sys_memberships:read")));
+
+    env_mock.assert_async().await;
+    client_mock.assert_async().await;
+}
+
+fn oauth_sign_in_attempt(verification_status: &str) -> Value {
+    let mut sign_in = sign_in_attempt("needs_first_factor", None);
+    sign_in["first_factor_verification"] = serde_json::json!({
+        "object": "verification_oauth",
+        "status": verification_status,
+        "strategy": "oauth_google",
+        "external_verification_redirect_url": "https://accounts.google.com/o/oauth2/auth?state=abc",
+        "error": null,
+        "expire_at": 1731414303443i64,
+        "attempts": null
+    });
+    sign_in
+}
+
+#[tokio::test]
+async fn test_oauth_sign_in_transfers_to_sign_up() {
+    use clerk_fapi_rs::oauth::{OAuthAttempt, OAuthRedirect};
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "oauth_google".to_string()),
+            Matcher::UrlEncoded(
+                "redirect_url".to_string(),
+                "myapp://oauth-callback".to_string(),
+            ),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": oauth_sign_in_attempt("unverified"),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let reload_mock = server
+        .mock("GET", "/v1/client/sign_ins/sia_abc123")
+        .match_query(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("rotating_token_nonce".to_string(), "nonce_123".to_string()),
+            Matcher::UrlEncoded("_is_native".to_string(), "1".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": oauth_sign_in_attempt("transferable"),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let transfer_mock = server
+        .mock("POST", "/v1/client/sign_ups?_is_native=1")
+        .match_body(Matcher::UrlEncoded(
+            "transfer".to_string(),
+            "true".to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": sign_up_attempt("complete", Some("sess_abc123xyz456def789")),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let touch_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/touch?_is_native=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client()["sessions"][0],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let oauth = clerk
+        .sign_in_with_oauth(
+            "oauth_google",
+            OAuthRedirect::DeepLink("myapp://oauth-callback".to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        oauth.authorization_url(),
+        "https://accounts.google.com/o/oauth2/auth?state=abc"
+    );
+    assert_eq!(oauth.redirect_url(), "myapp://oauth-callback");
+
+    let attempt = oauth
+        .complete("myapp://oauth-callback?rotating_token_nonce=nonce_123")
+        .await
+        .unwrap();
+    assert!(matches!(attempt, OAuthAttempt::SignUp(_)));
+    assert!(attempt.is_complete());
+    assert_eq!(
+        attempt.created_session_id(),
+        Some("sess_abc123xyz456def789")
+    );
+    assert!(clerk.session().unwrap().is_some());
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_mock.assert_async().await;
+    reload_mock.assert_async().await;
+    transfer_mock.assert_async().await;
+    touch_mock.assert_async().await;
+}
+
+fn passkey(verification_status: &str, nonce: Option<String>) -> Value {
+    serde_json::json!({
+        "object": "passkey",
+        "id": "pk_abc123",
+        "name": "Software authenticator",
+        "last_used_at": null,
+        "verification": {
+            "object": "verification_passkey",
+            "status": verification_status,
+            "strategy": "passkey",
+            "attempts": null,
+            "expire_at": 1731414303443i64,
+            "nonce": nonce
+        },
+        "created_at": 1731327903443i64,
+        "updated_at": 1731327903443i64
+    })
+}
+
+fn passkey_sign_in_attempt(status: &str, created_session_id: Option<&str>) -> Value {
+    let mut sign_in = sign_in_attempt(status, created_session_id);
+    sign_in["identifier"] = Value::Null;
+    sign_in["first_factor_verification"] = serde_json::json!({
+        "object": "verification_passkey",
+        "status": if created_session_id.is_some() { "verified" } else { "unverified" },
+        "strategy": "passkey",
+        "attempts": null,
+        "expire_at": 1731414303443i64,
+        "nonce": serde_json::json!({
+            "challenge": "c2lnbl9pbl9jaGFsbGVuZ2U",
+            "rpId": "example.com",
+            "userVerification": "required"
+        })
+        .to_string()
+    });
+    sign_in
+}
+
+#[tokio::test]
+async fn test_passkey_register_and_sign_in() {
+    use clerk_fapi_rs::passkey::SoftwareAuthenticator;
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let creation_options = serde_json::json!({
+        "rp": { "id": "example.com", "name": "Example" },
+        "user": { "id": "dXNlcl8x", "name": "john.doe@example.com", "displayName": "John Doe" },
+        "challenge": "cmVnaXN0ZXJfY2hhbGxlbmdl",
+        "pubKeyCredParams": [{ "type": "public-key", "alg": -7 }],
+        "authenticatorSelection": { "residentKey": "required", "userVerification": "required" },
+        "attestation": "none"
+    });
+    let create_passkey_mock = server
+        .mock("POST", "/v1/me/passkeys")
+        .match_query(Matcher::Any)
+        .with_body(
+            serde_json::json!({
+                "response": passkey("unverified", Some(creation_options.to_string())),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let verify_passkey_mock = server
+        .mock("POST", "/v1/me/passkeys/pk_abc123/attempt_verification")
+        .match_query(Matcher::Any)
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "passkey".to_string()),
+            Matcher::Regex("public_key_credential=".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": passkey("verified", None),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let sign_in_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .match_body(Matcher::UrlEncoded(
+            "strategy".to_string(),
+            "passkey".to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": passkey_sign_in_attempt("needs_first_factor", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let attempt_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ins/sia_abc123/attempt_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "passkey".to_string()),
+            Matcher::Regex("public_key_credential=".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": passkey_sign_in_attempt("complete", Some("sess_abc123xyz456def789")),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let touch_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/touch?_is_native=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client()["sessions"][0],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let authenticator = SoftwareAuthenticator::new("https://example.com");
+    let registered = clerk.register_passkey(&authenticator).await.unwrap();
+    assert_eq!(registered.id, "pk_abc123");
+    assert_eq!(authenticator.credential_ids().len(), 1);
+
+    // The instance is in single session mode, so sign out before signing
+    // in again
+    clerk
+        .set_client(serde_json::from_value(not_logged_in_client()).unwrap())
+        .unwrap();
+    let sign_in = clerk.sign_in_with_passkey(&authenticator).await.unwrap();
+    assert!(sign_in.is_complete());
+    assert_eq!(
+        sign_in.created_session_id(),
+        Some("sess_abc123xyz456def789")
+    );
+    assert!(clerk.session().unwrap().is_some());
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_passkey_mock.assert_async().await;
+    verify_passkey_mock.assert_async().await;
+    sign_in_mock.assert_async().await;
+    attempt_mock.assert_async().await;
+    touch_mock.assert_async().await;
+}
+
+const WEB3_ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+
+fn web3_verification(status: &str, message: Option<&str>) -> Value {
+    serde_json::json!({
+        "object": "verification_web3",
+        "status": status,
+        "strategy": "web3_metamask_signature",
+        "attempts": null,
+        "expire_at": 1731414303443i64,
+        "nonce": null,
+        "message": message
+    })
+}
+
+fn web3_sign_in_attempt(status: &str, created_session_id: Option<&str>) -> Value {
+    let mut sign_in = sign_in_attempt(status, created_session_id);
+    sign_in["identifier"] = WEB3_ADDRESS.into();
+    sign_in["supported_first_factors"] = serde_json::json!([{
+        "strategy": "web3_metamask_signature",
+        "safe_identifier": WEB3_ADDRESS,
+        "web3_wallet_id": "idn_web3abc123",
+        "primary": true
+    }]);
+    sign_in
+}
+
+fn web3_wallet(verification: Value) -> Value {
+    serde_json::json!({
+        "id": "idn_web3abc123",
+        "object": "web3_wallet",
+        "web3_wallet": WEB3_ADDRESS,
+        "verification": verification,
+        "created_at": 1731327903443i64,
+        "updated_at": 1731327903443i64
+    })
+}
+
+#[tokio::test]
+async fn test_web3_sign_in_and_link_wallet() {
+    use clerk_fapi_rs::web3::{LocalWeb3Signer, Web3Signer};
+    use mockito::Matcher;
+
+    let signer = LocalWeb3Signer::from_hex(
+        "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+    )
+    .unwrap();
+    let signature = signer.sign_message("Some data").await.unwrap();
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": not_logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .match_body(Matcher::UrlEncoded(
+            "identifier".to_string(),
+            WEB3_ADDRESS.to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": web3_sign_in_attempt("needs_first_factor", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let mut prepared = web3_sign_in_attempt("needs_first_factor", None);
+    prepared["first_factor_verification"] = web3_verification("unverified", Some("Some data"));
+    let prepare_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ins/sia_abc123/prepare_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded(
+                "strategy".to_string(),
+                "web3_metamask_signature".to_string(),
+            ),
+            Matcher::UrlEncoded("web3_wallet_id".to_string(), "idn_web3abc123".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": prepared,
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let attempt_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ins/sia_abc123/attempt_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded(
+                "strategy".to_string(),
+                "web3_metamask_signature".to_string(),
+            ),
+            Matcher::UrlEncoded("signature".to_string(), signature.clone()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": web3_sign_in_attempt("complete", Some("sess_abc123xyz456def789")),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let touch_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/touch?_is_native=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client()["sessions"][0],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_wallet_mock = server
+        .mock("POST", "/v1/me/web3_wallets")
+        .match_query(Matcher::Any)
+        .match_body(Matcher::UrlEncoded(
+            "web3_wallet".to_string(),
+            WEB3_ADDRESS.to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": web3_wallet(Value::Null),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let prepare_wallet_mock = server
+        .mock(
+            "POST",
+            "/v1/me/web3_wallets/idn_web3abc123/prepare_verification",
+        )
+        .match_query(Matcher::Any)
+        .match_body(Matcher::UrlEncoded(
+            "strategy".to_string(),
+            "web3_metamask_signature".to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": web3_wallet(web3_verification("unverified", Some("Some data"))),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let attempt_wallet_mock = server
+        .mock(
+            "POST",
+            "/v1/me/web3_wallets/idn_web3abc123/attempt_verification",
+        )
+        .match_query(Matcher::Any)
+        .match_body(Matcher::UrlEncoded("signature".to_string(), signature))
+        .with_body(
+            serde_json::json!({
+                "response": web3_wallet(web3_verification("verified", None)),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let sign_in = clerk.sign_in_with_web3(&signer).await.unwrap();
+    assert!(sign_in.is_complete());
+    assert!(clerk.session().unwrap().is_some());
+
+    let wallet = clerk.link_web3_wallet(&signer).await.unwrap();
+    assert_eq!(wallet.web3_wallet, WEB3_ADDRESS);
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_mock.assert_async().await;
+    prepare_mock.assert_async().await;
+    attempt_mock.assert_async().await;
+    touch_mock.assert_async().await;
+    create_wallet_mock.assert_async().await;
+    prepare_wallet_mock.assert_async().await;
+    attempt_wallet_mock.assert_async().await;
+}
+
+fn totp(verified: bool, backup_codes: Option<Vec<&str>>) -> Value {
+    serde_json::json!({
+        "object": "totp",
+        "id": "totp_abc123",
+        "secret": if verified { None } else { Some("JBSWY3DPEHPK3PXP") },
+        "uri": if verified {
+            None
+        } else {
+            Some("otpauth://totp/Example:john.doe%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example&algorithm=SHA1&digits=6&period=30")
+        },
+        "verified": verified,
+        "backup_codes": backup_codes,
+        "created_at": 1731327903443i64,
+        "updated_at": 1731327903443i64
+    })
+}
+
+#[tokio::test]
+async fn test_enroll_totp() {
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let create_mock = server
+        .mock("POST", "/v1/me/totp")
+        .match_query(Matcher::Any)
+        .with_body(
+            serde_json::json!({
+                "response": totp(false, None),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let verify_mock = server
+        .mock("POST", "/v1/me/totp/attempt_verification")
+        .match_query(Matcher::Any)
+        .match_body(Matcher::Regex("^code=[0-9]{6}$".to_string()))
+        .with_body(
+            serde_json::json!({
+                "response": totp(true, Some(vec!["backup1", "backup2"])),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let enrollment = clerk.enroll_totp().await.unwrap();
+    assert_eq!(enrollment.uri().issuer.as_deref(), Some("Example"));
+    assert_eq!(enrollment.uri().account_name, "john.doe@example.com");
+    assert_eq!(enrollment.uri().secret, "JBSWY3DPEHPK3PXP");
+    assert!(enrollment.uri().qr_svg().unwrap().contains("<svg"));
+
+    let backup_codes = enrollment.verify_with_generated_code().await.unwrap();
+    assert_eq!(backup_codes, vec!["backup1", "backup2"]);
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    create_mock.assert_async().await;
+    verify_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_paginate_organization_memberships() {
+    use futures::TryStreamExt;
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_status(200)
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let memberships = logged_in_client()["sessions"][0]["user"]["organization_memberships"].clone();
+    let membership = |i: usize| memberships[i % 2].clone();
+    let mut page_mock = |offset: &str, data: Vec<Value>| {
+        server
+            .mock("GET", "/v1/organizations/org_456abc789xyz123/memberships")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("limit".to_string(), "2".to_string()),
+                Matcher::UrlEncoded("offset".to_string(), offset.to_string()),
+                Matcher::UrlEncoded("paginated".to_string(), "true".to_string()),
+                Matcher::UrlEncoded("role".to_string(), "org:admin".to_string()),
+            ]))
+            .with_body(
+                serde_json::json!({
+                    "response": { "data": data, "total_count": 4 },
+                    "client": logged_in_client()
+                })
+                .to_string(),
+            )
+            .with_header("content-type", "application/json")
+            .expect(1)
+    };
+    let first_page_mock = page_mock("0", vec![membership(0), membership(1)])
+        .create_async()
+        .await;
+    let second_page_mock = page_mock("2", vec![membership(2), membership(3)])
+        .create_async()
+        .await;
+    // The total count tells the list ended with the full second page
+    let empty_page_mock = page_mock("4", vec![]).expect(0).create_async().await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let paginator = clerk
+        .organization_memberships("org_456abc789xyz123", None, Some("org:admin"))
+        .page_size(2);
+    let ids: Vec<String> = paginator
+        .stream()
+        .map_ok(|m| m.id)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        ids,
+        vec![
+            "orgmem_123xyz789abc456",
+            "orgmem_789def123xyz456",
+            "orgmem_123xyz789abc456",
+            "orgmem_789def123xyz456"
+        ]
+    );
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    first_page_mock.assert_async().await;
+    second_page_mock.assert_async().await;
+    empty_page_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_fake_fapi() {
+    use clerk_fapi_rs::sign_in::{FirstFactor, PrepareFirstFactor};
+    use clerk_fapi_rs::sign_up::{PrepareVerification, SignUpParams, Verification};
+    use clerk_fapi_rs::testing::{FakeFapi, FakeOrganization, FakeUser, TEST_CODE};
+
+    let fake = FakeFapi::builder()
+        .user(FakeUser::new("jane@example.com").password("correct horse"))
+        .organization(FakeOrganization::new("Example Corp").member("jane@example.com", "org:admin"))
+        .start()
+        .unwrap();
+    let jane_id = fake.user_id("jane@example.com").unwrap();
+
+    let clerk = fake.clerk();
+    clerk.load().await.unwrap();
+    assert!(clerk.session().unwrap().is_none());
+    let first_authorization = clerk.get_client_authorization_header();
+    assert!(first_authorization.is_some());
+
+    let mut sign_in = clerk.sign_in("jane@example.com").await.unwrap();
+    sign_in
+        .prepare_first_factor(PrepareFirstFactor::EmailCode)
+        .await
+        .unwrap();
+    let err = sign_in
+        .attempt_first_factor(FirstFactor::EmailCode("000000".to_string()))
+        .await
+        .unwrap_err();
+    assert!(err
+        .api_error()
+        .is_some_and(|e| e.is_code("form_code_incorrect")));
+    sign_in
+        .attempt_first_factor(FirstFactor::EmailCode(TEST_CODE.to_string()))
+        .await
+        .unwrap();
+    assert!(sign_in.is_complete());
+    assert_eq!(clerk.user().unwrap().unwrap().id, jane_id);
+    // The client keeps the same identity with a rotated header
+    assert_ne!(clerk.get_client_authorization_header(), first_authorization);
+
+    let token = clerk.get_token(None, None).await.unwrap().unwrap();
+    let claims = clerk.verify_token(&token).await.unwrap();
+    assert_eq!(claims.sub, jane_id);
+    assert_eq!(claims.org_id, None);
+
+    clerk
+        .set_active(None, Some("example-corp".to_string()))
+        .await
+        .unwrap();
+    let organization = clerk.organization().unwrap().unwrap();
+    assert_eq!(organization.slug, "example-corp");
+    let token = clerk.get_token(None, None).await.unwrap().unwrap();
+    let claims = clerk.verify_token(&token).await.unwrap();
+    assert_eq!(claims.org_id, Some(organization.id));
+    assert_eq!(claims.org_role.as_deref(), Some("org:admin"));
+
+    let organization = clerk
+        .get_fapi_client()
+        .create_organization(Some("Side Project"))
+        .await
+        .unwrap();
+    assert_eq!(organization.slug, "side-project");
+    let memberships = clerk
+        .user_organization_memberships()
+        .collect_all(10)
+        .await
+        .unwrap();
+    assert_eq!(memberships.len(), 2);
+
+    // Another client is signed out and can sign in with the password
+    let other = fake.clerk();
+    other.load().await.unwrap();
+    assert!(other.session().unwrap().is_none());
+    let err = other.sign_in("nobody@example.com").await.unwrap_err();
+    assert!(err
+        .api_error()
+        .is_some_and(|e| e.is_code("form_identifier_not_found")));
+    let mut sign_in = other.sign_in("jane@example.com").await.unwrap();
+    sign_in
+        .attempt_first_factor(FirstFactor::Password("correct horse".to_string()))
+        .await
+        .unwrap();
+    assert_eq!(other.user().unwrap().unwrap().id, jane_id);
+    assert_eq!(fake.active_session_count(), 2);
+
+    clerk.sign_out(None).await.unwrap();
+    assert!(clerk.session().unwrap().is_none());
+    assert_eq!(fake.active_session_count(), 1);
+
+    let mut sign_up = clerk
+        .sign_up(
+            SignUpParams::default()
+                .email_address("john@example.com")
+                .password("hunter2 hunter2"),
+        )
+        .await
+        .unwrap();
+    assert!(!sign_up.is_complete());
+    sign_up
+        .prepare_verification(PrepareVerification::EmailCode)
+        .await
+        .unwrap();
+    sign_up
+        .attempt_verification(Verification::EmailCode(TEST_CODE.to_string()))
+        .await
+        .unwrap();
+    assert!(sign_up.is_complete());
+    assert_eq!(
+        clerk.user().unwrap().unwrap().id,
+        fake.user_id("john@example.com").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_multi_session() {
+    use clerk_fapi_rs::sessions::SingleSessionSignIn;
+    use clerk_fapi_rs::sign_in::{ClerkSignInError, FirstFactor, SignInFlow};
+    use clerk_fapi_rs::testing::{FakeFapi, FakeUser};
+
+    async fn sign_in(clerk: &Clerk, email: &str) -> Result<(), ClerkSignInError> {
+        let mut sign_in = clerk.sign_in(email).await?;
+        sign_in
+            .attempt_first_factor(FirstFactor::Password("correct horse".to_string()))
+            .await?;
+        assert!(sign_in.is_complete());
+        Ok(())
+    }
+
+    let mut environment = FakeFapi::default_environment();
+    environment
+        .auth_config
+        .as_mut()
+        .unwrap()
+        .single_session_mode = false;
+    let fake = FakeFapi::builder()
+        .environment(environment)
+        .user(FakeUser::new("jane@example.com").password("correct horse"))
+        .user(FakeUser::new("john@example.com").password("correct horse"))
+        .start()
+        .unwrap();
+    let jane_id = fake.user_id("jane@example.com").unwrap();
+    let john_id = fake.user_id("john@example.com").unwrap();
+
+    let clerk = fake.clerk();
+    clerk.load().await.unwrap();
+    assert!(!clerk.single_session_mode().unwrap());
+    sign_in(&clerk, "jane@example.com").await.unwrap();
+    let jane_session = clerk.session().unwrap().unwrap().id;
+    sign_in(&clerk, "john@example.com").await.unwrap();
+    assert_eq!(clerk.user().unwrap().unwrap().id, john_id);
+
+    let sessions = clerk.sessions().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions.iter().all(|s| s.is_active()));
+    let current = sessions.iter().find(|s| s.is_current).unwrap();
+    assert_eq!(current.user.as_ref().unwrap().id, john_id);
+
+    // Tokens for the session that isn't active
+    let token = clerk
+        .get_session_token(&jane_session, None, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(clerk.verify_token(&token).await.unwrap().sub, jane_id);
+    assert_eq!(
+        clerk
+            .get_session_token("sess_unknown", None, None)
+            .await
+            .unwrap(),
+        None
+    );
+
+    clerk.switch_session(&jane_session).await.unwrap();
+    assert_eq!(clerk.user().unwrap().unwrap().id, jane_id);
+    assert!(clerk.switch_session("sess_unknown").await.is_err());
+
+    // Signing out the active session moves to the remaining one
+    clerk.sign_out(Some(jane_session.clone())).await.unwrap();
+    assert_eq!(clerk.user().unwrap().unwrap().id, john_id);
+    assert!(!clerk
+        .sessions()
+        .unwrap()
+        .iter()
+        .any(|s| s.id == jane_session && s.is_active()));
+    assert!(clerk.switch_session(&jane_session).await.is_err());
+
+    // Single session mode blocks or replaces the active session
+    let fake = FakeFapi::builder()
+        .user(FakeUser::new("jane@example.com").password("correct horse"))
+        .user(FakeUser::new("john@example.com").password("correct horse"))
+        .start()
+        .unwrap();
+    let clerk = fake.clerk();
+    clerk.load().await.unwrap();
+    assert!(clerk.single_session_mode().unwrap());
+    sign_in(&clerk, "jane@example.com").await.unwrap();
+    let err = sign_in(&clerk, "john@example.com").await.unwrap_err();
+    assert!(matches!(err, ClerkSignInError::SessionExists(_)));
+    // Sign ups transferred to a sign in, example by OAuth, too
+    let err = SignInFlow::create_with_transfer(&clerk).await.unwrap_err();
+    assert!(matches!(err, ClerkSignInError::SessionExists(_)));
+
+    let clerk = Clerk::new(
+        fake.configuration()
+            .with_single_session_sign_in(SingleSessionSignIn::Replace),
+    );
+    clerk.load().await.unwrap();
+    sign_in(&clerk, "jane@example.com").await.unwrap();
+    sign_in(&clerk, "john@example.com").await.unwrap();
+    assert_eq!(
+        clerk.user().unwrap().unwrap().id,
+        fake.user_id("john@example.com").unwrap()
+    );
+    // The blocked client still has its session
+    assert_eq!(fake.active_session_count(), 2);
+}
+
+#[tokio::test]
+async fn test_organization_lifecycle() {
+    use clerk_fapi_rs::organizations::ClerkOrganizationError;
+    use clerk_fapi_rs::sign_in::FirstFactor;
+    use clerk_fapi_rs::testing::{FakeFapi, FakeOrganization, FakeUser};
+    use std::sync::atomic::AtomicUsize;
+
+    let fake = FakeFapi::builder()
+        .user(FakeUser::new("jane@example.com").password("correct horse"))
+        .organization(FakeOrganization::new("Example Corp").member("jane@example.com", "org:admin"))
+        .start()
+        .unwrap();
+    let clerk = fake.clerk();
+    clerk.load().await.unwrap();
+    let mut sign_in = clerk.sign_in("jane@example.com").await.unwrap();
+    sign_in
+        .attempt_first_factor(FirstFactor::Password("correct horse".to_string()))
+        .await
+        .unwrap();
+
+    let emits = Arc::new(AtomicUsize::new(0));
+    let emits_clone = emits.clone();
+    clerk.add_listener(move |_, _, _, _| {
+        emits_clone.fetch_add(1, Ordering::SeqCst);
+    });
+    emits.store(0, Ordering::SeqCst);
+
+    let organization = clerk.switch_organization("example-corp").await.unwrap();
+    assert_eq!(organization.slug, "example-corp");
+    assert_eq!(clerk.organization().unwrap().unwrap().id, organization.id);
+    assert_eq!(emits.load(Ordering::SeqCst), 1);
+
+    clerk.clear_active_organization().await.unwrap();
+    assert!(clerk.organization().unwrap().is_none());
+    assert_eq!(emits.load(Ordering::SeqCst), 2);
+
+    // Creating makes two requests but emits once
+    let created = clerk.create_organization("Side Project").await.unwrap();
+    assert_eq!(clerk.organization().unwrap().unwrap().id, created.id);
+    assert_eq!(emits.load(Ordering::SeqCst), 3);
+    let token = clerk.get_token(None, None).await.unwrap().unwrap();
+    let claims = clerk.verify_token(&token).await.unwrap();
+    assert_eq!(claims.org_id, Some(created.id.clone()));
+
+    clerk.leave_organization(&created.id).await.unwrap();
+    assert!(clerk.organization().unwrap().is_none());
+    assert_eq!(emits.load(Ordering::SeqCst), 4);
+    let memberships = clerk.user().unwrap().unwrap().organization_memberships;
+    assert_eq!(memberships.unwrap().len(), 1);
+
+    let err = clerk.switch_organization(&created.id).await.unwrap_err();
+    assert!(matches!(err, ClerkOrganizationError::NoMatchFound(_)));
+}
+
+fn role(key: &str, name: &str) -> Value {
+    serde_json::json!({
+        "object": "role",
+        "id": format!("role_{name}"),
+        "name": name,
+        "key": key,
+        "description": "",
+        "is_creator_eligible": true,
+        "permissions": [],
+        "created_at": 1729249255195i64,
+        "updated_at": 1729249255195i64
+    })
+}
+
+fn invitation(email_address: &str) -> Value {
+    serde_json::json!({
+        "object": "organization_invitation",
+        "id": format!("orginv_{}", email_address.replace(['@', '.'], "_")),
+        "email_address": email_address,
+        "role": "org:member",
+        "role_name": "Member",
+        "organization_id": "org_456abc789xyz123",
+        "status": "pending",
+        "public_metadata": {},
+        "url": null,
+        "expires_at": null,
+        "created_at": 1729249255195i64,
+        "updated_at": 1729249255195i64
+    })
+}
+
+#[tokio::test]
+async fn test_organization_admin() {
+    use clerk_fapi_rs::organization_admin::{ClerkOrganizationAdminError, DomainEnrollmentMode};
+    use mockito::Matcher;
+
+    let mut server = Server::new_async().await;
+
+    let client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client(),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+
+    let roles_mock = server
+        .mock("GET", "/v1/organizations/org_456abc789xyz123/roles")
+        .match_query(Matcher::Any)
+        .with_body(
+            serde_json::json!({
+                "response": {
+                    "data": [role("org:admin", "Admin"), role("org:member", "Member")],
+                    "total_count": 2
+                },
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // One of the addresses is rejected so the bulk request fails
+    let bulk_mock = server
+        .mock(
+            "POST",
+            "/v1/organizations/org_456abc789xyz123/invitations/bulk",
+        )
+        .match_query(Matcher::Any)
+        .with_status(422)
+        .with_body(
+            serde_json::json!({
+                "errors": [{
+                    "code": "form_param_format_invalid",
+                    "message": "is invalid",
+                    "long_message": "Email address is invalid",
+                    "meta": { "param_name": "email_address" }
+                }]
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // Then the bulk request succeeds without one of the addresses
+    let partial_bulk_mock = server
+        .mock(
+            "POST",
+            "/v1/organizations/org_456abc789xyz123/invitations/bulk",
+        )
+        .match_query(Matcher::Any)
+        .with_body(
+            serde_json::json!({
+                "response": [invitation("jane@example.com")],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // And then it's forbidden
+    let forbidden_bulk_mock = server
+        .mock(
+            "POST",
+            "/v1/organizations/org_456abc789xyz123/invitations/bulk",
+        )
+        .match_query(Matcher::Any)
+        .with_status(403)
+        .with_body(
+            serde_json::json!({
+                "errors": [{
+                    "code": "not_allowed_access",
+                    "message": "not allowed",
+                    "long_message": "You are not authorized to perform this request"
+                }]
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let invite_mock = server
+        .mock("POST", "/v1/organizations/org_456abc789xyz123/invitations")
+        .match_query(Matcher::Any)
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("email_address".to_string(), "jane@example.com".to_string()),
+            Matcher::UrlEncoded("role".to_string(), "org:member".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": invitation("jane@example.com"),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let invalid_invite_mock = server
+        .mock("POST", "/v1/organizations/org_456abc789xyz123/invitations")
+        .match_query(Matcher::Any)
+        .match_body(Matcher::UrlEncoded(
+            "email_address".to_string(),
+            "not-an-email".to_string(),
+        ))
+        .with_status(422)
+        .with_body(
+            serde_json::json!({
+                "errors": [{
+                    "code": "form_param_format_invalid",
+                    "message": "is invalid",
+                    "long_message": "Email address is invalid",
+                    "meta": { "param_name": "email_address" }
+                }]
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let enrollment_mock = server
+        .mock(
+            "POST",
+            "/v1/organizations/org_456abc789xyz123/domains/dmn_123/update_enrollment_mode",
+        )
+        .match_query(Matcher::Any)
+        .match_body(Matcher::UrlEncoded(
+            "enrollment_mode".to_string(),
+            "automatic_suggestion".to_string(),
+        ))
+        .with_body(
+            serde_json::json!({
+                "response": {
+                    "object": "organization_domain",
+                    "id": "dmn_123",
+                    "organization_id": "org_456abc789xyz123",
+                    "name": "example.com",
+                    "enrollment_mode": "automatic_suggestion",
+                    "affiliation_email_address": null,
+                    "verification": null,
+                    "total_pending_invitations": 0,
+                    "total_pending_suggestions": 0,
+                    "created_at": 1729249255195i64,
+                    "updated_at": 1729249255195i64
+                },
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let admin = clerk.organization_admin("org_456abc789xyz123");
+    assert!(admin.has_permission("sys_memberships:manage").unwrap());
+
+    // The role prefix is optional, the rows are kept in order
+    let results = admin
+        .invite_many(&["jane@example.com", "not-an-email"], "member")
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].email_address, "jane@example.com");
+    assert_eq!(
+        results[0].result.as_ref().unwrap().email_address,
+        "jane@example.com"
+    );
+    assert!(results[1]
+        .result
+        .as_ref()
+        .unwrap_err()
+        .api_error()
+        .is_some_and(|e| e.is_code("form_param_format_invalid")));
+
+    // Addresses missing from the bulk response are not invited again
+    let results = admin
+        .invite_many(&["jane@example.com", "john@example.com"], "member")
+        .await
+        .unwrap();
+    assert!(results[0].result.is_ok());
+    assert!(matches!(
+        &results[1].result,
+        Err(ClerkOrganizationAdminError::NotInvited(e)) if e == "john@example.com"
+    ));
+
+    // Auth failures are not retried one by one
+    let err = admin
+        .invite_many(&["jane@example.com", "john@example.com"], "member")
+        .await
+        .unwrap_err();
+    assert_eq!(err.api_error().and_then(|e| e.status()), Some(403));
+
+    // Roles are validated before calling the API
+    let err = admin
+        .update_member_role("user_123", "org:owner")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClerkOrganizationAdminError::UnknownRole(_)));
+
+    let domain = admin
+        .set_domain_enrollment_mode("dmn_123", DomainEnrollmentMode::AutomaticSuggestion, false)
+        .await
+        .unwrap();
+    assert_eq!(domain.enrollment_mode, "automatic_suggestion");
+
+    // Permissions are checked before calling the API
+    let other = clerk.organization_admin("org_not_a_member");
+    assert!(!other.has_permission("org:sys_memberships:manage").unwrap());
+    let err = other
+        .invite("john@example.com", "org:member")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClerkOrganizationAdminError::MissingPermission(_)
+    ));
+
+    client_mock.assert_async().await;
+    env_mock.assert_async().await;
+    roles_mock.assert_async().await;
+    bulk_mock.assert_async().await;
+    partial_bulk_mock.assert_async().await;
+    forbidden_bulk_mock.assert_async().await;
+    invite_mock.assert_async().await;
+    invalid_invite_mock.assert_async().await;
+    enrollment_mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_capabilities() {
+    use clerk_fapi_rs::capabilities::SocialProvider;
+    use clerk_fapi_rs::models::user_settings_sign_up::Mode;
+    use clerk_fapi_rs::models::UserSettingsSocial;
+    use clerk_fapi_rs::testing::FakeFapi;
+
+    let social = |strategy: &str, name: Option<&str>, authenticatable: bool| {
+        Some(Box::new(UserSettingsSocial {
+            enabled: true,
+            authenticatable,
+            strategy: strategy.to_string(),
+            name: name.map(str::to_string),
+            logo_url: Some(Some(format!("https://img.clerk.com/{strategy}.png"))),
+            ..Default::default()
+        }))
+    };
+
+    let mut environment = FakeFapi::default_environment();
+    let user_settings = environment.user_settings.as_mut().unwrap();
+    user_settings.social.oauth_google = social("oauth_google", Some("Google"), true);
+    user_settings.social.oauth_github = social("oauth_github", None, true);
+    user_settings.social.oauth_slack = social("oauth_slack", Some("Slack"), false);
+    user_settings.attributes.passkey.enabled = true;
+    user_settings.attributes.passkey.used_for_first_factor = true;
+    user_settings.attributes.passkey.first_factors = vec!["passkey".to_string()];
+    user_settings.attributes.authenticator_app.enabled = true;
+    user_settings
+        .attributes
+        .authenticator_app
+        .used_for_second_factor = true;
+    user_settings.attributes.authenticator_app.second_factors = vec!["totp".to_string()];
+    user_settings.sign_in.second_factor.required = true;
+    user_settings.sign_up.mode = Mode::Waitlist;
+    environment.maintenance_mode = Some(true);
+
+    let fake = FakeFapi::builder()
+        .environment(environment)
+        .start()
+        .unwrap();
+    let clerk = fake.clerk();
+    assert!(clerk.capabilities().is_err());
+    clerk.load().await.unwrap();
+
+    let capabilities = clerk.capabilities().unwrap();
+    assert_eq!(
+        capabilities.enabled_first_factors(),
+        vec!["email_code", "passkey", "password"]
+    );
+    assert_eq!(capabilities.enabled_second_factors(), vec!["totp"]);
+    assert_eq!(
+        capabilities.enabled_social_providers(),
+        vec![
+            SocialProvider {
+                strategy: "oauth_github".to_string(),
+                name: "github".to_string(),
+                logo_url: Some("https://img.clerk.com/oauth_github.png".to_string()),
+            },
+            SocialProvider {
+                strategy: "oauth_google".to_string(),
+                name: "Google".to_string(),
+                logo_url: Some("https://img.clerk.com/oauth_google.png".to_string()),
+            },
+        ]
+    );
+    assert!(capabilities.requires_mfa());
+    assert!(capabilities.organizations_enabled());
+    assert!(capabilities.passkeys_enabled());
+    assert!(capabilities.waitlist_mode());
+    assert!(capabilities.single_session_mode());
+    assert!(capabilities.is_in_maintenance());
+
+    // Without user settings the auth config is used
+    let mut environment = FakeFapi::default_environment();
+    environment.user_settings = None;
+    environment.organization_settings = None;
+    let fake = FakeFapi::builder()
+        .environment(environment)
+        .start()
+        .unwrap();
+    let clerk = fake.clerk();
+    clerk.load().await.unwrap();
+
+    let capabilities = clerk.capabilities().unwrap();
+    assert_eq!(
+        capabilities.enabled_first_factors(),
+        vec!["email_code", "password"]
+    );
+    assert!(capabilities.enabled_social_providers().is_empty());
+    assert!(!capabilities.requires_mfa());
+    assert!(!capabilities.organizations_enabled());
+    assert!(!capabilities.passkeys_enabled());
+    assert!(!capabilities.waitlist_mode());
+    assert!(!capabilities.is_in_maintenance());
+}
+
+#[tokio::test]
+async fn test_email_link_sign_in() {
+    use clerk_fapi_rs::email_link::{ClerkEmailLinkError, EmailLinkAttempt};
+    use mockito::Matcher;
+    use std::time::Duration;
+
+    fn email_link_sign_in(verification_status: &str, created_session_id: Option<&str>) -> Value {
+        let mut sign_in = sign_in_attempt(
+            if created_session_id.is_some() {
+                "complete"
+            } else {
+                "needs_first_factor"
+            },
+            created_session_id,
+        );
+        sign_in["supported_first_factors"] = serde_json::json!([{
+            "strategy": "email_link",
+            "safe_identifier": "j***@example.com",
+            "email_address_id": "idn_abc123",
+            "primary": true
+        }]);
+        sign_in["first_factor_verification"] = serde_json::json!({
+            "object": "verification_email_link",
+            "status": verification_status,
+            "strategy": "email_link",
+            "attempts": null,
+            "expire_at": 1731414303443i64,
+            "verified_at_client": null
+        });
+        sign_in
+    }
+
+    let mut server = Server::new_async().await;
+    let _client_mock = server
+        .mock("GET", "/v1/client?_is_native=1")
+        .with_body(
+            serde_json::json!({ "response": not_logged_in_client(), "client": null }).to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+    let _env_mock = server
+        .mock("GET", "/v1/environment?_is_native=1")
+        .with_body(get_env_data())
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+    let create_mock = server
+        .mock("POST", "/v1/client/sign_ins?_is_native=1")
+        .with_body(
+            serde_json::json!({
+                "response": email_link_sign_in("unverified", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(3)
+        .create_async()
+        .await;
+    let prepare_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sign_ins/sia_abc123/prepare_first_factor?_is_native=1",
+        )
+        .match_body(Matcher::AllOf(vec![
+            Matcher::UrlEncoded("strategy".to_string(), "email_link".to_string()),
+            Matcher::UrlEncoded("email_address_id".to_string(), "idn_abc123".to_string()),
+            Matcher::UrlEncoded("redirect_url".to_string(), "myapp://email-link".to_string()),
+        ]))
+        .with_body(
+            serde_json::json!({
+                "response": email_link_sign_in("unverified", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(3)
+        .create_async()
+        .await;
+    // Not opened on the first poll
+    let unverified_mock = server
+        .mock("GET", "/v1/client/sign_ins/sia_abc123?_is_native=1")
+        .with_body(
+            serde_json::json!({
+                "response": email_link_sign_in("unverified", None),
+                "client": null
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+    let verified_mock = server
+        .mock("GET", "/v1/client/sign_ins/sia_abc123?_is_native=1")
+        .with_body(
+            serde_json::json!({
+                "response": email_link_sign_in("verified", Some("sess_abc123xyz456def789")),
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .create_async()
+        .await;
+    let touch_mock = server
+        .mock(
+            "POST",
+            "/v1/client/sessions/sess_abc123xyz456def789/touch?_is_native=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "response": logged_in_client()["sessions"][0],
+                "client": logged_in_client()
+            })
+            .to_string(),
+        )
+        .with_header("content-type", "application/json")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = ClerkFapiConfiguration::new(
+        "pk_test_Y2xlcmsuZXhhbXBsZS5jb20k".to_string(),
+        Some(server.url()),
+        None,
+    )
+    .unwrap();
+    let clerk = Clerk::new(config);
+    clerk.load().await.unwrap();
+
+    let mut flows = Vec::new();
+    for _ in 0..3 {
+        let flow = clerk
+            .sign_in_with_email_link("john.doe@example.com", "myapp://email-link")
+            .await
+            .unwrap()
+            .with_poll_interval(Duration::from_millis(10), Duration::from_millis(20));
+        assert!(!flow.attempt().is_complete());
+        flows.push(flow);
+    }
+
+    // A failed redirect ends the flow without polling
+    let result = flows
+        .pop()
+        .unwrap()
+        .complete("myapp://email-link?__clerk_status=expired")
+        .await;
+    assert!(matches!(
+        result,
+        Err(ClerkEmailLinkError::VerificationFailed(status)) if status == "expired"
+    ));
+
+    // Cancelling stops waiting
+    let flow = flows
+        .pop()
+        .unwrap()
+        .with_poll_interval(Duration::from_secs(60), Duration::from_secs(60));
+    let cancel = flow.cancel_handle();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        cancel.cancel();
+    });
+    let result = flow.wait(Duration::from_secs(120)).await;
+    assert!(matches!(result, Err(ClerkEmailLinkError::Cancelled)));
+
+    // The link is opened on another device on the second poll
+    let attempt = flows
+        .pop()
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert!(matches!(attempt, EmailLinkAttempt::SignIn(_)));
+    assert!(attempt.is_complete());
+    assert_eq!(
+        attempt.created_session_id(),
+        Some("sess_abc123xyz456def789")
+    );
+    assert!(clerk.session().unwrap().is_some());
+
+    create_mock.assert_async().await;
+    prepare_mock.assert_async().await;
+    unverified_mock.assert_async().await;
+    verified_mock.assert_async().await;
+    touch_mock.assert_async().await;
+}